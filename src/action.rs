@@ -6,6 +6,58 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+/// Action category (the high byte of an action code)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum ActionCategory {
+    System = 0x00,
+    Response = 0x01,
+    Query = 0x02,
+    Knowledge = 0x03,
+    Skill = 0x04,
+    Emotion = 0x05,
+    Template = 0x06,
+    Chain = 0x07,
+}
+
+impl ActionCategory {
+    /// All categories, in code order
+    pub const ALL: [Self; 8] = [
+        Self::System,
+        Self::Response,
+        Self::Query,
+        Self::Knowledge,
+        Self::Skill,
+        Self::Emotion,
+        Self::Template,
+        Self::Chain,
+    ];
+
+    /// Parse from a category byte
+    pub fn from_byte(b: u8) -> Option<Self> {
+        Self::ALL.get(b as usize).copied()
+    }
+
+    /// Convert to the category byte
+    pub fn to_byte(self) -> u8 {
+        self as u8
+    }
+
+    /// Human-readable name
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::System => "system",
+            Self::Response => "response",
+            Self::Query => "query",
+            Self::Knowledge => "knowledge",
+            Self::Skill => "skill",
+            Self::Emotion => "emotion",
+            Self::Template => "template",
+            Self::Chain => "chain",
+        }
+    }
+}
+
 /// Action code (2 bytes)
 ///
 /// Specifies what operation to perform. Actions are organized into categories:
@@ -17,7 +69,8 @@ use std::fmt;
 /// - `0x04xx` - Skill actions (CALCULATE, SET_TIMER, KNOWLEDGE_SEARCH)
 /// - `0x05xx` - Emotion actions (EMPATHY, CONCERN, ENCOURAGEMENT, REASSURE)
 /// - `0x06xx` - Template actions (TEMPLATE_LOAD, TEMPLATE_FILL)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// - `0x07xx` - Chain actions (CHAIN, FORK, MERGE)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct Action(pub u16);
 
@@ -106,6 +159,44 @@ impl Action {
     /// Merge results from TRMs
     pub const MERGE: Self = Self(0x0702);
 
+    /// All named actions, in code order
+    pub const ALL: [Self; 34] = [
+        Self::NOP,
+        Self::HALT,
+        Self::ERROR,
+        Self::STATUS,
+        Self::GREET,
+        Self::CONFIRM,
+        Self::DENY,
+        Self::EXPLAIN,
+        Self::CLARIFY,
+        Self::APOLOGIZE,
+        Self::THANK,
+        Self::RESPOND,
+        Self::ASK,
+        Self::REQUEST,
+        Self::SEARCH,
+        Self::RETRIEVE,
+        Self::DEFINE,
+        Self::DESCRIBE,
+        Self::COMPARE,
+        Self::SUMMARIZE,
+        Self::EXPLAIN_HOW,
+        Self::EXPLAIN_WHY,
+        Self::CALCULATE,
+        Self::SET_TIMER,
+        Self::KNOWLEDGE_SEARCH,
+        Self::EMPATHY,
+        Self::CONCERN,
+        Self::ENCOURAGEMENT,
+        Self::REASSURE,
+        Self::TEMPLATE_LOAD,
+        Self::TEMPLATE_FILL,
+        Self::CHAIN,
+        Self::FORK,
+        Self::MERGE,
+    ];
+
     /// Create from raw u16 value
     #[inline]
     pub const fn from_u16(value: u16) -> Self {
@@ -124,6 +215,12 @@ impl Action {
         (self.0 >> 8) as u8
     }
 
+    /// Get the category, if the high byte is a defined one
+    #[inline]
+    pub fn action_category(&self) -> Option<ActionCategory> {
+        ActionCategory::from_byte(self.category())
+    }
+
     /// Get the subcategory byte (low byte)
     #[inline]
     pub const fn subcategory(&self) -> u8 {
@@ -172,12 +269,28 @@ impl Action {
         self.0 >= 0x0600 && self.0 <= 0x06FF
     }
 
+    /// Check if this action retrieves documents (SEARCH, RETRIEVE, KNOWLEDGE_SEARCH)
+    #[inline]
+    pub const fn is_retrieval(&self) -> bool {
+        matches!(
+            *self,
+            Self::SEARCH | Self::RETRIEVE | Self::KNOWLEDGE_SEARCH
+        )
+    }
+
     /// Check if this is a chain action (0x07xx)
     #[inline]
     pub const fn is_chain(&self) -> bool {
         self.0 >= 0x0700 && self.0 <= 0x07FF
     }
 
+    /// Look up a named action by its [`name`](Self::name) (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.name().eq_ignore_ascii_case(name))
+    }
+
     /// Get the human-readable name for this action
     pub fn name(&self) -> &'static str {
         match *self {
@@ -252,6 +365,16 @@ mod tests {
         assert!(Action::EMPATHY.is_emotion());
         assert!(Action::TEMPLATE_LOAD.is_template());
         assert!(Action::CHAIN.is_chain());
+
+        assert_eq!(Action::HALT.action_category(), Some(ActionCategory::System));
+        assert_eq!(Action::MERGE.action_category(), Some(ActionCategory::Chain));
+        assert_eq!(Action::from_u16(0x0800).action_category(), None);
+        for category in ActionCategory::ALL {
+            assert_eq!(
+                ActionCategory::from_byte(category.to_byte()),
+                Some(category)
+            );
+        }
     }
 
     #[test]
@@ -267,6 +390,13 @@ mod tests {
         assert_eq!(Action::GREET.name(), "GREET");
         assert_eq!(Action::CALCULATE.name(), "CALCULATE");
         assert_eq!(Action::from_u16(0xFFFF).name(), "UNKNOWN");
+
+        for action in Action::ALL {
+            assert_ne!(action.name(), "UNKNOWN");
+            assert_eq!(Action::from_name(action.name()), Some(action));
+        }
+        assert_eq!(Action::from_name("set_timer"), Some(Action::SET_TIMER));
+        assert_eq!(Action::from_name("UNKNOWN"), None);
     }
 
     #[test]
@@ -0,0 +1,395 @@
+//! Reference arithmetic semantics for calculator payloads
+//!
+//! Every consumer of [`Op`] should agree on edge cases, so the canonical
+//! evaluation lives here. [`CalcPayload::evaluate`] and
+//! [`ExprPayload::evaluate`](crate::ExprPayload::evaluate) both use it.
+//!
+//! ```rust
+//! use frame_isa::{CalcError, CalcPayload, Op};
+//!
+//! assert_eq!(CalcPayload::new(Op::Mod, -7.0, 3.0).evaluate(), Ok(-1.0));
+//! assert_eq!(CalcPayload::new(Op::Div, 1.0, 0.0).evaluate(), Err(CalcError::DivisionByZero));
+//! ```
+//!
+//! Errors take precedence in the order listed:
+//!
+//! - A NaN operand is rejected with [`CalcError::NotANumber`]
+//! - An infinite operand is rejected with [`CalcError::Infinite`]
+//! - Division or modulo by zero (including `-0.0`) is [`CalcError::DivisionByZero`]
+//! - Results that leave the real numbers (e.g. `sqrt(-1)`) are [`CalcError::Domain`]
+//! - Finite operands producing an infinite result are [`CalcError::Overflow`]
+//! - `Mod` is the truncated remainder: the result has the sign of the dividend
+//!   (`-7 % 3 == -1`), matching Rust's `%` and C's `fmod`
+//! - `Pow`: `0 ^ 0 == 1`, zero to a negative power is a division by zero, and a
+//!   negative base with a non-integer exponent is a domain error
+//! - `Round` rounds half away from zero (`round(-2.5) == -3`)
+//! - `Log` is base 10; `Log` and `Ln` of zero or a negative number are domain errors
+//! - `PercentOf` computes `a` percent of `b` (`pct(15, 80) == 12`)
+//! - `Factorial` is defined for non-negative integers up to 170 (larger overflows)
+//! - Trigonometric functions take and return radians; `Asin`/`Acos` outside
+//!   `[-1, 1]` are domain errors
+
+use crate::{CalcPayload, Op};
+use thiserror::Error;
+
+impl CalcPayload {
+    /// Evaluate with the reference semantics (see [module docs](self))
+    pub fn evaluate(&self) -> Result<f64, CalcError> {
+        self.op.apply(self.a, self.b)
+    }
+}
+
+impl Op {
+    /// Apply the operation to operands (`b` is ignored for unary operations)
+    pub fn apply(self, a: f64, b: f64) -> Result<f64, CalcError> {
+        check_operand(a)?;
+        if !self.is_unary() {
+            check_operand(b)?;
+        }
+
+        let result = match self {
+            Op::Add => a + b,
+            Op::Sub => a - b,
+            Op::Mul => a * b,
+            Op::Div | Op::Mod if b == 0.0 => return Err(CalcError::DivisionByZero),
+            Op::Div => a / b,
+            Op::Mod => a % b,
+            Op::Pow if a == 0.0 && b < 0.0 => return Err(CalcError::DivisionByZero),
+            Op::Pow => a.powf(b),
+            Op::Sqrt if a < 0.0 => return Err(CalcError::Domain { op: self, value: a }),
+            Op::Sqrt => a.sqrt(),
+            Op::Abs => a.abs(),
+            Op::Neg => -a,
+            Op::Floor => a.floor(),
+            Op::Ceil => a.ceil(),
+            Op::Round => a.round(),
+            Op::Log | Op::Ln if a <= 0.0 => return Err(CalcError::Domain { op: self, value: a }),
+            Op::Log => a.log10(),
+            Op::Ln => a.ln(),
+            Op::Exp => a.exp(),
+            Op::Min => a.min(b),
+            Op::Max => a.max(b),
+            Op::PercentOf => a / 100.0 * b,
+            Op::Factorial => factorial(a)?,
+            Op::Sin => a.sin(),
+            Op::Cos => a.cos(),
+            Op::Tan => a.tan(),
+            Op::Asin => a.asin(),
+            Op::Acos => a.acos(),
+            Op::Atan => a.atan(),
+        };
+
+        if result.is_nan() {
+            Err(CalcError::Domain { op: self, value: a })
+        } else if result.is_infinite() {
+            Err(CalcError::Overflow(self))
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+/// Largest `n` for which `n!` fits in an f64
+const MAX_FACTORIAL: f64 = 170.0;
+
+fn factorial(n: f64) -> Result<f64, CalcError> {
+    if n < 0.0 || n.fract() != 0.0 {
+        return Err(CalcError::Domain {
+            op: Op::Factorial,
+            value: n,
+        });
+    }
+    if n > MAX_FACTORIAL {
+        return Err(CalcError::Overflow(Op::Factorial));
+    }
+    Ok((2..=n as u32).fold(1.0, |acc, k| acc * k as f64))
+}
+
+fn check_operand(x: f64) -> Result<(), CalcError> {
+    if x.is_nan() {
+        Err(CalcError::NotANumber)
+    } else if x.is_infinite() {
+        Err(CalcError::Infinite)
+    } else {
+        Ok(())
+    }
+}
+
+/// Errors that can occur when evaluating arithmetic
+#[derive(Debug, Error, Clone, Copy, PartialEq)]
+pub enum CalcError {
+    #[error("Division by zero")]
+    DivisionByZero,
+
+    #[error("{} is undefined for {value}", op.symbol())]
+    Domain { op: Op, value: f64 },
+
+    #[error("Result of {} overflowed", .0.symbol())]
+    Overflow(Op),
+
+    #[error("Operand is not a number")]
+    NotANumber,
+
+    #[error("Operand is infinite")]
+    Infinite,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basic_ops() {
+        assert_eq!(Op::Add.apply(15.0, 7.0), Ok(22.0));
+        assert_eq!(Op::Sub.apply(15.0, 7.0), Ok(8.0));
+        assert_eq!(Op::Mul.apply(6.0, 7.0), Ok(42.0));
+        assert_eq!(Op::Div.apply(1.0, 4.0), Ok(0.25));
+        assert_eq!(Op::Pow.apply(2.0, 10.0), Ok(1024.0));
+        assert_eq!(Op::Sqrt.apply(144.0, 0.0), Ok(12.0));
+    }
+
+    #[test]
+    fn test_mod_sign_follows_dividend() {
+        assert_eq!(Op::Mod.apply(7.0, 3.0), Ok(1.0));
+        assert_eq!(Op::Mod.apply(-7.0, 3.0), Ok(-1.0));
+        assert_eq!(Op::Mod.apply(7.0, -3.0), Ok(1.0));
+    }
+
+    #[test]
+    fn test_extended_ops() {
+        assert_eq!(Op::Abs.apply(-3.5, 0.0), Ok(3.5));
+        assert_eq!(Op::Neg.apply(3.5, 0.0), Ok(-3.5));
+        assert_eq!(Op::Floor.apply(-2.5, 0.0), Ok(-3.0));
+        assert_eq!(Op::Ceil.apply(-2.5, 0.0), Ok(-2.0));
+        assert_eq!(Op::Round.apply(2.5, 0.0), Ok(3.0));
+        assert_eq!(Op::Round.apply(-2.5, 0.0), Ok(-3.0));
+        assert_eq!(Op::Log.apply(1000.0, 0.0), Ok(3.0));
+        assert_eq!(Op::Ln.apply(1.0, 0.0), Ok(0.0));
+        assert_eq!(Op::Exp.apply(0.0, 0.0), Ok(1.0));
+        assert_eq!(Op::Min.apply(2.0, 9.0), Ok(2.0));
+        assert_eq!(Op::Max.apply(2.0, 9.0), Ok(9.0));
+        assert_eq!(Op::PercentOf.apply(15.0, 80.0), Ok(12.0));
+        assert_eq!(Op::Factorial.apply(5.0, 0.0), Ok(120.0));
+        assert_eq!(Op::Factorial.apply(0.0, 0.0), Ok(1.0));
+        assert_eq!(Op::Sin.apply(0.0, 0.0), Ok(0.0));
+        assert_eq!(Op::Cos.apply(0.0, 0.0), Ok(1.0));
+        assert_eq!(Op::Tan.apply(0.0, 0.0), Ok(0.0));
+        assert_eq!(Op::Asin.apply(1.0, 0.0), Ok(std::f64::consts::FRAC_PI_2));
+        assert_eq!(Op::Acos.apply(1.0, 0.0), Ok(0.0));
+        assert_eq!(Op::Atan.apply(0.0, 0.0), Ok(0.0));
+    }
+
+    #[test]
+    fn test_unary_ignores_second_operand() {
+        assert_eq!(Op::Sqrt.apply(9.0, f64::NAN), Ok(3.0));
+        assert_eq!(Op::Abs.apply(-1.0, f64::INFINITY), Ok(1.0));
+    }
+
+    #[test]
+    fn test_extended_op_errors() {
+        for (op, value) in [
+            (Op::Log, 0.0),
+            (Op::Ln, -1.0),
+            (Op::Asin, 2.0),
+            (Op::Acos, -1.5),
+            (Op::Factorial, -1.0),
+            (Op::Factorial, 2.5),
+        ] {
+            assert_eq!(
+                op.apply(value, 0.0),
+                Err(CalcError::Domain { op, value }),
+                "{:?}({})",
+                op,
+                value
+            );
+        }
+        assert_eq!(
+            Op::Factorial.apply(171.0, 0.0),
+            Err(CalcError::Overflow(Op::Factorial))
+        );
+        assert_eq!(
+            Op::Exp.apply(1000.0, 0.0),
+            Err(CalcError::Overflow(Op::Exp))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(Op::Div.apply(1.0, 0.0), Err(CalcError::DivisionByZero));
+        assert_eq!(Op::Mod.apply(1.0, 0.0), Err(CalcError::DivisionByZero));
+        assert_eq!(Op::Pow.apply(0.0, -1.0), Err(CalcError::DivisionByZero));
+        assert_eq!(
+            Op::Sqrt.apply(-4.0, 0.0),
+            Err(CalcError::Domain {
+                op: Op::Sqrt,
+                value: -4.0
+            })
+        );
+        assert_eq!(
+            Op::Pow.apply(10.0, 400.0),
+            Err(CalcError::Overflow(Op::Pow))
+        );
+        assert_eq!(Op::Add.apply(f64::NAN, 1.0), Err(CalcError::NotANumber));
+        assert_eq!(Op::Add.apply(1.0, f64::INFINITY), Err(CalcError::Infinite));
+    }
+
+    #[test]
+    fn test_evaluate_every_op() {
+        let cases = [
+            (Op::Add, 15.0, 7.0, 22.0),
+            (Op::Sub, 7.0, 15.0, -8.0),
+            (Op::Mul, -6.0, 7.0, -42.0),
+            (Op::Div, 7.0, 2.0, 3.5),
+            (Op::Mod, 7.5, 2.0, 1.5),
+            (Op::Pow, -2.0, 3.0, -8.0),
+            (Op::Sqrt, 2.25, 0.0, 1.5),
+            (Op::Abs, -0.5, 0.0, 0.5),
+            (Op::Neg, -0.5, 0.0, 0.5),
+            (Op::Floor, 1.9, 0.0, 1.0),
+            (Op::Ceil, 1.1, 0.0, 2.0),
+            (Op::Round, 1.49, 0.0, 1.0),
+            (Op::Log, 0.01, 0.0, -2.0),
+            (Op::Ln, std::f64::consts::E, 0.0, 1.0),
+            (Op::Exp, 1.0, 0.0, std::f64::consts::E),
+            (Op::Min, -1.0, -2.0, -2.0),
+            (Op::Max, -1.0, -2.0, -1.0),
+            (Op::PercentOf, 50.0, 30.0, 15.0),
+            (Op::Factorial, 10.0, 0.0, 3628800.0),
+            (Op::Sin, std::f64::consts::FRAC_PI_2, 0.0, 1.0),
+            (Op::Cos, std::f64::consts::PI, 0.0, -1.0),
+            (Op::Tan, std::f64::consts::FRAC_PI_4, 0.0, 1.0),
+            (Op::Asin, 0.0, 0.0, 0.0),
+            (Op::Acos, -1.0, 0.0, std::f64::consts::PI),
+            (Op::Atan, 1.0, 0.0, std::f64::consts::FRAC_PI_4),
+        ];
+        for op in Op::ALL {
+            assert!(cases.iter().any(|c| c.0 == op), "{:?} not covered", op);
+        }
+        for (op, a, b, expected) in cases {
+            let actual = CalcPayload::new(op, a, b).evaluate().unwrap();
+            assert!(
+                (actual - expected).abs() < 1e-12,
+                "{:?}({}, {}) = {}, expected {}",
+                op,
+                a,
+                b,
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_non_finite_operands_rejected_for_every_op() {
+        for op in Op::ALL {
+            assert_eq!(
+                CalcPayload::new(op, f64::NAN, 1.0).evaluate(),
+                Err(CalcError::NotANumber)
+            );
+            assert_eq!(
+                CalcPayload::new(op, f64::NEG_INFINITY, 1.0).evaluate(),
+                Err(CalcError::Infinite)
+            );
+            if !op.is_unary() {
+                assert_eq!(
+                    CalcPayload::new(op, 1.0, f64::NAN).evaluate(),
+                    Err(CalcError::NotANumber)
+                );
+                assert_eq!(
+                    CalcPayload::new(op, 1.0, f64::INFINITY).evaluate(),
+                    Err(CalcError::Infinite)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_results_are_always_finite() {
+        let samples = [
+            -1e308, -1e6, -2.5, -1.0, -0.5, -0.0, 0.0, 0.5, 1.0, 2.5, 170.0, 1e6, 1e308,
+        ];
+        for op in Op::ALL {
+            for a in samples {
+                for b in samples {
+                    if let Ok(value) = op.apply(a, b) {
+                        assert!(value.is_finite(), "{:?}({}, {}) = {}", op, a, b, value);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_mod_negative_operands() {
+        assert_eq!(CalcPayload::new(Op::Mod, -7.0, 3.0).evaluate(), Ok(-1.0));
+        assert_eq!(CalcPayload::new(Op::Mod, -7.0, -3.0).evaluate(), Ok(-1.0));
+        assert_eq!(CalcPayload::new(Op::Mod, 7.0, -3.0).evaluate(), Ok(1.0));
+        assert_eq!(CalcPayload::new(Op::Mod, -6.0, 3.0).evaluate(), Ok(-0.0));
+        assert_eq!(CalcPayload::new(Op::Mod, -5.5, 2.0).evaluate(), Ok(-1.5));
+    }
+
+    #[test]
+    fn test_pow_edge_cases() {
+        assert_eq!(CalcPayload::new(Op::Pow, 0.0, 0.0).evaluate(), Ok(1.0));
+        assert_eq!(CalcPayload::new(Op::Pow, 4.0, 0.5).evaluate(), Ok(2.0));
+        assert_eq!(CalcPayload::new(Op::Pow, 2.0, -2.0).evaluate(), Ok(0.25));
+        assert_eq!(
+            CalcPayload::new(Op::Pow, 0.0, -2.0).evaluate(),
+            Err(CalcError::DivisionByZero)
+        );
+        assert_eq!(
+            CalcPayload::new(Op::Pow, -8.0, 1.0 / 3.0).evaluate(),
+            Err(CalcError::Domain {
+                op: Op::Pow,
+                value: -8.0
+            })
+        );
+        assert_eq!(
+            CalcPayload::new(Op::Pow, -10.0, 309.0).evaluate(),
+            Err(CalcError::Overflow(Op::Pow))
+        );
+        // Underflow to zero is not an error
+        assert_eq!(CalcPayload::new(Op::Pow, 10.0, -400.0).evaluate(), Ok(0.0));
+    }
+
+    #[test]
+    fn test_sqrt_and_division_edge_cases() {
+        assert_eq!(
+            CalcPayload::unary(Op::Sqrt, -1e-300).evaluate(),
+            Err(CalcError::Domain {
+                op: Op::Sqrt,
+                value: -1e-300
+            })
+        );
+        assert_eq!(CalcPayload::unary(Op::Sqrt, -0.0).evaluate(), Ok(-0.0));
+        assert_eq!(
+            CalcPayload::new(Op::Div, 0.0, -0.0).evaluate(),
+            Err(CalcError::DivisionByZero)
+        );
+        assert_eq!(
+            CalcPayload::new(Op::Div, 1e308, 1e-308).evaluate(),
+            Err(CalcError::Overflow(Op::Div))
+        );
+        assert_eq!(
+            CalcPayload::new(Op::Mul, 1e308, 10.0).evaluate(),
+            Err(CalcError::Overflow(Op::Mul))
+        );
+    }
+
+    #[test]
+    fn test_error_messages() {
+        assert_eq!(CalcError::DivisionByZero.to_string(), "Division by zero");
+        assert_eq!(
+            CalcError::Domain {
+                op: Op::Sqrt,
+                value: -1.0
+            }
+            .to_string(),
+            "sqrt is undefined for -1"
+        );
+        assert_eq!(
+            CalcError::Overflow(Op::Pow).to_string(),
+            "Result of ^ overflowed"
+        );
+    }
+}
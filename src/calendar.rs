@@ -0,0 +1,434 @@
+//! Proleptic Gregorian calendar arithmetic on Unix timestamps
+//!
+//! Month and year steps are calendar-aware: adding a month keeps the time of
+//! day and day-of-month, clamping to the end of shorter months, so
+//! `2024-01-31 + 1 month == 2024-02-29` and `2024-02-29 + 1 year == 2025-02-28`.
+//! Every other unit is a fixed number of seconds (leap seconds are ignored,
+//! as in Unix time).
+//!
+//! ```rust
+//! use frame_isa::calendar::{DateTime, TimeArithmetic};
+//! use frame_isa::TimeUnit;
+//!
+//! let jan31 = DateTime::new(2024, 1, 31, 12, 0, 0).unwrap().to_timestamp();
+//! let feb = TimeArithmetic::Calendar.add(jan31, 1, TimeUnit::Month);
+//! assert_eq!(DateTime::from_timestamp(feb), DateTime::new(2024, 2, 29, 12, 0, 0).unwrap());
+//!
+//! // The nominal 30-day month is still available explicitly
+//! let fixed = TimeArithmetic::FixedSeconds.add(jan31, 1, TimeUnit::Month);
+//! assert_eq!(fixed - jan31, 30 * 86_400);
+//! ```
+//!
+//! Day counts use Howard Hinnant's `days_from_civil` algorithms, which are
+//! exact for every year representable here. Results past the `i64` range
+//! saturate instead of overflowing, so decoded payloads with extreme values
+//! still display.
+
+use crate::TimeUnit;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Seconds in a calendar day (ignoring leap seconds)
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Days in a 400-year Gregorian cycle
+const DAYS_PER_ERA: i64 = 146_097;
+
+/// Days from 0000-03-01 to 1970-01-01
+const EPOCH_SHIFT: i64 = 719_468;
+
+/// Check if a year is a Gregorian leap year
+pub const fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Number of days in a month (1-12), or 0 for an invalid month
+pub const fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+/// Days since 1970-01-01 for a civil date (month 1-12, day 1-31)
+pub const fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    // Widened so the extreme years stay exact up to the final saturation
+    let year = if month <= 2 {
+        year as i128 - 1
+    } else {
+        year as i128
+    };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month as i128 + 9) % 12; // March = 0
+    let doy = (153 * mp + 2) / 5 + day as i128 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * DAYS_PER_ERA as i128 + doe - EPOCH_SHIFT as i128;
+    if days > i64::MAX as i128 {
+        i64::MAX
+    } else if days < i64::MIN as i128 {
+        i64::MIN
+    } else {
+        days as i64
+    }
+}
+
+/// Civil date `(year, month, day)` for a count of days since 1970-01-01
+pub const fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let z = days.saturating_add(EPOCH_SHIFT);
+    let era = z.div_euclid(DAYS_PER_ERA);
+    let doe = z - era * DAYS_PER_ERA;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Day of the week (ISO 8601 numbering, Monday = 1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Weekday {
+    Monday = 1,
+    Tuesday = 2,
+    Wednesday = 3,
+    Thursday = 4,
+    Friday = 5,
+    Saturday = 6,
+    Sunday = 7,
+}
+
+impl Weekday {
+    /// Every weekday, Monday first
+    pub const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    /// Parse from ISO number (1-7)
+    pub fn from_number(n: u8) -> Option<Self> {
+        Self::ALL.get((n as usize).wrapping_sub(1)).copied()
+    }
+
+    /// ISO number (Monday = 1 ... Sunday = 7)
+    pub fn number(self) -> u8 {
+        self as u8
+    }
+
+    /// Weekday of a count of days since 1970-01-01 (a Thursday)
+    pub fn from_days(days: i64) -> Self {
+        Self::ALL[(days.rem_euclid(7) + 3) as usize % 7]
+    }
+
+    /// Display name
+    pub fn name(self) -> &'static str {
+        match self {
+            Weekday::Monday => "Monday",
+            Weekday::Tuesday => "Tuesday",
+            Weekday::Wednesday => "Wednesday",
+            Weekday::Thursday => "Thursday",
+            Weekday::Friday => "Friday",
+            Weekday::Saturday => "Saturday",
+            Weekday::Sunday => "Sunday",
+        }
+    }
+}
+
+/// Day of month of the `n`th `weekday` in a month
+///
+/// `n` counts from the start for positive values (1 = first) and from the
+/// end for negative values (-1 = last). Returns `None` if there is no such day.
+pub fn nth_weekday_of_month(year: i64, month: u8, weekday: Weekday, n: i8) -> Option<u8> {
+    let len = days_in_month(year, month);
+    if len == 0 || n == 0 {
+        return None;
+    }
+    let day = if n > 0 {
+        let first = Weekday::from_days(days_from_civil(year, month, 1));
+        let offset = (weekday.number() + 7 - first.number()) % 7;
+        1 + offset as i64 + 7 * (n as i64 - 1)
+    } else {
+        let last = Weekday::from_days(days_from_civil(year, month, len));
+        let offset = (last.number() + 7 - weekday.number()) % 7;
+        len as i64 - offset as i64 - 7 * (-(n as i64) - 1)
+    };
+    (1..=len as i64).contains(&day).then_some(day as u8)
+}
+
+/// Broken-down UTC date and time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct DateTime {
+    pub year: i64,
+    /// Month (1-12)
+    pub month: u8,
+    /// Day of month (1-31)
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl DateTime {
+    /// Create from components, or `None` if any is out of range
+    pub fn new(year: i64, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Option<Self> {
+        let valid = (1..=12).contains(&month)
+            && (1..=days_in_month(year, month)).contains(&day)
+            && hour < 24
+            && minute < 60
+            && second < 60;
+        valid.then_some(Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    /// Break down a Unix timestamp
+    pub fn from_timestamp(timestamp: i64) -> Self {
+        let days = timestamp.div_euclid(SECONDS_PER_DAY);
+        let secs = timestamp.rem_euclid(SECONDS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        Self {
+            year,
+            month,
+            day,
+            hour: (secs / 3600) as u8,
+            minute: (secs / 60 % 60) as u8,
+            second: (secs % 60) as u8,
+        }
+    }
+
+    /// Convert to a Unix timestamp (saturating for years out of range)
+    pub fn to_timestamp(&self) -> i64 {
+        let days = days_from_civil(self.year, self.month, self.day) as i128;
+        let seconds = self.hour as i128 * 3600 + self.minute as i128 * 60 + self.second as i128;
+        (days * SECONDS_PER_DAY as i128 + seconds).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+
+    /// Day of the week
+    pub fn weekday(&self) -> Weekday {
+        Weekday::from_days(days_from_civil(self.year, self.month, self.day))
+    }
+
+    /// Move by whole months, clamping the day to the end of the target month
+    pub fn add_months(self, months: i64) -> Self {
+        let index = self
+            .year
+            .saturating_mul(12)
+            .saturating_add(self.month as i64 - 1)
+            .saturating_add(months);
+        let year = index.div_euclid(12);
+        let month = index.rem_euclid(12) as u8 + 1;
+        Self {
+            year,
+            month,
+            day: self.day.min(days_in_month(year, month)),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+/// How to step a timestamp by a [`TimeUnit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum TimeArithmetic {
+    /// Months and years follow the calendar (clamping day-of-month)
+    #[default]
+    Calendar,
+    /// Every unit is [`TimeUnit::seconds`] long (30-day months, 365-day years)
+    FixedSeconds,
+}
+
+impl TimeArithmetic {
+    /// Add `delta` units to a Unix timestamp, saturating at the `i64` range
+    pub fn add(self, timestamp: i64, delta: i64, unit: TimeUnit) -> i64 {
+        match (self, unit) {
+            (TimeArithmetic::Calendar, TimeUnit::Month) => DateTime::from_timestamp(timestamp)
+                .add_months(delta)
+                .to_timestamp(),
+            (TimeArithmetic::Calendar, TimeUnit::Year) => DateTime::from_timestamp(timestamp)
+                .add_months(delta.saturating_mul(12))
+                .to_timestamp(),
+            _ => timestamp.saturating_add(delta.saturating_mul(unit.seconds())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(year: i64, month: u8, day: u8) -> i64 {
+        DateTime::new(year, month, day, 0, 0, 0)
+            .unwrap()
+            .to_timestamp()
+    }
+
+    #[test]
+    fn test_leap_years() {
+        assert!(is_leap_year(2024));
+        assert!(is_leap_year(2000));
+        assert!(!is_leap_year(1900));
+        assert!(!is_leap_year(2023));
+        assert!(is_leap_year(-4));
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2100, 2), 28);
+        assert_eq!(days_in_month(2024, 13), 0);
+    }
+
+    #[test]
+    fn test_civil_days() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+
+        // Round-trips across several 400-year eras, including negative years
+        for days in (-1_000_000..1_000_000).step_by(997) {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+    }
+
+    #[test]
+    fn test_weekdays() {
+        assert_eq!(Weekday::from_days(0), Weekday::Thursday);
+        assert_eq!(Weekday::from_days(-1), Weekday::Wednesday);
+        assert_eq!(
+            DateTime::from_timestamp(ts(2024, 2, 29)).weekday(),
+            Weekday::Thursday
+        );
+        assert_eq!(Weekday::from_number(7), Some(Weekday::Sunday));
+        assert_eq!(Weekday::from_number(0), None);
+
+        // US DST 2024: second Sunday of March, first Sunday of November
+        assert_eq!(nth_weekday_of_month(2024, 3, Weekday::Sunday, 2), Some(10));
+        assert_eq!(nth_weekday_of_month(2024, 11, Weekday::Sunday, 1), Some(3));
+        // EU DST 2024: last Sunday of March and October
+        assert_eq!(nth_weekday_of_month(2024, 3, Weekday::Sunday, -1), Some(31));
+        assert_eq!(
+            nth_weekday_of_month(2024, 10, Weekday::Sunday, -1),
+            Some(27)
+        );
+        assert_eq!(
+            nth_weekday_of_month(2024, 2, Weekday::Thursday, 5),
+            Some(29)
+        );
+        assert_eq!(nth_weekday_of_month(2024, 2, Weekday::Friday, 5), None);
+    }
+
+    #[test]
+    fn test_datetime_roundtrip() {
+        let dt = DateTime::from_timestamp(1_700_000_000);
+        assert_eq!(dt, DateTime::new(2023, 11, 14, 22, 13, 20).unwrap());
+        assert_eq!(dt.to_timestamp(), 1_700_000_000);
+        assert_eq!(dt.to_string(), "2023-11-14 22:13:20");
+
+        let before_epoch = DateTime::from_timestamp(-1);
+        assert_eq!(
+            before_epoch,
+            DateTime::new(1969, 12, 31, 23, 59, 59).unwrap()
+        );
+        assert_eq!(before_epoch.to_timestamp(), -1);
+
+        assert!(DateTime::new(2023, 2, 29, 0, 0, 0).is_none());
+        assert!(DateTime::new(2024, 1, 1, 24, 0, 0).is_none());
+    }
+
+    #[test]
+    fn test_add_months_clamps() {
+        let cal = TimeArithmetic::Calendar;
+        assert_eq!(
+            cal.add(ts(2024, 1, 31), 1, TimeUnit::Month),
+            ts(2024, 2, 29)
+        );
+        assert_eq!(
+            cal.add(ts(2023, 1, 31), 1, TimeUnit::Month),
+            ts(2023, 2, 28)
+        );
+        assert_eq!(
+            cal.add(ts(2024, 3, 31), -1, TimeUnit::Month),
+            ts(2024, 2, 29)
+        );
+        assert_eq!(
+            cal.add(ts(2024, 11, 15), 2, TimeUnit::Month),
+            ts(2025, 1, 15)
+        );
+        assert_eq!(
+            cal.add(ts(2024, 1, 15), -13, TimeUnit::Month),
+            ts(2022, 12, 15)
+        );
+        assert_eq!(cal.add(ts(2024, 2, 29), 1, TimeUnit::Year), ts(2025, 2, 28));
+        assert_eq!(cal.add(ts(2024, 2, 29), 4, TimeUnit::Year), ts(2028, 2, 29));
+
+        // Time of day is preserved
+        let noon = ts(2024, 1, 31) + 12 * 3600;
+        assert_eq!(
+            cal.add(noon, 1, TimeUnit::Month),
+            ts(2024, 2, 29) + 12 * 3600
+        );
+    }
+
+    #[test]
+    fn test_extreme_timestamps() {
+        // Every timestamp breaks down and converts back exactly
+        for timestamp in [i64::MIN, i64::MIN + 1, i64::MAX - 1, i64::MAX] {
+            assert_eq!(
+                DateTime::from_timestamp(timestamp).to_timestamp(),
+                timestamp
+            );
+        }
+
+        // Steps past the range saturate instead of overflowing
+        let cal = TimeArithmetic::Calendar;
+        assert_eq!(cal.add(i64::MAX, 1, TimeUnit::Day), i64::MAX);
+        assert_eq!(cal.add(i64::MAX, 1, TimeUnit::Year), i64::MAX);
+        assert_eq!(cal.add(i64::MIN, i64::MIN, TimeUnit::Month), i64::MIN);
+        let far = DateTime::new(i64::MAX, 12, 31, 23, 59, 59).unwrap();
+        assert_eq!(far.to_timestamp(), i64::MAX);
+        assert!((1..=12).contains(&far.add_months(i64::MAX).month));
+    }
+
+    #[test]
+    fn test_fixed_seconds_mode() {
+        let fixed = TimeArithmetic::FixedSeconds;
+        assert_eq!(fixed.add(0, 2, TimeUnit::Month), 2 * 30 * SECONDS_PER_DAY);
+        assert_eq!(fixed.add(0, 1, TimeUnit::Year), 365 * SECONDS_PER_DAY);
+
+        // Non-calendar units agree in both modes
+        for unit in [
+            TimeUnit::Second,
+            TimeUnit::Hour,
+            TimeUnit::Day,
+            TimeUnit::Week,
+        ] {
+            assert_eq!(
+                TimeArithmetic::Calendar.add(1_000, 3, unit),
+                fixed.add(1_000, 3, unit)
+            );
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Clock abstraction for time-dependent constructors
+//!
+//! Anything that needs "now" takes a [`Clock`] so tests can pin time:
+//!
+//! ```rust
+//! use frame_isa::clock::{FixedClock, ManualClock};
+//! use frame_isa::{TimePayload, TimeUnit};
+//!
+//! let clock = FixedClock(1_700_000_000);
+//! assert_eq!(TimePayload::now_with(&clock).reference, 1_700_000_000);
+//!
+//! let clock = ManualClock::new(0);
+//! clock.advance(90);
+//! let time = TimePayload::from_now_with(&clock, 3, TimeUnit::Hour);
+//! assert_eq!(time.target_timestamp(), 90 + 3 * 3600);
+//! ```
+//!
+//! [`SystemClock`] reads the host clock. Times before 1970 are negative
+//! timestamps, not errors. The `try_*` constructors report a clock that
+//! cannot be read as a [`ClockError`].
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Errors reading a clock
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ClockError {
+    #[error("Clock reading is outside the representable range")]
+    OutOfRange,
+
+    #[error("Clock unavailable: {0}")]
+    Unavailable(String),
+}
+
+/// Source of the current time
+pub trait Clock {
+    /// Current time in Unix seconds
+    fn try_now(&self) -> Result<i64, ClockError>;
+
+    /// Current time in Unix seconds, falling back to the saturating
+    /// [`SystemClock::now`] if this clock cannot be read
+    fn now(&self) -> i64 {
+        self.try_now().unwrap_or_else(|_| SystemClock.now())
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn try_now(&self) -> Result<i64, ClockError> {
+        (**self).try_now()
+    }
+
+    fn now(&self) -> i64 {
+        (**self).now()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn try_now(&self) -> Result<i64, ClockError> {
+        (**self).try_now()
+    }
+
+    fn now(&self) -> i64 {
+        (**self).now()
+    }
+}
+
+/// The host's wall clock
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl SystemClock {
+    /// Current time in Unix seconds, saturating outside the i64 range
+    pub fn now(&self) -> i64 {
+        match self.try_now() {
+            Ok(now) => now,
+            Err(_) if SystemTime::now() < UNIX_EPOCH => i64::MIN,
+            Err(_) => i64::MAX,
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn try_now(&self) -> Result<i64, ClockError> {
+        let now = SystemTime::now();
+        match now.duration_since(UNIX_EPOCH) {
+            Ok(since) => i64::try_from(since.as_secs()).map_err(|_| ClockError::OutOfRange),
+            Err(before) => {
+                // Round towards negative infinity, like positive times truncate towards the past
+                let before = before.duration();
+                let secs = i64::try_from(before.as_secs()).map_err(|_| ClockError::OutOfRange)?;
+                let partial = i64::from(before.subsec_nanos() > 0);
+                secs.checked_add(partial)
+                    .map(|secs| -secs)
+                    .ok_or(ClockError::OutOfRange)
+            }
+        }
+    }
+}
+
+/// A clock stopped at a fixed time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub i64);
+
+impl Clock for FixedClock {
+    fn try_now(&self) -> Result<i64, ClockError> {
+        Ok(self.0)
+    }
+}
+
+/// A clock moved explicitly by the caller (shareable across threads)
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: AtomicI64,
+}
+
+impl ManualClock {
+    /// Create a clock reading `now`
+    pub fn new(now: i64) -> Self {
+        Self {
+            now: AtomicI64::new(now),
+        }
+    }
+
+    /// Current reading
+    pub fn now(&self) -> i64 {
+        self.now.load(Ordering::SeqCst)
+    }
+
+    /// Set the reading
+    pub fn set(&self, now: i64) {
+        self.now.store(now, Ordering::SeqCst);
+    }
+
+    /// Move the reading by `seconds` (may be negative)
+    pub fn advance(&self, seconds: i64) {
+        self.now.fetch_add(seconds, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn try_now(&self) -> Result<i64, ClockError> {
+        Ok(self.now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_clock() {
+        let now = SystemClock.try_now().unwrap();
+        assert!(now > 1_700_000_000);
+        assert!(SystemClock.now() >= now);
+    }
+
+    #[test]
+    fn test_manual_clock() {
+        let clock = ManualClock::new(100);
+        clock.advance(50);
+        assert_eq!(clock.try_now(), Ok(150));
+        clock.advance(-200);
+        assert_eq!(clock.try_now(), Ok(-50));
+        clock.set(7);
+        assert_eq!(clock.now(), 7);
+
+        // Shared handles see the same reading
+        let shared = Arc::new(clock);
+        let handle = Arc::clone(&shared);
+        handle.advance(1);
+        assert_eq!(shared.try_now(), Ok(8));
+    }
+
+    #[test]
+    fn test_dyn_clock() {
+        struct Broken;
+        impl Clock for Broken {
+            fn try_now(&self) -> Result<i64, ClockError> {
+                Err(ClockError::Unavailable("no signal".to_string()))
+            }
+        }
+
+        let clocks: [&dyn Clock; 2] = [&FixedClock(42), &Broken];
+        assert_eq!(clocks[0].try_now(), Ok(42));
+        assert_eq!(clocks[0].now(), 42);
+        assert!(clocks[1].try_now().is_err());
+        // An unreadable clock falls back to the host clock
+        assert!(clocks[1].now() > 1_700_000_000);
+    }
+}
@@ -0,0 +1,541 @@
+//! Application-defined payloads for extended instructions
+//!
+//! The payload type byte space is split in two:
+//!
+//! ```text
+//! 0x00-0x7F  Reserved for payloads defined by this crate
+//! 0x80-0xFF  Application-defined payloads
+//! ```
+//!
+//! Application payloads are variable-length and encoded with a length prefix:
+//!
+//! ```text
+//! [BASE:6 bytes][PAYLOAD_TYPE:1 byte][LEN:2 bytes][DATA:LEN bytes]
+//! ```
+//!
+//! Downstream crates implement [`CustomPayload`] and register the type with a
+//! [`PayloadRegistry`] so that parsing yields the typed payload. Payloads whose
+//! type byte is not registered are kept as raw bytes and survive a round trip
+//! unchanged.
+
+use crate::{ExtendedInstruction, InstructionError, Payload, PayloadType};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Maximum encoded size of an application-defined payload
+pub const MAX_CUSTOM_PAYLOAD_SIZE: usize = u16::MAX as usize;
+
+/// An application-defined payload
+///
+/// ```rust
+/// use frame_isa::custom::{CustomPayload, PayloadRegistry};
+/// use frame_isa::{Action, ExtendedInstruction, Instruction, Subject};
+/// use std::fmt;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Reading(u16);
+///
+/// impl fmt::Display for Reading {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         write!(f, "{} lux", self.0)
+///     }
+/// }
+///
+/// impl CustomPayload for Reading {
+///     const TYPE_BYTE: u8 = 0x80;
+///
+///     fn encode(&self) -> Vec<u8> {
+///         self.0.to_be_bytes().to_vec()
+///     }
+///
+///     fn decode(bytes: &[u8]) -> Option<Self> {
+///         Some(Reading(u16::from_be_bytes(bytes.try_into().ok()?)))
+///     }
+/// }
+///
+/// let mut registry = PayloadRegistry::new();
+/// registry.register::<Reading>().unwrap();
+///
+/// let base = Instruction::simple(Action::RESPOND, Subject::WEATHER);
+/// let ext = ExtendedInstruction::with_custom(base, Reading(300)).unwrap();
+///
+/// let parsed = ExtendedInstruction::from_bytes_with(&ext.to_bytes(), &registry).unwrap();
+/// assert_eq!(parsed.as_custom::<Reading>(), Some(&Reading(300)));
+/// ```
+pub trait CustomPayload: fmt::Debug + fmt::Display + Send + Sync + Any {
+    /// Payload type id byte (must be in the application range 0x80-0xFF)
+    const TYPE_BYTE: u8;
+
+    /// Serialize payload data (not including type byte or length prefix)
+    fn encode(&self) -> Vec<u8>;
+
+    /// Parse payload data
+    fn decode(bytes: &[u8]) -> Option<Self>
+    where
+        Self: Sized;
+}
+
+/// Object-safe view of a [`CustomPayload`], implemented for every payload type
+pub trait DynPayload: fmt::Debug + fmt::Display + Send + Sync {
+    /// Payload type id byte ([`CustomPayload::TYPE_BYTE`])
+    fn type_byte(&self) -> u8;
+
+    /// Serialize payload data ([`CustomPayload::encode`])
+    fn encode(&self) -> Vec<u8>;
+
+    /// Access the concrete payload for downcasting
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: CustomPayload> DynPayload for T {
+    fn type_byte(&self) -> u8 {
+        T::TYPE_BYTE
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        CustomPayload::encode(self)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Application-defined payload carried by an extended instruction
+#[derive(Debug, Clone)]
+pub enum CustomData {
+    /// Payload decoded through a [`PayloadRegistry`]
+    Registered(Arc<dyn DynPayload>),
+    /// Payload whose type byte is not registered
+    Raw {
+        /// Payload type id byte
+        type_byte: u8,
+        /// Payload data (without length prefix)
+        bytes: Vec<u8>,
+    },
+}
+
+impl CustomData {
+    /// Wrap a typed payload, checking its type byte and encoded size
+    pub fn new<T: CustomPayload>(payload: T) -> Result<Self, RegistryError> {
+        let data = CustomData::Registered(Arc::new(payload));
+        data.check()?;
+        Ok(data)
+    }
+
+    /// Check the type byte is in the application range and the data fits the [LEN:2] prefix
+    pub fn check(&self) -> Result<(), RegistryError> {
+        let type_byte = self.type_byte();
+        if !PayloadType::is_application_byte(type_byte) {
+            return Err(RegistryError::ReservedType(type_byte));
+        }
+        let size = self.to_bytes().len();
+        if size > MAX_CUSTOM_PAYLOAD_SIZE {
+            return Err(RegistryError::TooLarge { type_byte, size });
+        }
+        Ok(())
+    }
+
+    /// Get payload type id byte
+    pub fn type_byte(&self) -> u8 {
+        match self {
+            CustomData::Registered(p) => p.type_byte(),
+            CustomData::Raw { type_byte, .. } => *type_byte,
+        }
+    }
+
+    /// Serialize payload data (not including type byte or length prefix)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            CustomData::Registered(p) => p.encode(),
+            CustomData::Raw { bytes, .. } => bytes.clone(),
+        }
+    }
+
+    /// Check if the payload type was recognized by a registry
+    pub fn is_registered(&self) -> bool {
+        matches!(self, CustomData::Registered(_))
+    }
+
+    /// Get the typed payload if registered as `T`
+    pub fn downcast_ref<T: CustomPayload>(&self) -> Option<&T> {
+        match self {
+            CustomData::Registered(p) => p.as_any().downcast_ref::<T>(),
+            CustomData::Raw { .. } => None,
+        }
+    }
+}
+
+impl PartialEq for CustomData {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_byte() == other.type_byte() && self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl fmt::Display for CustomData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CustomData::Registered(p) => write!(f, "{}", p),
+            CustomData::Raw { type_byte, bytes } => {
+                write!(f, "0x{:02X}[{} bytes]", type_byte, bytes.len())
+            }
+        }
+    }
+}
+
+/// Serialized form of custom data (always raw bytes)
+#[derive(Serialize, Deserialize)]
+struct RawCustomData {
+    type_byte: u8,
+    bytes: Vec<u8>,
+}
+
+impl Serialize for CustomData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RawCustomData {
+            type_byte: self.type_byte(),
+            bytes: self.to_bytes(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CustomData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = RawCustomData::deserialize(deserializer)?;
+        let data = CustomData::Raw {
+            type_byte: raw.type_byte,
+            bytes: raw.bytes,
+        };
+        data.check().map_err(D::Error::custom)?;
+        Ok(data)
+    }
+}
+
+type DecodeFn = fn(&[u8]) -> Option<Arc<dyn DynPayload>>;
+
+fn decode_as<T: CustomPayload>(bytes: &[u8]) -> Option<Arc<dyn DynPayload>> {
+    T::decode(bytes).map(|p| Arc::new(p) as Arc<dyn DynPayload>)
+}
+
+/// Registry of application-defined payload types
+#[derive(Debug, Clone, Default)]
+pub struct PayloadRegistry {
+    decoders: HashMap<u8, DecodeFn>,
+}
+
+impl PayloadRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a payload type under its [`TYPE_BYTE`](CustomPayload::TYPE_BYTE)
+    pub fn register<T: CustomPayload>(&mut self) -> Result<(), RegistryError> {
+        let type_byte = T::TYPE_BYTE;
+        if !PayloadType::is_application_byte(type_byte) {
+            return Err(RegistryError::ReservedType(type_byte));
+        }
+        if self.decoders.contains_key(&type_byte) {
+            return Err(RegistryError::AlreadyRegistered(type_byte));
+        }
+        self.decoders.insert(type_byte, decode_as::<T>);
+        Ok(())
+    }
+
+    /// Check if a type byte is registered
+    pub fn is_registered(&self, type_byte: u8) -> bool {
+        self.decoders.contains_key(&type_byte)
+    }
+
+    /// Decode payload data, falling back to raw bytes for unregistered types
+    ///
+    /// Returns `None` if the type is registered but its data fails to decode.
+    pub fn decode(&self, type_byte: u8, bytes: &[u8]) -> Option<CustomData> {
+        match self.decoders.get(&type_byte) {
+            Some(decode) => decode(bytes).map(CustomData::Registered),
+            None => Some(CustomData::Raw {
+                type_byte,
+                bytes: bytes.to_vec(),
+            }),
+        }
+    }
+
+    /// Decode a raw custom payload in place if its type is registered
+    pub fn resolve(&self, payload: &mut Payload) -> Result<(), InstructionError> {
+        if let Payload::Custom(CustomData::Raw { type_byte, bytes }) = payload {
+            if self.is_registered(*type_byte) {
+                let decoded = self.decode(*type_byte, bytes).ok_or_else(|| {
+                    InstructionError::InvalidPayload(format!(
+                        "Failed to decode custom payload 0x{:02X}",
+                        type_byte
+                    ))
+                })?;
+                *payload = Payload::Custom(decoded);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ExtendedInstruction {
+    /// Create extended instruction with an application-defined payload
+    pub fn with_custom<T: CustomPayload>(
+        base: crate::Instruction,
+        payload: T,
+    ) -> Result<Self, RegistryError> {
+        Ok(Self {
+            base,
+            payload: Payload::Custom(CustomData::new(payload)?),
+        })
+    }
+
+    /// Parse from bytes, decoding application payloads through a registry
+    pub fn from_bytes_with(
+        bytes: &[u8],
+        registry: &PayloadRegistry,
+    ) -> Result<Self, InstructionError> {
+        let mut ext = Self::from_bytes(bytes)?;
+        registry.resolve(&mut ext.payload)?;
+        Ok(ext)
+    }
+
+    /// Get as custom payload data if present
+    pub fn as_custom_data(&self) -> Option<&CustomData> {
+        match &self.payload {
+            Payload::Custom(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Get as typed custom payload if present and registered as `T`
+    pub fn as_custom<T: CustomPayload>(&self) -> Option<&T> {
+        self.as_custom_data()?.downcast_ref::<T>()
+    }
+}
+
+/// Errors that can occur when registering or attaching custom payloads
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RegistryError {
+    #[error("Payload type 0x{0:02X} is reserved (application types are 0x80-0xFF)")]
+    ReservedType(u8),
+
+    #[error("Payload type 0x{0:02X} is already registered")]
+    AlreadyRegistered(u8),
+
+    #[error("Payload type 0x{type_byte:02X} encodes to {size} bytes (max {MAX_CUSTOM_PAYLOAD_SIZE})")]
+    TooLarge { type_byte: u8, size: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, Instruction, Modifier, Subject};
+
+    #[derive(Debug, PartialEq)]
+    struct SensorReading {
+        sensor: u8,
+        value: f32,
+    }
+
+    impl fmt::Display for SensorReading {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "sensor {} = {}", self.sensor, self.value)
+        }
+    }
+
+    impl CustomPayload for SensorReading {
+        const TYPE_BYTE: u8 = 0x90;
+
+        fn encode(&self) -> Vec<u8> {
+            let mut bytes = vec![self.sensor];
+            bytes.extend_from_slice(&self.value.to_be_bytes());
+            bytes
+        }
+
+        fn decode(bytes: &[u8]) -> Option<Self> {
+            if bytes.len() != 5 {
+                return None;
+            }
+            Some(Self {
+                sensor: bytes[0],
+                value: f32::from_be_bytes(bytes[1..5].try_into().ok()?),
+            })
+        }
+    }
+
+    /// Payload claiming a reserved type byte
+    #[derive(Debug)]
+    struct Bad;
+
+    impl fmt::Display for Bad {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "bad")
+        }
+    }
+
+    impl CustomPayload for Bad {
+        const TYPE_BYTE: u8 = 0x01;
+
+        fn encode(&self) -> Vec<u8> {
+            Vec::new()
+        }
+
+        fn decode(_: &[u8]) -> Option<Self> {
+            Some(Bad)
+        }
+    }
+
+    fn reading_instruction() -> ExtendedInstruction {
+        let base = Instruction::new(Action::RESPOND, Subject::WEATHER, Modifier::default());
+        let reading = SensorReading {
+            sensor: 3,
+            value: 21.5,
+        };
+        ExtendedInstruction::with_custom(base, reading).unwrap()
+    }
+
+    #[test]
+    fn test_payload_type_ranges() {
+        assert!(PayloadType::is_reserved_byte(0x00));
+        assert!(PayloadType::is_reserved_byte(0x7F));
+        assert!(PayloadType::is_application_byte(0x80));
+        assert!(PayloadType::is_application_byte(0xFF));
+
+        assert_eq!(PayloadType::from_byte(0x90), Some(PayloadType::Custom(0x90)));
+        assert_eq!(PayloadType::from_byte(0x7F), None);
+        assert_eq!(PayloadType::Custom(0x90).to_byte(), 0x90);
+    }
+
+    #[test]
+    fn test_register_rejects_reserved_and_duplicates() {
+        let mut registry = PayloadRegistry::new();
+        assert_eq!(
+            registry.register::<Bad>(),
+            Err(RegistryError::ReservedType(0x01))
+        );
+        registry.register::<SensorReading>().unwrap();
+        assert_eq!(
+            registry.register::<SensorReading>(),
+            Err(RegistryError::AlreadyRegistered(0x90))
+        );
+    }
+
+    #[test]
+    fn test_custom_roundtrip_registered() {
+        let mut registry = PayloadRegistry::new();
+        registry.register::<SensorReading>().unwrap();
+
+        let ext = reading_instruction();
+        let bytes = ext.to_bytes();
+        assert_eq!(bytes.len(), 6 + 1 + 2 + 5);
+        assert_eq!(ext.byte_size(), bytes.len());
+
+        let parsed = ExtendedInstruction::from_bytes_with(&bytes, &registry).unwrap();
+        assert_eq!(parsed, ext);
+        assert_eq!(parsed.as_custom::<SensorReading>().unwrap().sensor, 3);
+    }
+
+    #[test]
+    fn test_custom_unregistered_kept_raw() {
+        let ext = reading_instruction();
+        let bytes = ext.to_bytes();
+
+        let parsed = ExtendedInstruction::from_bytes(&bytes).unwrap();
+        let data = parsed.as_custom_data().unwrap();
+        assert!(!data.is_registered());
+        assert_eq!(data.type_byte(), 0x90);
+        assert!(parsed.as_custom::<SensorReading>().is_none());
+
+        // Raw payloads re-encode byte-for-byte
+        assert_eq!(parsed.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_resolve_raw_payload() {
+        let mut registry = PayloadRegistry::new();
+        registry.register::<SensorReading>().unwrap();
+
+        let mut parsed = ExtendedInstruction::from_bytes(&reading_instruction().to_bytes()).unwrap();
+        registry.resolve(&mut parsed.payload).unwrap();
+        assert!(parsed.as_custom::<SensorReading>().is_some());
+
+        let mut payload = Payload::Custom(CustomData::Raw {
+            type_byte: 0x90,
+            bytes: vec![3],
+        });
+        assert!(matches!(
+            registry.resolve(&mut payload),
+            Err(InstructionError::InvalidPayload(_))
+        ));
+    }
+
+    #[test]
+    fn test_with_custom_rejects_reserved_type() {
+        let base = Instruction::simple(Action::RESPOND, Subject::NULL);
+        assert_eq!(
+            ExtendedInstruction::with_custom(base, Bad).unwrap_err(),
+            RegistryError::ReservedType(0x01)
+        );
+    }
+
+    #[test]
+    fn test_truncated_custom_payload() {
+        let bytes = reading_instruction().to_bytes();
+        assert!(ExtendedInstruction::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_custom_display_and_serde() {
+        let ext = reading_instruction();
+        assert!(format!("{}", ext).ends_with("# sensor 3 = 21.5"));
+
+        let json = serde_json::to_string(&ext).unwrap();
+        let parsed: ExtendedInstruction = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, ext);
+        assert!(!parsed.as_custom_data().unwrap().is_registered());
+
+        // Reserved type bytes are rejected rather than producing unparseable bytes
+        let json = json.replace("\"type_byte\":144", "\"type_byte\":3");
+        assert!(serde_json::from_str::<ExtendedInstruction>(&json).is_err());
+    }
+
+    #[test]
+    fn test_oversized_payload() {
+        let data = CustomData::Raw {
+            type_byte: 0x90,
+            bytes: vec![0; MAX_CUSTOM_PAYLOAD_SIZE + 1],
+        };
+        assert_eq!(
+            data.check(),
+            Err(RegistryError::TooLarge {
+                type_byte: 0x90,
+                size: MAX_CUSTOM_PAYLOAD_SIZE + 1
+            })
+        );
+        let ext = ExtendedInstruction {
+            base: Instruction::simple(Action::RESPOND, Subject::NULL),
+            payload: Payload::Custom(data),
+        };
+        assert!(matches!(
+            ext.validate(),
+            Err(InstructionError::InvalidPayload(_))
+        ));
+
+        // Encoding truncates to what the length prefix can express
+        let bytes = ext.to_bytes();
+        assert_eq!(bytes.len(), ext.byte_size());
+        let parsed = ExtendedInstruction::from_bytes(&bytes).unwrap();
+        match parsed.payload {
+            Payload::Custom(CustomData::Raw { bytes, .. }) => {
+                assert_eq!(bytes.len(), MAX_CUSTOM_PAYLOAD_SIZE)
+            }
+            payload => panic!("unexpected payload {:?}", payload),
+        }
+    }
+}
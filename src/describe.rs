@@ -0,0 +1,394 @@
+//! English descriptions of instructions
+//!
+//! A [`Vocabulary`] turns an [`Instruction`] into a sentence such as
+//! "Respond casually and warmly about the time, high urgency", for debugging
+//! UIs and for generating text/opcode training pairs.
+//!
+//! Descriptions are table-driven: each action maps to an [`ActionPhrase`]
+//! (verb plus optional preposition), each subject to a noun phrase, and each
+//! modifier field value to an adverb or trailing qualifier. Default modifier
+//! fields are left out. Codes without an entry fall back to their category,
+//! RAG/TRM reference or hex code, and applications add their own:
+//!
+//! ```rust
+//! use frame_isa::describe::Vocabulary;
+//! use frame_isa::modifier::{Urgency, Voice, Warmth};
+//! use frame_isa::{Action, Instruction, Modifier, Subject};
+//!
+//! let modifier = Modifier::default()
+//!     .with_voice(Voice::Casual)
+//!     .with_warmth(Warmth::Warm)
+//!     .with_urgency(Urgency::High);
+//! let instr = Instruction::new(Action::RESPOND, Subject::TIME, modifier);
+//! assert_eq!(instr.describe(), "Respond casually and warmly about the time, high urgency");
+//!
+//! let escalate = Action::from_u16(0x0110);
+//! let english = Vocabulary::english().with_action(escalate, "Escalate", Some("to"));
+//! let instr = Instruction::simple(escalate, Subject::trm_ref(4));
+//! assert_eq!(english.describe(&instr), "Escalate to model 0x04");
+//! ```
+
+use crate::modifier::{Accuracy, Format, Tone, Urgency, Voice, Warmth};
+use crate::{Action, Instruction, Subject};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// How an action reads in a sentence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionPhrase {
+    /// Imperative verb phrase ("Respond", "Search the knowledge base")
+    pub verb: String,
+    /// Preposition before the subject ("about", "for"); `None` takes the subject as direct object
+    pub preposition: Option<String>,
+}
+
+impl ActionPhrase {
+    /// Create a phrase
+    pub fn new(verb: impl Into<String>, preposition: Option<&str>) -> Self {
+        Self {
+            verb: verb.into(),
+            preposition: preposition.map(str::to_string),
+        }
+    }
+}
+
+/// Phrase tables for describing instructions
+///
+/// Empty modifier phrases (the defaults for neutral fields) are left out of
+/// descriptions.
+#[derive(Debug, Clone, Default)]
+pub struct Vocabulary {
+    actions: HashMap<Action, ActionPhrase>,
+    subjects: HashMap<Subject, String>,
+    voices: HashMap<Voice, String>,
+    tones: HashMap<Tone, String>,
+    warmths: HashMap<Warmth, String>,
+    formats: HashMap<Format, String>,
+    accuracies: HashMap<Accuracy, String>,
+    urgencies: HashMap<Urgency, String>,
+}
+
+const ENGLISH_ACTIONS: &[(Action, &str, Option<&str>)] = &[
+    (Action::NOP, "Do nothing", Some("for")),
+    (Action::HALT, "Stop", Some("for")),
+    (Action::ERROR, "Report an error", Some("about")),
+    (Action::STATUS, "Report the status", Some("of")),
+    (Action::GREET, "Greet", None),
+    (Action::CONFIRM, "Confirm", None),
+    (Action::DENY, "Deny", None),
+    (Action::EXPLAIN, "Explain", None),
+    (Action::CLARIFY, "Clarify", None),
+    (Action::APOLOGIZE, "Apologize", Some("to")),
+    (Action::THANK, "Thank", None),
+    (Action::RESPOND, "Respond", Some("about")),
+    (Action::ASK, "Ask", Some("about")),
+    (Action::REQUEST, "Request", None),
+    (Action::SEARCH, "Search", Some("for")),
+    (Action::RETRIEVE, "Retrieve", None),
+    (Action::DEFINE, "Define", None),
+    (Action::DESCRIBE, "Describe", None),
+    (Action::COMPARE, "Compare", None),
+    (Action::SUMMARIZE, "Summarize", None),
+    (Action::EXPLAIN_HOW, "Explain how to work with", None),
+    (Action::EXPLAIN_WHY, "Explain the reasons behind", None),
+    (Action::CALCULATE, "Calculate", None),
+    (Action::SET_TIMER, "Set a timer", Some("for")),
+    (
+        Action::KNOWLEDGE_SEARCH,
+        "Search the knowledge base",
+        Some("for"),
+    ),
+    (Action::EMPATHY, "Empathize", Some("with")),
+    (Action::CONCERN, "Express concern", Some("about")),
+    (Action::ENCOURAGEMENT, "Encourage", None),
+    (Action::REASSURE, "Reassure", None),
+    (Action::TEMPLATE_LOAD, "Load a template", Some("for")),
+    (Action::TEMPLATE_FILL, "Fill a template", Some("for")),
+    (Action::CHAIN, "Hand off", Some("to")),
+    (Action::FORK, "Fork", Some("to")),
+    (Action::MERGE, "Merge the branches", Some("into")),
+];
+
+const ENGLISH_SUBJECTS: &[(Subject, &str)] = &[
+    (Subject::NULL, ""),
+    (Subject::SELF, "yourself"),
+    (Subject::USER, "the user"),
+    (Subject::CONTEXT, "the context"),
+    (Subject::WEATHER, "the weather"),
+    (Subject::TIME, "the time"),
+    (Subject::DATE, "the date"),
+    (Subject::SCHEDULE, "the schedule"),
+    (Subject::HEALTH, "health"),
+    (Subject::HELP, "help"),
+    (Subject::TIMEZONE, "the time zone"),
+    (Subject::NUMBER, "the number"),
+    (Subject::EQUATION, "the equation"),
+    (Subject::PHYSICS, "physics"),
+    (Subject::CHEMISTRY, "chemistry"),
+    (Subject::COMPUTER, "the computer"),
+    (Subject::SOFTWARE, "the software"),
+    (Subject::HARDWARE, "the hardware"),
+    (Subject::AI, "AI"),
+    (Subject::API, "the API"),
+    (Subject::DOCUMENTATION, "the documentation"),
+    (Subject::CONCEPT, "the concept"),
+    (Subject::FEELINGS, "feelings"),
+    (Subject::STRESS, "stress"),
+    (Subject::ANXIETY, "anxiety"),
+];
+
+impl Vocabulary {
+    /// English phrase tables
+    pub fn english() -> Self {
+        let mut vocabulary = Self::default();
+        for &(action, verb, preposition) in ENGLISH_ACTIONS {
+            vocabulary = vocabulary.with_action(action, verb, preposition);
+        }
+        for &(subject, phrase) in ENGLISH_SUBJECTS {
+            vocabulary = vocabulary.with_subject(subject, phrase);
+        }
+        vocabulary
+            .with_voice(Voice::Formal, "formally")
+            .with_voice(Voice::Casual, "casually")
+            .with_voice(Voice::Technical, "technically")
+            .with_tone(Tone::Positive, "positively")
+            .with_tone(Tone::Empathetic, "empathetically")
+            .with_tone(Tone::Cautious, "cautiously")
+            .with_warmth(Warmth::Cold, "coldly")
+            .with_warmth(Warmth::Warm, "warmly")
+            .with_warmth(Warmth::VeryWarm, "very warmly")
+            .with_format(Format::Bulleted, "as a bulleted list")
+            .with_format(Format::Numbered, "as a numbered list")
+            .with_format(Format::Structured, "in a structured format")
+            .with_accuracy(Accuracy::Low, "low confidence")
+            .with_accuracy(Accuracy::High, "high confidence")
+            .with_accuracy(Accuracy::Verified, "verified")
+            .with_urgency(Urgency::Low, "low urgency")
+            .with_urgency(Urgency::High, "high urgency")
+            .with_urgency(Urgency::Critical, "critical urgency")
+    }
+
+    /// Set the phrase for an action
+    pub fn with_action(
+        mut self,
+        action: Action,
+        verb: impl Into<String>,
+        preposition: Option<&str>,
+    ) -> Self {
+        self.actions
+            .insert(action, ActionPhrase::new(verb, preposition));
+        self
+    }
+
+    /// Set the noun phrase for a subject (empty to leave the subject out)
+    pub fn with_subject(mut self, subject: Subject, phrase: impl Into<String>) -> Self {
+        self.subjects.insert(subject, phrase.into());
+        self
+    }
+
+    /// Set the adverb for a voice
+    pub fn with_voice(mut self, voice: Voice, phrase: impl Into<String>) -> Self {
+        self.voices.insert(voice, phrase.into());
+        self
+    }
+
+    /// Set the adverb for a tone
+    pub fn with_tone(mut self, tone: Tone, phrase: impl Into<String>) -> Self {
+        self.tones.insert(tone, phrase.into());
+        self
+    }
+
+    /// Set the adverb for a warmth level
+    pub fn with_warmth(mut self, warmth: Warmth, phrase: impl Into<String>) -> Self {
+        self.warmths.insert(warmth, phrase.into());
+        self
+    }
+
+    /// Set the qualifier for an output format
+    pub fn with_format(mut self, format: Format, phrase: impl Into<String>) -> Self {
+        self.formats.insert(format, phrase.into());
+        self
+    }
+
+    /// Set the qualifier for an accuracy level
+    pub fn with_accuracy(mut self, accuracy: Accuracy, phrase: impl Into<String>) -> Self {
+        self.accuracies.insert(accuracy, phrase.into());
+        self
+    }
+
+    /// Set the qualifier for an urgency level
+    pub fn with_urgency(mut self, urgency: Urgency, phrase: impl Into<String>) -> Self {
+        self.urgencies.insert(urgency, phrase.into());
+        self
+    }
+
+    /// Phrase for an action, falling back to its category and code
+    pub fn action_phrase(&self, action: Action) -> ActionPhrase {
+        if let Some(phrase) = self.actions.get(&action) {
+            return phrase.clone();
+        }
+        let verb = match action.action_category() {
+            Some(category) => format!("Run {} action 0x{:04X}", category.name(), action.0),
+            None => format!("Run action 0x{:04X}", action.0),
+        };
+        ActionPhrase::new(verb, Some("on"))
+    }
+
+    /// Noun phrase for a subject, falling back to its reference or code
+    pub fn subject_phrase(&self, subject: Subject) -> String {
+        if let Some(phrase) = self.subjects.get(&subject) {
+            phrase.clone()
+        } else if let Some(doc_id) = subject.rag_doc_id() {
+            format!("RAG document {}", doc_id)
+        } else if let Some(model_id) = subject.trm_model_id() {
+            format!("model 0x{:02X}", model_id)
+        } else {
+            format!("subject 0x{:04X}", subject.0)
+        }
+    }
+
+    /// Describe an instruction
+    ///
+    /// Adverbs (voice, tone, warmth) follow the verb when the subject takes a
+    /// preposition ("Respond warmly about the time") and the subject
+    /// otherwise ("Greet the user warmly"); format, accuracy and urgency
+    /// qualifiers come last, after a comma.
+    pub fn describe(&self, instr: &Instruction) -> String {
+        let phrase = self.action_phrase(instr.action);
+        let subject = self.subject_phrase(instr.subject);
+        let modifier = instr.modifier;
+        let lookup = |table_phrase: Option<&String>| table_phrase.cloned().unwrap_or_default();
+
+        let adverbs = join_and(&[
+            lookup(self.voices.get(&modifier.voice())),
+            lookup(self.tones.get(&modifier.tone())),
+            lookup(self.warmths.get(&modifier.warmth())),
+        ]);
+        let object = match (&phrase.preposition, subject.is_empty()) {
+            (_, true) => String::new(),
+            (Some(preposition), false) => format!("{} {}", preposition, subject),
+            (None, false) => subject,
+        };
+        let words: Vec<&str> = if phrase.preposition.is_some() {
+            vec![&phrase.verb, &adverbs, &object]
+        } else {
+            vec![&phrase.verb, &object, &adverbs]
+        };
+        let mut sentence = words
+            .into_iter()
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        for qualifier in [
+            lookup(self.formats.get(&modifier.format())),
+            lookup(self.accuracies.get(&modifier.accuracy())),
+            lookup(self.urgencies.get(&modifier.urgency())),
+        ] {
+            if !qualifier.is_empty() {
+                sentence.push_str(", ");
+                sentence.push_str(&qualifier);
+            }
+        }
+        sentence
+    }
+}
+
+/// Join non-empty words as "a", "a and b", "a, b and c"
+fn join_and(words: &[String]) -> String {
+    let words: Vec<&str> = words
+        .iter()
+        .map(String::as_str)
+        .filter(|word| !word.is_empty())
+        .collect();
+    match words.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.to_string(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+impl Instruction {
+    /// Describe this instruction in English (see [`Vocabulary::describe`])
+    pub fn describe(&self) -> String {
+        static ENGLISH: OnceLock<Vocabulary> = OnceLock::new();
+        ENGLISH.get_or_init(Vocabulary::english).describe(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Modifier;
+
+    #[test]
+    fn test_describe() {
+        let cases = [
+            (
+                Instruction::simple(Action::GREET, Subject::USER),
+                "Greet the user",
+            ),
+            (
+                Instruction::new(Action::GREET, Subject::USER, Modifier::friendly()),
+                "Greet the user casually, positively and warmly, low confidence",
+            ),
+            (
+                Instruction::new(Action::EXPLAIN, Subject::API, Modifier::professional()),
+                "Explain the API formally, high confidence",
+            ),
+            (
+                Instruction::new(Action::CONCERN, Subject::STRESS, Modifier::crisis()),
+                "Express concern empathetically and very warmly about stress, high confidence, high urgency",
+            ),
+            (
+                Instruction::new(
+                    Action::SUMMARIZE,
+                    Subject::DOCUMENTATION,
+                    Modifier::default().with_format(Format::Numbered),
+                ),
+                "Summarize the documentation, as a numbered list",
+            ),
+            (
+                Instruction::simple(Action::HALT, Subject::NULL),
+                "Stop",
+            ),
+            (
+                Instruction::simple(Action::RETRIEVE, Subject::rag_ref(0x42)),
+                "Retrieve RAG document 66",
+            ),
+        ];
+        for (instr, expected) in cases {
+            assert_eq!(instr.describe(), expected);
+        }
+    }
+
+    #[test]
+    fn test_fallbacks() {
+        let english = Vocabulary::english();
+        for action in Action::ALL {
+            assert!(!english.action_phrase(action).verb.starts_with("Run"));
+        }
+        let unknown = Instruction::simple(Action::from_u16(0x02FF), Subject(0x0FFF));
+        assert_eq!(
+            english.describe(&unknown),
+            "Run query action 0x02FF on subject 0x0FFF"
+        );
+        let unknown = Instruction::simple(Action::from_u16(0x0900), Subject::USER);
+        assert_eq!(english.describe(&unknown), "Run action 0x0900 on the user");
+    }
+
+    #[test]
+    fn test_custom_entries() {
+        let lookup = Action::from_u16(0x0310);
+        let vocabulary = Vocabulary::english()
+            .with_action(lookup, "Look up", None)
+            .with_subject(Subject(0x0410), "the glossary")
+            .with_warmth(Warmth::Warm, "kindly");
+        let instr = Instruction::new(
+            lookup,
+            Subject(0x0410),
+            Modifier::default().with_warmth(Warmth::Warm),
+        );
+        assert_eq!(vocabulary.describe(&instr), "Look up the glossary kindly");
+    }
+}
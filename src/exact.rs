@@ -0,0 +1,615 @@
+//! Exact integer and decimal operands for calculator payloads
+//!
+//! [`CalcPayload`](crate::CalcPayload) carries `f64` operands, so `0.1 + 0.2`
+//! and integers above 2^53 lose precision. [`ExactCalcPayload`] selects the
+//! operand encoding with a tag byte:
+//!
+//! - `0x00` - [`OperandKind::Float`]: IEEE 754 `f64`
+//! - `0x01` - [`OperandKind::Int`]: exact `i64`
+//! - `0x02` - [`OperandKind::Decimal`]: fixed-point `mantissa / 10^scale`
+//!
+//! ```rust
+//! use frame_isa::{ExactCalcPayload, Number, Op};
+//!
+//! let calc = ExactCalcPayload::new(Op::Add, Number::parse("0.1").unwrap(), Number::parse("0.2").unwrap());
+//! assert_eq!(calc.evaluate().unwrap().to_string(), "0.3");
+//! ```
+//!
+//! Evaluation follows the [reference semantics](crate::calc) but stays exact
+//! whenever the result is representable: integral results are [`Number::Int`],
+//! other exact results are normalized [`Number::Decimal`]s (no trailing zeros,
+//! scale at most [`MAX_SCALE`]). Results that are not representable exactly
+//! (`1 / 3`, `sqrt(2)`, trigonometry, i64 overflow) fall back to `f64`.
+//!
+//! Payload format:
+//!
+//! ```text
+//! [OP:1][TAG:1][A:8][B:8][A_SCALE:1][B_SCALE:1] = 20 bytes
+//! ```
+
+use crate::calc::CalcError;
+use crate::Op;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Largest decimal scale (fractional digits) an operand may carry
+pub const MAX_SCALE: u8 = 18;
+
+/// Largest `n` for which `n!` is computed exactly (fits in an i128)
+const MAX_EXACT_FACTORIAL: i128 = 33;
+
+/// Operand encoding selected by the payload tag byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum OperandKind {
+    Float = 0x00,
+    Int = 0x01,
+    Decimal = 0x02,
+}
+
+impl OperandKind {
+    /// Parse from byte
+    pub fn from_byte(b: u8) -> Option<Self> {
+        match b {
+            0x00 => Some(OperandKind::Float),
+            0x01 => Some(OperandKind::Int),
+            0x02 => Some(OperandKind::Decimal),
+            _ => None,
+        }
+    }
+
+    /// Convert to byte
+    pub fn to_byte(self) -> u8 {
+        self as u8
+    }
+
+    /// Smallest kind that can represent operands of both kinds
+    ///
+    /// Integers widen to decimals; anything mixed with a float becomes a float.
+    pub fn common(self, other: Self) -> Self {
+        match (self, other) {
+            (OperandKind::Float, _) | (_, OperandKind::Float) => OperandKind::Float,
+            (OperandKind::Decimal, _) | (_, OperandKind::Decimal) => OperandKind::Decimal,
+            _ => OperandKind::Int,
+        }
+    }
+}
+
+/// A calculator operand or result
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Number {
+    Float(f64),
+    Int(i64),
+    /// Fixed-point value `mantissa / 10^scale`
+    Decimal {
+        mantissa: i64,
+        scale: u8,
+    },
+}
+
+impl Number {
+    /// Create a decimal, or `None` if `scale` exceeds [`MAX_SCALE`]
+    pub fn decimal(mantissa: i64, scale: u8) -> Option<Self> {
+        (scale <= MAX_SCALE).then_some(Number::Decimal { mantissa, scale })
+    }
+
+    /// Parse a literal, keeping it exact where possible
+    ///
+    /// Plain integers become [`Number::Int`], plain decimals (`"-12.50"`)
+    /// become [`Number::Decimal`] with the written scale. Exponent notation,
+    /// or literals too large for the exact kinds, become [`Number::Float`].
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let digits = s.strip_prefix(['-', '+']).unwrap_or(s);
+        let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        let is_plain = !(int.is_empty() && frac.is_empty())
+            && int.bytes().all(|b| b.is_ascii_digit())
+            && frac.bytes().all(|b| b.is_ascii_digit());
+        if !is_plain {
+            return s.parse().ok().map(Number::Float);
+        }
+
+        let exact = if digits.contains('.') {
+            let scale = u8::try_from(frac.len()).ok().filter(|&s| s <= MAX_SCALE);
+            let mantissa = format!("{}{}{}", &s[..s.len() - digits.len()], int, frac)
+                .parse()
+                .ok();
+            scale
+                .zip(mantissa)
+                .map(|(scale, mantissa)| Number::Decimal { mantissa, scale })
+        } else {
+            s.parse().ok().map(Number::Int)
+        };
+        exact.or_else(|| s.parse().ok().map(Number::Float))
+    }
+
+    /// Get the operand encoding of this value
+    pub fn kind(self) -> OperandKind {
+        match self {
+            Number::Float(_) => OperandKind::Float,
+            Number::Int(_) => OperandKind::Int,
+            Number::Decimal { .. } => OperandKind::Decimal,
+        }
+    }
+
+    /// Check if the value is held exactly (not as a float)
+    pub fn is_exact(self) -> bool {
+        self.kind() != OperandKind::Float
+    }
+
+    /// Convert to the nearest `f64`
+    pub fn to_f64(self) -> f64 {
+        match self {
+            Number::Float(x) => x,
+            Number::Int(i) => i as f64,
+            Number::Decimal { mantissa, scale } => mantissa as f64 / 10f64.powi(scale as i32),
+        }
+    }
+
+    /// Convert to the given encoding
+    ///
+    /// Widening (int to decimal, anything to float) always succeeds. Narrowing
+    /// succeeds only if the value is representable exactly.
+    pub fn to_kind(self, kind: OperandKind) -> Option<Self> {
+        match (kind, self) {
+            (OperandKind::Float, n) => Some(Number::Float(n.to_f64())),
+            (OperandKind::Decimal, Number::Int(i)) => Some(Number::Decimal {
+                mantissa: i,
+                scale: 0,
+            }),
+            (OperandKind::Decimal, n @ Number::Decimal { .. }) => Some(n),
+            (OperandKind::Int, n @ Number::Int(_)) => Some(n),
+            (OperandKind::Int, n @ Number::Decimal { .. }) => match n.exact().map(normalize) {
+                Some((m, 0)) => i64::try_from(m).ok().map(Number::Int),
+                _ => None,
+            },
+            (_, Number::Float(x)) => {
+                let fits = x.fract() == 0.0 && x >= i64::MIN as f64 && x < i64::MAX as f64;
+                fits.then_some(Number::Int(x as i64))?.to_kind(kind)
+            }
+        }
+    }
+
+    /// Exact value as `(mantissa, scale)`, or `None` for floats
+    fn exact(self) -> Option<(i128, u32)> {
+        match self {
+            Number::Float(_) => None,
+            Number::Int(i) => Some((i as i128, 0)),
+            Number::Decimal { mantissa, scale } => Some((mantissa as i128, scale as u32)),
+        }
+    }
+
+    /// Canonical result from an exact value, or `None` if it doesn't fit
+    fn from_exact(value: (i128, u32)) -> Option<Self> {
+        let (mantissa, scale) = normalize(value);
+        let mantissa = i64::try_from(mantissa).ok()?;
+        match u8::try_from(scale).ok().filter(|&s| s <= MAX_SCALE)? {
+            0 => Some(Number::Int(mantissa)),
+            scale => Some(Number::Decimal { mantissa, scale }),
+        }
+    }
+}
+
+impl From<i64> for Number {
+    fn from(i: i64) -> Self {
+        Number::Int(i)
+    }
+}
+
+impl From<f64> for Number {
+    fn from(x: f64) -> Self {
+        Number::Float(x)
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Number::Float(x) => write!(f, "{}", x),
+            Number::Int(i) => write!(f, "{}", i),
+            Number::Decimal { mantissa, scale: 0 } => write!(f, "{}", mantissa),
+            Number::Decimal { mantissa, scale } => {
+                let scale = scale as usize;
+                let digits = format!("{:0>width$}", mantissa.unsigned_abs(), width = scale + 1);
+                let (int, frac) = digits.split_at(digits.len() - scale);
+                let sign = if mantissa < 0 { "-" } else { "" };
+                write!(f, "{}{}.{}", sign, int, frac)
+            }
+        }
+    }
+}
+
+/// Calculator payload with exact operand encodings
+///
+/// Operands of differing kinds are widened to their
+/// [common kind](OperandKind::common) when constructed and encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExactCalcPayload {
+    pub op: Op,
+    pub a: Number,
+    pub b: Number,
+}
+
+impl ExactCalcPayload {
+    /// Create new exact calc payload
+    pub fn new(op: Op, a: impl Into<Number>, b: impl Into<Number>) -> Self {
+        let (a, b) = (a.into(), b.into());
+        let kind = a.kind().common(b.kind());
+        Self {
+            op,
+            a: a.to_kind(kind).unwrap_or(a),
+            b: b.to_kind(kind).unwrap_or(b),
+        }
+    }
+
+    /// Create unary operation (sqrt, etc.)
+    pub fn unary(op: Op, a: impl Into<Number>) -> Self {
+        let a = a.into();
+        let zero = Number::Int(0).to_kind(a.kind()).unwrap_or(Number::Int(0));
+        Self { op, a, b: zero }
+    }
+
+    /// Get the operand encoding (the tag byte)
+    pub fn kind(&self) -> OperandKind {
+        self.a.kind().common(self.b.kind())
+    }
+
+    /// Evaluate with the reference semantics, preserving exactness where possible
+    pub fn evaluate(&self) -> Result<Number, CalcError> {
+        self.op.apply_exact(self.a, self.b)
+    }
+
+    /// Serialize to bytes: [OP:1][TAG:1][A:8][B:8][A_SCALE:1][B_SCALE:1] = 20 bytes
+    pub fn to_bytes(&self) -> [u8; 20] {
+        let kind = self.kind();
+        let encode = |n: Number| -> ([u8; 8], u8) {
+            match n.to_kind(kind).unwrap_or(n) {
+                Number::Float(x) => (x.to_be_bytes(), 0),
+                Number::Int(i) => (i.to_be_bytes(), 0),
+                Number::Decimal { mantissa, scale } => (mantissa.to_be_bytes(), scale),
+            }
+        };
+        let (a, a_scale) = encode(self.a);
+        let (b, b_scale) = encode(self.b);
+
+        let mut bytes = [0u8; 20];
+        bytes[0] = self.op.to_byte();
+        bytes[1] = kind.to_byte();
+        bytes[2..10].copy_from_slice(&a);
+        bytes[10..18].copy_from_slice(&b);
+        bytes[18] = a_scale;
+        bytes[19] = b_scale;
+        bytes
+    }
+
+    /// Parse from bytes
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 20 {
+            return None;
+        }
+        let op = Op::from_byte(bytes[0])?;
+        let kind = OperandKind::from_byte(bytes[1])?;
+        let decode = |value: &[u8], scale: u8| -> Option<Number> {
+            let value: [u8; 8] = value.try_into().ok()?;
+            match kind {
+                OperandKind::Float if scale == 0 => Some(Number::Float(f64::from_be_bytes(value))),
+                OperandKind::Int if scale == 0 => Some(Number::Int(i64::from_be_bytes(value))),
+                OperandKind::Decimal => Number::decimal(i64::from_be_bytes(value), scale),
+                _ => None,
+            }
+        };
+        Some(Self {
+            op,
+            a: decode(&bytes[2..10], bytes[18])?,
+            b: decode(&bytes[10..18], bytes[19])?,
+        })
+    }
+}
+
+impl fmt::Display for ExactCalcPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crate::extended::fmt_calc(f, self.op, self.a, self.b)
+    }
+}
+
+impl Op {
+    /// Apply the operation, staying exact where possible (`b` is ignored for unary operations)
+    ///
+    /// Errors are those of [`Op::apply`].
+    pub fn apply_exact(self, a: Number, b: Number) -> Result<Number, CalcError> {
+        let b_exact = if self.is_unary() {
+            Some((0, 0))
+        } else {
+            b.exact()
+        };
+        let exact = a
+            .exact()
+            .zip(b_exact)
+            .and_then(|(a, b)| exact_apply(self, normalize(a), normalize(b)))
+            .and_then(Number::from_exact);
+        match exact {
+            Some(n) => Ok(n),
+            None => self.apply(a.to_f64(), b.to_f64()).map(Number::Float),
+        }
+    }
+}
+
+/// Strip trailing zeros from an exact value
+fn normalize((mut m, mut s): (i128, u32)) -> (i128, u32) {
+    while s > 0 && m % 10 == 0 {
+        m /= 10;
+        s -= 1;
+    }
+    (m, s)
+}
+
+fn pow10(k: u32) -> Option<i128> {
+    10i128.checked_pow(k)
+}
+
+/// Bring two exact values to a common scale
+fn align((ma, sa): (i128, u32), (mb, sb): (i128, u32)) -> Option<(i128, i128, u32)> {
+    let s = sa.max(sb);
+    Some((
+        ma.checked_mul(pow10(s - sa)?)?,
+        mb.checked_mul(pow10(s - sb)?)?,
+        s,
+    ))
+}
+
+/// Exact result of `op`, or `None` if it is not exactly representable or is
+/// an error (the float path then produces the result or the error)
+fn exact_apply(op: Op, a: (i128, u32), b: (i128, u32)) -> Option<(i128, u32)> {
+    let (ma, sa) = a;
+    let (mb, sb) = b;
+    match op {
+        Op::Add => align(a, b).and_then(|(x, y, s)| Some((x.checked_add(y)?, s))),
+        Op::Sub => align(a, b).and_then(|(x, y, s)| Some((x.checked_sub(y)?, s))),
+        Op::Mul => Some((ma.checked_mul(mb)?, sa + sb)),
+        Op::PercentOf => Some((ma.checked_mul(mb)?, sa + sb + 2)),
+        Op::Div if mb == 0 => None,
+        Op::Div => {
+            // a / b = (ma * 10^sb) / (mb * 10^sa); find the smallest scale at
+            // which the quotient is an integer mantissa
+            let den = mb.checked_mul(pow10(sa)?)?;
+            (0..=MAX_SCALE as u32).find_map(|s| {
+                let num = ma.checked_mul(pow10(sb + s)?)?;
+                (num % den == 0).then(|| (num / den, s))
+            })
+        }
+        Op::Mod if mb == 0 => None,
+        Op::Mod => align(a, b).and_then(|(x, y, s)| Some((x.checked_rem(y)?, s))),
+        Op::Pow if sb != 0 || mb < 0 => None,
+        Op::Pow => {
+            let e = u32::try_from(mb).ok()?;
+            Some((ma.checked_pow(e)?, sa.checked_mul(e)?))
+        }
+        Op::Sqrt if ma < 0 => None,
+        Op::Sqrt => {
+            // An odd scale needs one more digit to take the root of 10^scale
+            let (m, s) = if sa % 2 == 1 {
+                (ma.checked_mul(10)?, sa + 1)
+            } else {
+                (ma, sa)
+            };
+            let root = m.isqrt();
+            (root * root == m).then_some((root, s / 2))
+        }
+        Op::Abs => Some((ma.checked_abs()?, sa)),
+        Op::Neg => Some((ma.checked_neg()?, sa)),
+        Op::Floor => Some((ma.div_euclid(pow10(sa)?), 0)),
+        Op::Ceil => Some((-(-ma).div_euclid(pow10(sa)?), 0)),
+        Op::Round => {
+            // Half away from zero
+            let p = pow10(sa)?;
+            let (q, r) = (ma.abs() / p, ma.abs() % p);
+            let q = if r * 2 >= p { q + 1 } else { q };
+            Some((q * ma.signum(), 0))
+        }
+        Op::Min | Op::Max => {
+            let (x, y, _) = align(a, b)?;
+            let a_wins = match x.cmp(&y) {
+                Ordering::Less => op == Op::Min,
+                Ordering::Greater => op == Op::Max,
+                Ordering::Equal => true,
+            };
+            Some(if a_wins { a } else { b })
+        }
+        Op::Factorial if sa != 0 || !(0..=MAX_EXACT_FACTORIAL).contains(&ma) => None,
+        Op::Factorial => Some(((2..=ma).product(), 0)),
+        Op::Log
+        | Op::Ln
+        | Op::Exp
+        | Op::Sin
+        | Op::Cos
+        | Op::Tan
+        | Op::Asin
+        | Op::Acos
+        | Op::Atan => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn num(s: &str) -> Number {
+        Number::parse(s).unwrap()
+    }
+
+    fn eval(op: Op, a: &str, b: &str) -> Result<Number, CalcError> {
+        ExactCalcPayload::new(op, num(a), num(b)).evaluate()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(num("42"), Number::Int(42));
+        assert_eq!(num("-7"), Number::Int(-7));
+        assert_eq!(
+            num("0.1"),
+            Number::Decimal {
+                mantissa: 1,
+                scale: 1
+            }
+        );
+        assert_eq!(
+            num("-12.50"),
+            Number::Decimal {
+                mantissa: -1250,
+                scale: 2
+            }
+        );
+        assert_eq!(
+            num(".5"),
+            Number::Decimal {
+                mantissa: 5,
+                scale: 1
+            }
+        );
+        assert_eq!(num("1e3"), Number::Float(1000.0));
+        assert_eq!(num("99999999999999999999"), Number::Float(1e20));
+        assert_eq!(Number::parse("abc"), None);
+        assert_eq!(Number::parse("-"), None);
+        assert_eq!(Number::parse("."), None);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(num("0.05").to_string(), "0.05");
+        assert_eq!(num("-12.50").to_string(), "-12.50");
+        assert_eq!(
+            Number::Decimal {
+                mantissa: -5,
+                scale: 3
+            }
+            .to_string(),
+            "-0.005"
+        );
+        assert_eq!(Number::Int(i64::MIN).to_string(), "-9223372036854775808");
+
+        let calc = ExactCalcPayload::new(Op::Add, num("0.1"), num("0.2"));
+        assert_eq!(calc.to_string(), "0.1 + 0.2");
+    }
+
+    #[test]
+    fn test_decimal_arithmetic_is_exact() {
+        assert_eq!(eval(Op::Add, "0.1", "0.2"), Ok(num("0.3")));
+        assert_eq!(eval(Op::Sub, "1.10", "0.1"), Ok(Number::Int(1)));
+        assert_eq!(eval(Op::Mul, "1.5", "1.5"), Ok(num("2.25")));
+        assert_eq!(eval(Op::Div, "1", "4"), Ok(num("0.25")));
+        assert_eq!(eval(Op::Div, "6", "3"), Ok(Number::Int(2)));
+        assert_eq!(eval(Op::Div, "0.3", "0.1"), Ok(Number::Int(3)));
+        assert_eq!(eval(Op::Mod, "-7.5", "2"), Ok(num("-1.5")));
+        assert_eq!(eval(Op::PercentOf, "15", "80"), Ok(Number::Int(12)));
+        assert_eq!(eval(Op::Pow, "0.5", "3"), Ok(num("0.125")));
+        assert_eq!(eval(Op::Min, "0.30", "0.3"), Ok(num("0.3")));
+        assert_eq!(eval(Op::Max, "-1", "-0.5"), Ok(num("-0.5")));
+    }
+
+    #[test]
+    fn test_large_integers_are_exact() {
+        let big = (1i64 << 53) + 1;
+        let calc = ExactCalcPayload::new(Op::Add, big, 1i64);
+        assert_eq!(calc.evaluate(), Ok(Number::Int(big + 1)));
+
+        let calc = ExactCalcPayload::new(Op::Mul, 3_037_000_499i64, 3_037_000_499i64);
+        assert_eq!(calc.evaluate(), Ok(Number::Int(9_223_372_030_926_249_001)));
+
+        let fact = ExactCalcPayload::unary(Op::Factorial, 20i64);
+        assert_eq!(fact.evaluate(), Ok(Number::Int(2_432_902_008_176_640_000)));
+    }
+
+    #[test]
+    fn test_unary_exact() {
+        let unary = |op, a: &str| ExactCalcPayload::unary(op, num(a)).evaluate();
+        assert_eq!(unary(Op::Sqrt, "2.25"), Ok(num("1.5")));
+        assert_eq!(unary(Op::Sqrt, "0.9"), Ok(Number::Float(0.9f64.sqrt())));
+        assert_eq!(unary(Op::Floor, "-2.5"), Ok(Number::Int(-3)));
+        assert_eq!(unary(Op::Ceil, "-2.5"), Ok(Number::Int(-2)));
+        assert_eq!(unary(Op::Round, "-2.5"), Ok(Number::Int(-3)));
+        assert_eq!(unary(Op::Round, "2.49"), Ok(Number::Int(2)));
+        assert_eq!(unary(Op::Abs, "-0.1"), Ok(num("0.1")));
+        assert_eq!(unary(Op::Neg, "7"), Ok(Number::Int(-7)));
+    }
+
+    #[test]
+    fn test_inexact_falls_back_to_float() {
+        assert_eq!(eval(Op::Div, "1", "3"), Ok(Number::Float(1.0 / 3.0)));
+        assert_eq!(eval(Op::Pow, "2", "-1"), Ok(Number::Float(0.5)));
+        assert_eq!(eval(Op::Add, "0.5", "1e0"), Ok(Number::Float(1.5)));
+        assert_eq!(
+            ExactCalcPayload::new(Op::Add, i64::MAX, 1i64).evaluate(),
+            Ok(Number::Float(i64::MAX as f64 + 1.0))
+        );
+        assert_eq!(
+            ExactCalcPayload::unary(Op::Factorial, 25i64).evaluate(),
+            Ok(Number::Float((1..=25).map(|k| k as f64).product()))
+        );
+    }
+
+    #[test]
+    fn test_errors_match_float_semantics() {
+        assert_eq!(eval(Op::Div, "1", "0.0"), Err(CalcError::DivisionByZero));
+        assert_eq!(eval(Op::Mod, "1", "0"), Err(CalcError::DivisionByZero));
+        assert_eq!(eval(Op::Pow, "0", "-1"), Err(CalcError::DivisionByZero));
+        assert!(matches!(
+            ExactCalcPayload::unary(Op::Sqrt, -4i64).evaluate(),
+            Err(CalcError::Domain { op: Op::Sqrt, .. })
+        ));
+        assert!(matches!(
+            ExactCalcPayload::unary(Op::Factorial, num("2.5")).evaluate(),
+            Err(CalcError::Domain {
+                op: Op::Factorial,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_mixed_kinds_widen() {
+        let calc = ExactCalcPayload::new(Op::Add, 1i64, num("0.5"));
+        assert_eq!(calc.kind(), OperandKind::Decimal);
+        assert_eq!(
+            calc.a,
+            Number::Decimal {
+                mantissa: 1,
+                scale: 0
+            }
+        );
+
+        let calc = ExactCalcPayload::new(Op::Add, 1i64, 0.5);
+        assert_eq!(calc.kind(), OperandKind::Float);
+        assert_eq!(calc.a, Number::Float(1.0));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let payloads = [
+            ExactCalcPayload::new(Op::Add, num("0.1"), num("-0.25")),
+            ExactCalcPayload::new(Op::Mul, i64::MAX, i64::MIN),
+            ExactCalcPayload::new(Op::Div, 1.5, 0.25),
+            ExactCalcPayload::unary(Op::Sqrt, num("2.25")),
+        ];
+        for calc in payloads {
+            let bytes = calc.to_bytes();
+            assert_eq!(bytes[1], calc.kind().to_byte());
+            assert_eq!(ExactCalcPayload::from_bytes(&bytes), Some(calc));
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_invalid() {
+        let mut bytes = ExactCalcPayload::new(Op::Add, num("0.1"), num("0.2")).to_bytes();
+        bytes[18] = MAX_SCALE + 1;
+        assert_eq!(ExactCalcPayload::from_bytes(&bytes), None);
+
+        let mut bytes = ExactCalcPayload::new(Op::Add, 1i64, 2i64).to_bytes();
+        bytes[1] = 0x03;
+        assert_eq!(ExactCalcPayload::from_bytes(&bytes), None);
+        bytes[1] = OperandKind::Int.to_byte();
+        bytes[19] = 1;
+        assert_eq!(ExactCalcPayload::from_bytes(&bytes), None);
+        assert_eq!(ExactCalcPayload::from_bytes(&bytes[..19]), None);
+    }
+}
@@ -0,0 +1,639 @@
+//! Expression-tree calculator payload
+//!
+//! Encodes multi-step arithmetic such as `(3 + 4) * 2` as a small postfix
+//! (RPN) program over constants and [`Op`]s, so a single instruction carries
+//! the whole computation:
+//!
+//! ```text
+//! [COUNT:1][TOKEN]...
+//!
+//! TOKEN = [0x00][VALUE:8]   constant (f64, big-endian)
+//!       | [0x01][OP:1]      operation
+//! ```
+//!
+//! Programs are bounded to [`MAX_EXPR_TOKENS`] tokens and an evaluation
+//! stack depth of [`MAX_EXPR_DEPTH`].
+
+use crate::calc::CalcError;
+use crate::Op;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+
+/// Maximum evaluation stack depth
+pub const MAX_EXPR_DEPTH: usize = 16;
+
+/// Maximum number of tokens in an expression
+pub const MAX_EXPR_TOKENS: usize = u8::MAX as usize;
+
+const TAG_CONST: u8 = 0x00;
+const TAG_OP: u8 = 0x01;
+
+/// Single postfix program token
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ExprToken {
+    /// Push a constant
+    Const(f64),
+    /// Pop operands, push the result
+    Op(Op),
+}
+
+/// Postfix arithmetic program payload
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExprPayload {
+    /// Program tokens in postfix order
+    pub tokens: Vec<ExprToken>,
+}
+
+impl ExprPayload {
+    /// Create from postfix tokens, checking that the program is well-formed
+    pub fn new(tokens: Vec<ExprToken>) -> Result<Self, ExprError> {
+        let expr = Self { tokens };
+        expr.validate()?;
+        Ok(expr)
+    }
+
+    /// Parse an infix expression such as `(3 + 4) * 2` or `sqrt(16) ^ 2`
+    ///
+    /// Supports `+ - * / % ^` (with `^` right-associative and binding tighter
+    /// than unary minus), postfix `!`, parentheses, and function calls using
+    /// the [`Op::symbol`] names (`sqrt(x)`, `sin(x)`, `max(a, b)`, `pct(a, b)`, ...).
+    pub fn parse(infix: &str) -> Result<Self, ExprError> {
+        Parser::new(infix).parse()
+    }
+
+    /// Maximum stack depth reached during evaluation
+    pub fn depth(&self) -> Result<usize, ExprError> {
+        if self.tokens.len() > MAX_EXPR_TOKENS {
+            return Err(ExprError::TooLong);
+        }
+        let mut depth = 0usize;
+        let mut max_depth = 0usize;
+        for token in &self.tokens {
+            match token {
+                ExprToken::Const(_) => depth += 1,
+                ExprToken::Op(op) => {
+                    let arity = op.arity() as usize;
+                    if depth < arity {
+                        return Err(ExprError::Malformed("operator is missing operands"));
+                    }
+                    depth -= arity - 1;
+                }
+            }
+            max_depth = max_depth.max(depth);
+        }
+        match depth {
+            0 => Err(ExprError::Malformed("empty expression")),
+            1 => Ok(max_depth),
+            _ => Err(ExprError::Malformed("unused operands")),
+        }
+    }
+
+    /// Check that the program is well-formed and within bounds
+    pub fn validate(&self) -> Result<(), ExprError> {
+        if self.depth()? > MAX_EXPR_DEPTH {
+            return Err(ExprError::TooDeep);
+        }
+        Ok(())
+    }
+
+    /// Evaluate the program with the reference semantics of [`Op::apply`]
+    pub fn evaluate(&self) -> Result<f64, ExprError> {
+        self.validate()?;
+        let mut stack: Vec<f64> = Vec::with_capacity(MAX_EXPR_DEPTH);
+        for token in &self.tokens {
+            match *token {
+                ExprToken::Const(value) => stack.push(value),
+                ExprToken::Op(op) => {
+                    // Depth was validated above, so the pops cannot fail
+                    let b = if op.is_unary() {
+                        0.0
+                    } else {
+                        stack.pop().unwrap_or_default()
+                    };
+                    let a = stack.pop().unwrap_or_default();
+                    stack.push(op.apply(a, b)?);
+                }
+            }
+        }
+        Ok(stack.pop().unwrap_or_default())
+    }
+
+    /// Serialize to bytes: [COUNT:1][TOKEN]...
+    ///
+    /// A program over [`MAX_EXPR_TOKENS`] is encoded as the empty program,
+    /// which fails [`validate`](Self::validate) once decoded. (Truncating it
+    /// instead could leave a well-formed program computing something else.)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        if self.tokens.len() > MAX_EXPR_TOKENS {
+            return vec![0];
+        }
+        let mut bytes = Vec::with_capacity(1 + self.tokens.len() * 9);
+        bytes.push(self.tokens.len() as u8);
+        for token in &self.tokens {
+            match token {
+                ExprToken::Const(value) => {
+                    bytes.push(TAG_CONST);
+                    bytes.extend_from_slice(&value.to_be_bytes());
+                }
+                ExprToken::Op(op) => {
+                    bytes.push(TAG_OP);
+                    bytes.push(op.to_byte());
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Parse from bytes
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (&count, mut rest) = bytes.split_first()?;
+        let mut tokens = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let (&tag, tail) = rest.split_first()?;
+            match tag {
+                TAG_CONST => {
+                    let value = f64::from_be_bytes(tail.get(..8)?.try_into().ok()?);
+                    tokens.push(ExprToken::Const(value));
+                    rest = &tail[8..];
+                }
+                TAG_OP => {
+                    tokens.push(ExprToken::Op(Op::from_byte(*tail.first()?)?));
+                    rest = &tail[1..];
+                }
+                _ => return None,
+            }
+        }
+        Some(Self { tokens })
+    }
+}
+
+/// Binding strength of infix operations, used for parsing and display
+fn precedence(op: Op) -> u8 {
+    match op {
+        Op::Add | Op::Sub => 1,
+        Op::Mul | Op::Div | Op::Mod => 2,
+        _ => 4,
+    }
+}
+
+/// Precedence of prefix negation (between `*` and `^`, so `-2^2 == -4`)
+const NEG_PRECEDENCE: u8 = 3;
+
+/// Precedence of postfix factorial (binds tighter than `^`)
+const FACTORIAL_PRECEDENCE: u8 = 5;
+
+/// Precedence of atoms (constants and function calls)
+const ATOM_PRECEDENCE: u8 = u8::MAX;
+
+impl fmt::Display for ExprPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.depth().is_err() {
+            // Not renderable as infix: fall back to the raw postfix program
+            let rpn: Vec<String> = self
+                .tokens
+                .iter()
+                .map(|t| match t {
+                    ExprToken::Const(v) => v.to_string(),
+                    ExprToken::Op(op) => op.symbol().to_string(),
+                })
+                .collect();
+            return write!(f, "[{}]", rpn.join(" "));
+        }
+
+        let mut stack: Vec<(String, u8)> = Vec::new();
+        for token in &self.tokens {
+            match *token {
+                ExprToken::Const(v) if v.is_sign_negative() => {
+                    stack.push((v.to_string(), NEG_PRECEDENCE))
+                }
+                ExprToken::Const(v) => stack.push((v.to_string(), ATOM_PRECEDENCE)),
+                ExprToken::Op(Op::Neg) => {
+                    let (a, a_prec) = stack.pop().unwrap_or_default();
+                    let a = if a_prec < NEG_PRECEDENCE {
+                        format!("({})", a)
+                    } else {
+                        a
+                    };
+                    stack.push((format!("-{}", a), NEG_PRECEDENCE));
+                }
+                ExprToken::Op(Op::Factorial) => {
+                    let (a, a_prec) = stack.pop().unwrap_or_default();
+                    let a = if a_prec < ATOM_PRECEDENCE {
+                        format!("({})", a)
+                    } else {
+                        a
+                    };
+                    stack.push((format!("{}!", a), FACTORIAL_PRECEDENCE));
+                }
+                ExprToken::Op(op) if op.is_unary() => {
+                    let (a, _) = stack.pop().unwrap_or_default();
+                    stack.push((format!("{}({})", op.symbol(), a), ATOM_PRECEDENCE));
+                }
+                ExprToken::Op(op) if !op.is_infix() => {
+                    let (b, _) = stack.pop().unwrap_or_default();
+                    let (a, _) = stack.pop().unwrap_or_default();
+                    stack.push((format!("{}({}, {})", op.symbol(), a, b), ATOM_PRECEDENCE));
+                }
+                ExprToken::Op(op) => {
+                    let (b, b_prec) = stack.pop().unwrap_or_default();
+                    let (a, a_prec) = stack.pop().unwrap_or_default();
+                    let prec = precedence(op);
+                    let right_assoc = matches!(op, Op::Pow);
+                    let wrap_a = a_prec < prec || (right_assoc && a_prec == prec);
+                    let wrap_b = b_prec < prec || (!right_assoc && b_prec == prec);
+                    let a = if wrap_a { format!("({})", a) } else { a };
+                    let b = if wrap_b { format!("({})", b) } else { b };
+                    stack.push((format!("{} {} {}", a, op.symbol(), b), prec));
+                }
+            }
+        }
+        write!(f, "{}", stack.pop().unwrap_or_default().0)
+    }
+}
+
+/// Operator-stack entry for the shunting-yard parser
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StackItem {
+    Binary(Op),
+    Negate,
+    Function(Op),
+    LeftParen,
+}
+
+/// Infix to postfix parser (shunting-yard)
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    output: Vec<ExprToken>,
+    stack: Vec<StackItem>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            output: Vec::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    fn parse(mut self) -> Result<ExprPayload, ExprError> {
+        let mut expect_operand = true;
+
+        while let Some(c) = self.peek() {
+            let start = self.pos;
+            match c {
+                c if c.is_whitespace() => self.bump(c),
+                '0'..='9' | '.' if expect_operand => {
+                    let value = self.number()?;
+                    self.output.push(ExprToken::Const(value));
+                    expect_operand = false;
+                }
+                'a'..='z' | 'A'..='Z' if expect_operand => {
+                    let name = self.identifier();
+                    let lower = name.to_ascii_lowercase();
+                    let op = Op::ALL
+                        .into_iter()
+                        .filter(|op| !op.is_infix() && !matches!(op, Op::Neg | Op::Factorial))
+                        .find(|op| op.symbol() == lower)
+                        .ok_or_else(|| ExprError::UnknownFunction(name.to_string()))?;
+                    if self.skip_whitespace_then('(') {
+                        self.stack.push(StackItem::Function(op));
+                        self.stack.push(StackItem::LeftParen);
+                    } else {
+                        return Err(ExprError::Malformed("function call needs parentheses"));
+                    }
+                }
+                '(' if expect_operand => {
+                    self.bump(c);
+                    self.stack.push(StackItem::LeftParen);
+                }
+                '-' if expect_operand => {
+                    self.bump(c);
+                    self.stack.push(StackItem::Negate);
+                }
+                '+' if expect_operand => self.bump(c),
+                ')' if !expect_operand => {
+                    self.bump(c);
+                    self.close_paren()?;
+                }
+                ',' if !expect_operand => {
+                    self.bump(c);
+                    self.next_argument()?;
+                    expect_operand = true;
+                }
+                '!' if !expect_operand => {
+                    self.bump(c);
+                    self.output.push(ExprToken::Op(Op::Factorial));
+                }
+                '+' | '-' | '*' | '/' | '%' | '^' if !expect_operand => {
+                    self.bump(c);
+                    let op = Op::from_byte(c as u8).ok_or(ExprError::UnexpectedChar(c, start))?;
+                    self.push_binary(op);
+                    expect_operand = true;
+                }
+                _ => return Err(ExprError::UnexpectedChar(c, start)),
+            }
+        }
+
+        if expect_operand {
+            return Err(ExprError::Malformed("unexpected end of expression"));
+        }
+        while let Some(item) = self.stack.pop() {
+            if item == StackItem::LeftParen {
+                return Err(ExprError::UnbalancedParens);
+            }
+            self.emit(item);
+        }
+
+        ExprPayload::new(self.output)
+    }
+
+    fn push_binary(&mut self, op: Op) {
+        let prec = precedence(op);
+        let right_assoc = matches!(op, Op::Pow);
+        while let Some(&top) = self.stack.last() {
+            let top_prec = match top {
+                StackItem::Binary(top_op) => precedence(top_op),
+                StackItem::Negate => NEG_PRECEDENCE,
+                StackItem::Function(_) | StackItem::LeftParen => break,
+            };
+            if top_prec > prec || (top_prec == prec && !right_assoc) {
+                self.stack.pop();
+                self.emit(top);
+            } else {
+                break;
+            }
+        }
+        self.stack.push(StackItem::Binary(op));
+    }
+
+    fn close_paren(&mut self) -> Result<(), ExprError> {
+        loop {
+            match self.stack.pop() {
+                None => return Err(ExprError::UnbalancedParens),
+                Some(StackItem::LeftParen) => break,
+                Some(item) => self.emit(item),
+            }
+        }
+        if let Some(&StackItem::Function(op)) = self.stack.last() {
+            self.stack.pop();
+            self.output.push(ExprToken::Op(op));
+        }
+        Ok(())
+    }
+
+    fn next_argument(&mut self) -> Result<(), ExprError> {
+        while let Some(&item) = self.stack.last() {
+            if item == StackItem::LeftParen {
+                return Ok(());
+            }
+            self.stack.pop();
+            self.emit(item);
+        }
+        Err(ExprError::Malformed(
+            "argument separator outside function call",
+        ))
+    }
+
+    fn emit(&mut self, item: StackItem) {
+        match item {
+            StackItem::Binary(op) | StackItem::Function(op) => self.output.push(ExprToken::Op(op)),
+            StackItem::Negate => self.output.push(ExprToken::Op(Op::Neg)),
+            StackItem::LeftParen => {}
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self, c: char) {
+        self.pos += c.len_utf8();
+    }
+
+    fn skip_whitespace_then(&mut self, expected: char) -> bool {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.bump(c);
+        }
+        if self.peek() == Some(expected) {
+            self.bump(expected);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn identifier(&mut self) -> &'a str {
+        let start = self.pos;
+        while let Some(c) = self.peek().filter(|c| c.is_ascii_alphanumeric()) {
+            self.bump(c);
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn number(&mut self) -> Result<f64, ExprError> {
+        let start = self.pos;
+        let mut prev = '\0';
+        while let Some(c) = self.peek() {
+            let exponent_sign = (c == '-' || c == '+') && (prev == 'e' || prev == 'E');
+            if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || exponent_sign {
+                self.bump(c);
+                prev = c;
+            } else {
+                break;
+            }
+        }
+        let text = &self.input[start..self.pos];
+        text.parse::<f64>()
+            .map_err(|_| ExprError::InvalidNumber(text.to_string()))
+    }
+}
+
+/// Errors that can occur when parsing or evaluating expressions
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum ExprError {
+    #[error("Unexpected character '{0}' at position {1}")]
+    UnexpectedChar(char, usize),
+
+    #[error("Invalid number: {0}")]
+    InvalidNumber(String),
+
+    #[error("Unknown function: {0}")]
+    UnknownFunction(String),
+
+    #[error("Unbalanced parentheses")]
+    UnbalancedParens,
+
+    #[error("Malformed expression: {0}")]
+    Malformed(&'static str),
+
+    #[error("Expression exceeds maximum depth of {MAX_EXPR_DEPTH}")]
+    TooDeep,
+
+    #[error("Expression exceeds maximum length of {MAX_EXPR_TOKENS} tokens")]
+    TooLong,
+
+    #[error(transparent)]
+    Calc(#[from] CalcError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(s: &str) -> Result<f64, ExprError> {
+        ExprPayload::parse(s)?.evaluate()
+    }
+
+    #[test]
+    fn test_parse_to_postfix() {
+        let expr = ExprPayload::parse("(3 + 4) * 2").unwrap();
+        assert_eq!(
+            expr.tokens,
+            vec![
+                ExprToken::Const(3.0),
+                ExprToken::Const(4.0),
+                ExprToken::Op(Op::Add),
+                ExprToken::Const(2.0),
+                ExprToken::Op(Op::Mul),
+            ]
+        );
+        assert_eq!(expr.evaluate(), Ok(14.0));
+    }
+
+    #[test]
+    fn test_precedence_and_associativity() {
+        assert_eq!(eval("3 + 4 * 2"), Ok(11.0));
+        assert_eq!(eval("10 - 4 - 3"), Ok(3.0));
+        assert_eq!(eval("2 ^ 3 ^ 2"), Ok(512.0));
+        assert_eq!(eval("-2 ^ 2"), Ok(-4.0));
+        assert_eq!(eval("2 ^ -1"), Ok(0.5));
+        assert_eq!(eval("sqrt(9 + 16) * 2"), Ok(10.0));
+        assert_eq!(eval("1.5e2 / 3"), Ok(50.0));
+        assert_eq!(eval("-3!"), Ok(-6.0));
+        assert_eq!(eval("2 ^ 3!"), Ok(64.0));
+        assert_eq!(eval("(1 + 2)! * 2"), Ok(12.0));
+    }
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(eval("max(2, 3 * 4) + min(1, -1)"), Ok(11.0));
+        assert_eq!(eval("pct(15, 80)"), Ok(12.0));
+        assert_eq!(
+            eval("abs(-4) + floor(2.7) + ceil(2.1) + round(2.5)"),
+            Ok(12.0)
+        );
+        assert_eq!(eval("log(1000) + ln(1) + exp(0)"), Ok(4.0));
+        assert_eq!(eval("cos(0) + SIN(0)"), Ok(1.0));
+
+        assert_eq!(
+            ExprPayload::parse("-(3 + 4)").unwrap().tokens.last(),
+            Some(&ExprToken::Op(Op::Neg))
+        );
+        assert!(matches!(
+            ExprPayload::parse("1, 2"),
+            Err(ExprError::Malformed(_))
+        ));
+        assert!(matches!(
+            ExprPayload::parse("max(1)"),
+            Err(ExprError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            ExprPayload::parse("(1 + 2"),
+            Err(ExprError::UnbalancedParens)
+        );
+        assert_eq!(
+            ExprPayload::parse("1 + 2)"),
+            Err(ExprError::UnbalancedParens)
+        );
+        assert!(matches!(
+            ExprPayload::parse("1 +"),
+            Err(ExprError::Malformed(_))
+        ));
+        assert_eq!(
+            ExprPayload::parse("1 $ 2"),
+            Err(ExprError::UnexpectedChar('$', 2))
+        );
+        assert_eq!(
+            ExprPayload::parse("cbrt(8)"),
+            Err(ExprError::UnknownFunction("cbrt".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_evaluation_errors() {
+        assert_eq!(
+            eval("1 / (2 - 2)"),
+            Err(ExprError::Calc(CalcError::DivisionByZero))
+        );
+        assert_eq!(
+            eval("10 ^ 200 * 10 ^ 200"),
+            Err(ExprError::Calc(CalcError::Overflow(Op::Mul)))
+        );
+        assert!(matches!(
+            eval("sqrt(1 - 5)"),
+            Err(ExprError::Calc(CalcError::Domain { op: Op::Sqrt, .. }))
+        ));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let deep = format!("{}1{}", "(1 + ".repeat(20), ")".repeat(20));
+        assert_eq!(ExprPayload::parse(&deep), Err(ExprError::TooDeep));
+
+        let long = vec![ExprToken::Const(1.0); MAX_EXPR_TOKENS + 1];
+        assert_eq!(ExprPayload::new(long), Err(ExprError::TooLong));
+
+        let underflow = vec![ExprToken::Const(1.0), ExprToken::Op(Op::Add)];
+        assert!(matches!(
+            ExprPayload::new(underflow),
+            Err(ExprError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let expr = ExprPayload::parse("(3 + 4) * 2 - sqrt(2)").unwrap();
+        let bytes = expr.to_bytes();
+        assert_eq!(bytes[0] as usize, expr.tokens.len());
+        assert_eq!(ExprPayload::from_bytes(&bytes).unwrap(), expr);
+
+        assert!(ExprPayload::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+
+        // Over the token limit: 1 (2 +)x127 is well-formed, so the trailing
+        // negation must not simply be cut off
+        let mut tokens = vec![ExprToken::Const(1.0)];
+        for _ in 0..127 {
+            tokens.extend([ExprToken::Const(2.0), ExprToken::Op(Op::Add)]);
+        }
+        tokens.push(ExprToken::Op(Op::Neg));
+        assert_eq!(tokens.len(), MAX_EXPR_TOKENS + 1);
+        let decoded = ExprPayload::from_bytes(&ExprPayload { tokens }.to_bytes()).unwrap();
+        assert!(decoded.validate().is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        for s in [
+            "(3 + 4) * 2",
+            "2 ^ 3 ^ 2",
+            "(2 ^ 3) ^ 2",
+            "10 - (4 - 3)",
+            "sqrt(2) + 1",
+            "-2 ^ 2",
+            "(-2) ^ 2",
+            "-(3 + 4)",
+            "(1 + 2)! * 2",
+            "max(1, 2 + 3)",
+        ] {
+            assert_eq!(ExprPayload::parse(s).unwrap().to_string(), s);
+        }
+    }
+}
@@ -10,83 +10,273 @@
 //! - 0x00: None (base instruction only)
 //! - 0x01: CalcArgs (17 bytes: [OP:1][A:8][B:8])
 //! - 0x02: TimeArgs (14 bytes: [REF:8][DELTA:4][UNIT:1][TZ:1])
+//! - 0x03: TimerArgs (22 bytes: [DUR:4][UNIT:1][REPEAT:2][INTERVAL:4][FLAGS:1][LABEL:2][DEADLINE:8])
+//! - 0x04: ConversionArgs (12 bytes: [VALUE:8][FROM:2][TO:2])
+//! - 0x05: ExprArgs (variable: [LEN:2][COUNT:1][TOKEN]...)
+//! - 0x06: ExactCalcArgs (20 bytes: [OP:1][TAG:1][A:8][B:8][A_SCALE:1][B_SCALE:1])
+//! - 0x07: ZonedTimeArgs (17 bytes: [REF:8][DELTA:4][UNIT:1][OFFSET_MIN:2][ZONE:2])
+//! - 0x08: ScheduleArgs (31 bytes: [START:8][DURATION:4][OFFSET_MIN:2][ZONE:2][FREQ:1][INTERVAL:2][WEEKDAYS:1][COUNT:2][FLAGS:1][UNTIL:8])
+//! - 0x09: RagQueryArgs (variable: [LEN:2][TOP_K:2][MIN_SCORE:4][QUERY_LEN:2][QUERY][COUNT:1][FILTER]...)
+//! - 0x0A: RagRefArgs (12 bytes: [COLLECTION:4][DOC_ID:8])
+//! - 0x0B: TemplateArgs (variable: [LEN:2][TEMPLATE_ID:2][COUNT:1][BINDING]...)
+//! - 0x80-0xFF: Application-defined (variable: [LEN:2][DATA:LEN])
 //!
 //! This format allows opcodes to be self-contained, carrying all data
 //! needed for execution without external context.
 
-use crate::{Instruction, InstructionError, INSTRUCTION_SIZE};
+use crate::calendar::TimeArithmetic;
+use crate::clock::{Clock, ClockError, SystemClock};
+use crate::custom::CustomData;
+use crate::exact::ExactCalcPayload;
+use crate::expr::ExprPayload;
+use crate::rag::{RagAddress, RagQueryPayload};
+use crate::schedule::SchedulePayload;
+use crate::template::TemplatePayload;
+use crate::tz::{format_offset, ZoneId, MAX_OFFSET_MINUTES, MIN_OFFSET_MINUTES};
+use crate::units::ConversionPayload;
+use crate::{Action, Instruction, InstructionError, INSTRUCTION_SIZE};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// Payload type identifier
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[repr(u8)]
+///
+/// Type bytes 0x00-0x7F are reserved for payloads defined by this crate;
+/// 0x80-0xFF are available for application-defined payloads (see [`crate::custom`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum PayloadType {
     /// No payload (base instruction only)
-    None = 0x00,
+    None,
     /// Calculator arguments: [OP:1][A:8][B:8] = 17 bytes
-    Calc = 0x01,
+    Calc,
     /// Time arguments: [REF:8][DELTA:4][UNIT:1][TZ:1] = 14 bytes
-    Time = 0x02,
+    Time,
+    /// Timer arguments: [DUR:4][UNIT:1][REPEAT:2][INTERVAL:4][FLAGS:1][LABEL:2][DEADLINE:8] = 22 bytes
+    Timer,
+    /// Unit conversion arguments: [VALUE:8][FROM:2][TO:2] = 12 bytes
+    Conversion,
+    /// Postfix arithmetic program: [LEN:2][COUNT:1][TOKEN]...
+    Expr,
+    /// Calculator arguments with exact operands: [OP:1][TAG:1][A:8][B:8][A_SCALE:1][B_SCALE:1] = 20 bytes
+    ExactCalc,
+    /// Time arguments with minute offset and zone: [REF:8][DELTA:4][UNIT:1][OFFSET_MIN:2][ZONE:2] = 17 bytes
+    ZonedTime,
+    /// Time range with recurrence: [START:8][DURATION:4][OFFSET_MIN:2][ZONE:2][FREQ:1][INTERVAL:2][WEEKDAYS:1][COUNT:2][FLAGS:1][UNTIL:8] = 31 bytes
+    Schedule,
+    /// Retrieval query: [LEN:2][TOP_K:2][MIN_SCORE:4][QUERY_LEN:2][QUERY][COUNT:1][FILTER]...
+    RagQuery,
+    /// Wide RAG document address: [COLLECTION:4][DOC_ID:8] = 12 bytes
+    RagRef,
+    /// Template reference with slot bindings: [LEN:2][TEMPLATE_ID:2][COUNT:1][BINDING]...
+    Template,
+    /// Application-defined payload: [LEN:2][DATA:LEN]
+    Custom(u8),
 }
 
 impl PayloadType {
+    /// Last type byte reserved for payloads defined by this crate
+    pub const RESERVED_END: u8 = 0x7F;
+    /// First type byte available for application-defined payloads
+    pub const APPLICATION_START: u8 = 0x80;
+    /// Size of the length prefix on variable-size payloads
+    pub const LENGTH_PREFIX_SIZE: usize = 2;
+
+    /// Check if a type byte is in the reserved range (0x00-0x7F)
+    #[inline]
+    pub const fn is_reserved_byte(b: u8) -> bool {
+        b <= Self::RESERVED_END
+    }
+
+    /// Check if a type byte is in the application-defined range (0x80-0xFF)
+    #[inline]
+    pub const fn is_application_byte(b: u8) -> bool {
+        b >= Self::APPLICATION_START
+    }
+
     /// Parse from byte
     pub fn from_byte(b: u8) -> Option<Self> {
         match b {
             0x00 => Some(PayloadType::None),
             0x01 => Some(PayloadType::Calc),
             0x02 => Some(PayloadType::Time),
+            0x03 => Some(PayloadType::Timer),
+            0x04 => Some(PayloadType::Conversion),
+            0x05 => Some(PayloadType::Expr),
+            0x06 => Some(PayloadType::ExactCalc),
+            0x07 => Some(PayloadType::ZonedTime),
+            0x08 => Some(PayloadType::Schedule),
+            0x09 => Some(PayloadType::RagQuery),
+            0x0A => Some(PayloadType::RagRef),
+            0x0B => Some(PayloadType::Template),
+            _ if Self::is_application_byte(b) => Some(PayloadType::Custom(b)),
             _ => None,
         }
     }
 
     /// Convert to byte
     pub fn to_byte(self) -> u8 {
-        self as u8
+        match self {
+            PayloadType::None => 0x00,
+            PayloadType::Calc => 0x01,
+            PayloadType::Time => 0x02,
+            PayloadType::Timer => 0x03,
+            PayloadType::Conversion => 0x04,
+            PayloadType::Expr => 0x05,
+            PayloadType::ExactCalc => 0x06,
+            PayloadType::ZonedTime => 0x07,
+            PayloadType::Schedule => 0x08,
+            PayloadType::RagQuery => 0x09,
+            PayloadType::RagRef => 0x0A,
+            PayloadType::Template => 0x0B,
+            PayloadType::Custom(b) => b,
+        }
+    }
+
+    /// Variant name (`Calc`, `ZonedTime`, ...; `Custom` for application-defined types)
+    pub fn name(self) -> &'static str {
+        match self {
+            PayloadType::None => "None",
+            PayloadType::Calc => "Calc",
+            PayloadType::Time => "Time",
+            PayloadType::Timer => "Timer",
+            PayloadType::Conversion => "Conversion",
+            PayloadType::Expr => "Expr",
+            PayloadType::ExactCalc => "ExactCalc",
+            PayloadType::ZonedTime => "ZonedTime",
+            PayloadType::Schedule => "Schedule",
+            PayloadType::RagQuery => "RagQuery",
+            PayloadType::RagRef => "RagRef",
+            PayloadType::Template => "Template",
+            PayloadType::Custom(_) => "Custom",
+        }
+    }
+
+    /// Parse a [`name`](Self::name) (case-insensitive) or a `0x..` type byte
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(hex) = name.strip_prefix("0x").or_else(|| name.strip_prefix("0X")) {
+            return u8::from_str_radix(hex, 16).ok().and_then(Self::from_byte);
+        }
+        (0x00..=Self::RESERVED_END)
+            .filter_map(Self::from_byte)
+            .find(|payload_type| payload_type.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Check if payloads of this type carry a [LEN:2] length prefix
+    pub fn is_variable_size(self) -> bool {
+        matches!(
+            self,
+            PayloadType::Expr
+                | PayloadType::RagQuery
+                | PayloadType::Template
+                | PayloadType::Custom(_)
+        )
     }
 
     /// Get payload size in bytes
+    ///
+    /// For variable-size types this is the minimum size (the length prefix).
     pub fn payload_size(self) -> usize {
         match self {
             PayloadType::None => 0,
-            PayloadType::Calc => 17, // [OP:1][A:8][B:8]
-            PayloadType::Time => 14, // [REF:8][DELTA:4][UNIT:1][TZ:1]
+            PayloadType::Calc => 17,       // [OP:1][A:8][B:8]
+            PayloadType::Time => 14,       // [REF:8][DELTA:4][UNIT:1][TZ:1]
+            PayloadType::Timer => 22, // [DUR:4][UNIT:1][REPEAT:2][INTERVAL:4][FLAGS:1][LABEL:2][DEADLINE:8]
+            PayloadType::Conversion => 12, // [VALUE:8][FROM:2][TO:2]
+            PayloadType::ExactCalc => 20, // [OP:1][TAG:1][A:8][B:8][A_SCALE:1][B_SCALE:1]
+            PayloadType::ZonedTime => 17, // [REF:8][DELTA:4][UNIT:1][OFFSET_MIN:2][ZONE:2]
+            PayloadType::Schedule => 31, // [START:8][DURATION:4][OFFSET_MIN:2][ZONE:2][FREQ:1][INTERVAL:2][WEEKDAYS:1][COUNT:2][FLAGS:1][UNTIL:8]
+            PayloadType::RagRef => 12,   // [COLLECTION:4][DOC_ID:8]
+            PayloadType::Expr
+            | PayloadType::RagQuery
+            | PayloadType::Template
+            | PayloadType::Custom(_) => Self::LENGTH_PREFIX_SIZE,
         }
     }
 
     /// Get total extended instruction size (6 base + 1 type + N payload)
+    ///
+    /// For variable-size types this is the minimum size.
     pub fn total_size(self) -> usize {
         INSTRUCTION_SIZE + 1 + self.payload_size()
     }
+
+    /// Get encoded payload size from the payload bytes (reads the length prefix)
+    pub fn encoded_size(self, bytes: &[u8]) -> Option<usize> {
+        if !self.is_variable_size() {
+            return Some(self.payload_size());
+        }
+        let prefix = bytes.get(..Self::LENGTH_PREFIX_SIZE)?;
+        let len = u16::from_be_bytes([prefix[0], prefix[1]]) as usize;
+        Some(Self::LENGTH_PREFIX_SIZE + len)
+    }
 }
 
 /// Arithmetic operation type (matches frame-interpreter CalcArgs)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// Byte codes are ASCII mnemonics and are stable across versions. Unary
+/// operations ignore their second operand. Angles are in radians.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum Op {
-    Add = 0x2B,  // '+'
-    Sub = 0x2D,  // '-'
-    Mul = 0x2A,  // '*'
-    Div = 0x2F,  // '/'
-    Mod = 0x25,  // '%'
-    Pow = 0x5E,  // '^'
-    Sqrt = 0x53, // 'S'
+    Add = 0x2B,       // '+'
+    Sub = 0x2D,       // '-'
+    Mul = 0x2A,       // '*'
+    Div = 0x2F,       // '/'
+    Mod = 0x25,       // '%'
+    Pow = 0x5E,       // '^'
+    Sqrt = 0x53,      // 'S'
+    Abs = 0x61,       // 'a'
+    Neg = 0x7E,       // '~'
+    Floor = 0x66,     // 'f'
+    Ceil = 0x43,      // 'C'
+    Round = 0x72,     // 'r'
+    Log = 0x6C,       // 'l' (base 10)
+    Ln = 0x4C,        // 'L'
+    Exp = 0x65,       // 'e'
+    Min = 0x6D,       // 'm'
+    Max = 0x4D,       // 'M'
+    PercentOf = 0x70, // 'p' (a% of b)
+    Factorial = 0x21, // '!'
+    Sin = 0x73,       // 's'
+    Cos = 0x63,       // 'c'
+    Tan = 0x74,       // 't'
+    Asin = 0x49,      // 'I'
+    Acos = 0x4F,      // 'O'
+    Atan = 0x54,      // 'T'
 }
 
 impl Op {
+    /// Every operation, in declaration order
+    pub const ALL: [Op; 25] = [
+        Op::Add,
+        Op::Sub,
+        Op::Mul,
+        Op::Div,
+        Op::Mod,
+        Op::Pow,
+        Op::Sqrt,
+        Op::Abs,
+        Op::Neg,
+        Op::Floor,
+        Op::Ceil,
+        Op::Round,
+        Op::Log,
+        Op::Ln,
+        Op::Exp,
+        Op::Min,
+        Op::Max,
+        Op::PercentOf,
+        Op::Factorial,
+        Op::Sin,
+        Op::Cos,
+        Op::Tan,
+        Op::Asin,
+        Op::Acos,
+        Op::Atan,
+    ];
+
     /// Parse from byte
     pub fn from_byte(b: u8) -> Option<Self> {
-        match b {
-            0x2B => Some(Op::Add),
-            0x2D => Some(Op::Sub),
-            0x2A => Some(Op::Mul),
-            0x2F => Some(Op::Div),
-            0x25 => Some(Op::Mod),
-            0x5E => Some(Op::Pow),
-            0x53 => Some(Op::Sqrt),
-            _ => None,
-        }
+        Self::ALL.into_iter().find(|op| op.to_byte() == b)
     }
 
     /// Convert to byte
@@ -94,6 +284,27 @@ impl Op {
         self as u8
     }
 
+    /// Number of operands (1 for unary, 2 for binary)
+    pub fn arity(self) -> u8 {
+        match self {
+            Op::Add
+            | Op::Sub
+            | Op::Mul
+            | Op::Div
+            | Op::Mod
+            | Op::Pow
+            | Op::Min
+            | Op::Max
+            | Op::PercentOf => 2,
+            _ => 1,
+        }
+    }
+
+    /// Check if the operation takes a single operand
+    pub fn is_unary(self) -> bool {
+        self.arity() == 1
+    }
+
     /// Symbol for display
     pub fn symbol(self) -> &'static str {
         match self {
@@ -104,8 +315,34 @@ impl Op {
             Op::Mod => "%",
             Op::Pow => "^",
             Op::Sqrt => "sqrt",
+            Op::Abs => "abs",
+            Op::Neg => "neg",
+            Op::Floor => "floor",
+            Op::Ceil => "ceil",
+            Op::Round => "round",
+            Op::Log => "log",
+            Op::Ln => "ln",
+            Op::Exp => "exp",
+            Op::Min => "min",
+            Op::Max => "max",
+            Op::PercentOf => "pct",
+            Op::Factorial => "!",
+            Op::Sin => "sin",
+            Op::Cos => "cos",
+            Op::Tan => "tan",
+            Op::Asin => "asin",
+            Op::Acos => "acos",
+            Op::Atan => "atan",
         }
     }
+
+    /// Check if the operation is written between its operands (`a + b`)
+    pub fn is_infix(self) -> bool {
+        matches!(
+            self,
+            Op::Add | Op::Sub | Op::Mul | Op::Div | Op::Mod | Op::Pow
+        )
+    }
 }
 
 /// Calculator arguments payload
@@ -150,16 +387,37 @@ impl CalcPayload {
 
 impl fmt::Display for CalcPayload {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if matches!(self.op, Op::Sqrt) {
-            write!(f, "{}({})", self.op.symbol(), self.a)
-        } else {
-            write!(f, "{} {} {}", self.a, self.op.symbol(), self.b)
-        }
+        fmt_calc(f, self.op, self.a, self.b)
+    }
+}
+
+/// Render a calculation in conventional notation (`1 + 2`, `-3`, `sqrt(4)`)
+///
+/// Negative operands of `Neg` and `Factorial` are parenthesized (`-(-3)`, `(-3)!`).
+pub(crate) fn fmt_calc(
+    f: &mut fmt::Formatter<'_>,
+    op: Op,
+    a: impl fmt::Display,
+    b: impl fmt::Display,
+) -> fmt::Result {
+    let operand = a.to_string();
+    let operand = if operand.starts_with('-') {
+        format!("({})", operand)
+    } else {
+        operand
+    };
+    match op {
+        Op::Neg => write!(f, "-{}", operand),
+        Op::Factorial => write!(f, "{}!", operand),
+        Op::PercentOf => write!(f, "{}% of {}", a, b),
+        op if op.is_infix() => write!(f, "{} {} {}", a, op.symbol(), b),
+        op if op.is_unary() => write!(f, "{}({})", op.symbol(), a),
+        op => write!(f, "{}({}, {})", op.symbol(), a, b),
     }
 }
 
 /// Time unit for temporal calculations
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum TimeUnit {
     Second = 0,
@@ -172,6 +430,17 @@ pub enum TimeUnit {
 }
 
 impl TimeUnit {
+    /// All units, in byte order
+    pub const ALL: [Self; 7] = [
+        TimeUnit::Second,
+        TimeUnit::Minute,
+        TimeUnit::Hour,
+        TimeUnit::Day,
+        TimeUnit::Week,
+        TimeUnit::Month,
+        TimeUnit::Year,
+    ];
+
     /// Parse from byte
     pub fn from_byte(b: u8) -> Option<Self> {
         match b {
@@ -191,7 +460,9 @@ impl TimeUnit {
         self as u8
     }
 
-    /// Seconds per unit
+    /// Nominal seconds per unit (30-day months, 365-day years)
+    ///
+    /// Calendar-aware stepping lives in [`TimeArithmetic`](crate::calendar::TimeArithmetic).
     pub fn seconds(self) -> i64 {
         match self {
             TimeUnit::Second => 1,
@@ -204,6 +475,13 @@ impl TimeUnit {
         }
     }
 
+    /// Look up a unit by its [`name`](Self::name) (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|unit| unit.name().eq_ignore_ascii_case(name))
+    }
+
     /// Display name
     pub fn name(self) -> &'static str {
         match self {
@@ -219,6 +497,15 @@ impl TimeUnit {
 }
 
 /// Time arguments payload
+///
+/// `reference` and [`target_timestamp`](Self::target_timestamp) are UTC
+/// instants. The offset (or zone) describes the user's local time: day,
+/// week, month and year steps follow the local calendar, and
+/// [`local_timestamp`](Self::local_timestamp) gives the wall-clock reading.
+///
+/// Payloads with a whole-hour offset and no zone use the original 14-byte
+/// [`PayloadType::Time`] encoding; others use the 17-byte
+/// [`PayloadType::ZonedTime`] encoding.
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct TimePayload {
     /// Reference timestamp (Unix seconds)
@@ -227,34 +514,49 @@ pub struct TimePayload {
     pub delta: i32,
     /// Unit of the delta
     pub unit: TimeUnit,
-    /// Timezone offset in hours (-12 to +14)
-    pub tz_offset: i8,
+    /// Fixed UTC offset in minutes (-720 to +840), used when `zone` is `None`
+    pub offset_minutes: i16,
+    /// Named zone with daylight-saving rules (takes precedence over `offset_minutes`)
+    pub zone: Option<ZoneId>,
 }
 
 impl TimePayload {
-    /// Create time payload for "now"
+    /// Create time payload for "now" from the system clock
+    ///
+    /// Use [`now_with`](Self::now_with) for deterministic time.
     pub fn now() -> Self {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let reference = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        Self {
-            reference,
-            delta: 0,
-            unit: TimeUnit::Second,
-            tz_offset: 0,
-        }
+        Self::at(SystemClock.now())
+    }
+
+    /// Create time payload for "now" from a clock (see [`Clock::now`])
+    pub fn now_with(clock: &(impl Clock + ?Sized)) -> Self {
+        Self::at(clock.now())
+    }
+
+    /// Create with delta from a clock's "now" ("in 3 hours")
+    pub fn from_now_with(clock: &(impl Clock + ?Sized), delta: i32, unit: TimeUnit) -> Self {
+        Self::with_delta(clock.now(), delta, unit)
+    }
+
+    /// Create time payload for "now" from a clock, failing if it cannot be read
+    pub fn try_now_with(clock: &(impl Clock + ?Sized)) -> Result<Self, ClockError> {
+        clock.try_now().map(Self::at)
+    }
+
+    /// Create with delta from a clock's "now", failing if it cannot be read
+    pub fn try_from_now_with(
+        clock: &(impl Clock + ?Sized),
+        delta: i32,
+        unit: TimeUnit,
+    ) -> Result<Self, ClockError> {
+        clock
+            .try_now()
+            .map(|now| Self::with_delta(now, delta, unit))
     }
 
     /// Create with specific reference
     pub fn at(reference: i64) -> Self {
-        Self {
-            reference,
-            delta: 0,
-            unit: TimeUnit::Second,
-            tz_offset: 0,
-        }
+        Self::with_delta(reference, 0, TimeUnit::Second)
     }
 
     /// Create with delta from reference
@@ -263,34 +565,134 @@ impl TimePayload {
             reference,
             delta,
             unit,
-            tz_offset: 0,
+            offset_minutes: 0,
+            zone: None,
         }
     }
 
-    /// Set timezone offset
-    pub fn with_tz(mut self, offset: i8) -> Self {
-        self.tz_offset = offset;
+    /// Set timezone offset in whole hours
+    pub fn with_tz(self, hours: i8) -> Self {
+        self.with_offset_minutes(hours as i16 * 60)
+    }
+
+    /// Set timezone offset in minutes (e.g. 330 for UTC+05:30)
+    pub fn with_offset_minutes(mut self, minutes: i16) -> Self {
+        self.offset_minutes = minutes;
+        self
+    }
+
+    /// Set a named zone
+    pub fn with_zone(mut self, zone: ZoneId) -> Self {
+        self.zone = Some(zone);
         self
     }
 
-    /// Calculate target timestamp
+    /// UTC offset in minutes in effect at a UTC timestamp
+    pub fn offset_at(&self, timestamp: i64) -> i16 {
+        self.zone
+            .map_or(self.offset_minutes, |zone| zone.offset_at(timestamp))
+    }
+
+    /// Calculate target timestamp (UTC)
+    ///
+    /// Months and years follow the calendar (`Jan 31 + 1 month == Feb 28/29`).
     pub fn target_timestamp(&self) -> i64 {
-        let delta_seconds = (self.delta as i64) * self.unit.seconds();
-        let tz_seconds = (self.tz_offset as i64) * 3600;
-        self.reference + delta_seconds + tz_seconds
+        self.target_timestamp_with(TimeArithmetic::Calendar)
+    }
+
+    /// Calculate target timestamp (UTC) with explicit month/year arithmetic
+    ///
+    /// In calendar mode, day-or-longer steps keep the local wall-clock time
+    /// across DST changes; shorter steps are exact durations.
+    pub fn target_timestamp_with(&self, arithmetic: TimeArithmetic) -> i64 {
+        let delta = self.delta as i64;
+        let is_wall_clock = matches!(
+            self.unit,
+            TimeUnit::Day | TimeUnit::Week | TimeUnit::Month | TimeUnit::Year
+        );
+        if arithmetic == TimeArithmetic::FixedSeconds || !is_wall_clock {
+            return arithmetic.add(self.reference, delta, self.unit);
+        }
+
+        let local = self
+            .reference
+            .saturating_add(self.offset_at(self.reference) as i64 * 60);
+        let target = arithmetic.add(local, delta, self.unit);
+        match self.zone {
+            Some(zone) => zone.to_utc(target),
+            None => target.saturating_sub(self.offset_minutes as i64 * 60),
+        }
     }
 
-    /// Serialize to bytes: [REF:8][DELTA:4][UNIT:1][TZ:1] = 14 bytes
+    /// Local wall-clock reading of the target (Unix seconds as if local time were UTC)
+    pub fn local_timestamp(&self) -> i64 {
+        let target = self.target_timestamp();
+        target.saturating_add(self.offset_at(target) as i64 * 60)
+    }
+
+    /// Validate that the offset is in range and the zone is known
+    pub fn validate(&self) -> Result<(), InstructionError> {
+        if !(MIN_OFFSET_MINUTES..=MAX_OFFSET_MINUTES).contains(&self.offset_minutes) {
+            return Err(InstructionError::InvalidPayload(format!(
+                "UTC offset {} out of range",
+                format_offset(self.offset_minutes)
+            )));
+        }
+        match self.zone {
+            Some(zone) if !zone.is_known() => Err(InstructionError::InvalidPayload(format!(
+                "Unknown time zone 0x{:04X}",
+                zone.0
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Wire format for this payload ([`PayloadType::Time`] when the legacy format suffices)
+    pub fn payload_type(&self) -> PayloadType {
+        let whole_hours =
+            self.offset_minutes % 60 == 0 && i8::try_from(self.offset_minutes / 60).is_ok();
+        if self.zone.is_none() && whole_hours {
+            PayloadType::Time
+        } else {
+            PayloadType::ZonedTime
+        }
+    }
+
+    /// Timezone offset in whole hours (truncated toward zero)
+    #[deprecated(
+        note = "use `offset_minutes` and `zone`, which carry minute offsets and DST rules"
+    )]
+    pub fn tz_offset(&self) -> i8 {
+        (self.offset_minutes / 60) as i8
+    }
+
+    /// Serialize to the 14-byte [`PayloadType::Time`] format
+    ///
+    /// [REF:8][DELTA:4][UNIT:1][TZ_HOURS:1]. Lossless only when
+    /// [`payload_type`](Self::payload_type) is `Time`: the offset is truncated
+    /// to whole hours and the zone is dropped. Use
+    /// [`to_zoned_bytes`](Self::to_zoned_bytes) otherwise.
     pub fn to_bytes(&self) -> [u8; 14] {
         let mut bytes = [0u8; 14];
         bytes[0..8].copy_from_slice(&self.reference.to_be_bytes());
         bytes[8..12].copy_from_slice(&self.delta.to_be_bytes());
         bytes[12] = self.unit.to_byte();
-        bytes[13] = self.tz_offset as u8;
+        bytes[13] = (self.offset_minutes / 60) as i8 as u8;
         bytes
     }
 
-    /// Parse from bytes
+    /// Serialize to the 17-byte [`PayloadType::ZonedTime`] format
+    ///
+    /// [REF:8][DELTA:4][UNIT:1][OFFSET_MIN:2][ZONE:2] (zone 0 = none)
+    pub fn to_zoned_bytes(&self) -> [u8; 17] {
+        let mut bytes = [0u8; 17];
+        bytes[..13].copy_from_slice(&self.to_bytes()[..13]);
+        bytes[13..15].copy_from_slice(&self.offset_minutes.to_be_bytes());
+        bytes[15..17].copy_from_slice(&self.zone.map_or(0, |zone| zone.0).to_be_bytes());
+        bytes
+    }
+
+    /// Parse from the 14-byte [`PayloadType::Time`] format
     pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
         if bytes.len() < 14 {
             return None;
@@ -298,22 +700,240 @@ impl TimePayload {
         let reference = i64::from_be_bytes(bytes[0..8].try_into().ok()?);
         let delta = i32::from_be_bytes(bytes[8..12].try_into().ok()?);
         let unit = TimeUnit::from_byte(bytes[12])?;
-        let tz_offset = bytes[13] as i8;
-        Some(Self {
-            reference,
-            delta,
+        let hours = bytes[13] as i8;
+        Some(Self::with_delta(reference, delta, unit).with_tz(hours))
+    }
+
+    /// Parse from the 17-byte [`PayloadType::ZonedTime`] format
+    pub fn from_zoned_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 17 {
+            return None;
+        }
+        let mut time = Self::from_bytes(&bytes[..14])?;
+        time.offset_minutes = i16::from_be_bytes([bytes[13], bytes[14]]);
+        time.zone = match u16::from_be_bytes([bytes[15], bytes[16]]) {
+            0 => None,
+            code => Some(ZoneId(code)),
+        };
+        Some(time)
+    }
+}
+
+/// Reference to a string stored outside the instruction stream
+///
+/// The index is resolved by the host (e.g. a per-conversation string table),
+/// keeping payloads fixed-size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(transparent)]
+pub struct StringRef(pub u16);
+
+impl fmt::Display for StringRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "STR(0x{:04X})", self.0)
+    }
+}
+
+/// Timer arguments payload for `SET_TIMER`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimerPayload {
+    /// Duration until the timer first fires
+    pub duration: u32,
+    /// Unit of duration and interval
+    pub unit: TimeUnit,
+    /// Additional firings after the first ([`TimerPayload::REPEAT_FOREVER`] = until cancelled)
+    pub repeat: u16,
+    /// Time between repeated firings (0 = same as duration)
+    pub interval: u32,
+    /// Optional label shown when the timer fires
+    pub label: Option<StringRef>,
+    /// Optional absolute deadline (Unix seconds); the timer never fires after it
+    pub deadline: Option<i64>,
+}
+
+impl TimerPayload {
+    /// Repeat count meaning "repeat until cancelled"
+    pub const REPEAT_FOREVER: u16 = u16::MAX;
+
+    const FLAG_LABEL: u8 = 0x01;
+    const FLAG_DEADLINE: u8 = 0x02;
+
+    /// Create a one-shot timer
+    pub fn new(duration: u32, unit: TimeUnit) -> Self {
+        Self {
+            duration,
             unit,
-            tz_offset,
+            repeat: 0,
+            interval: 0,
+            label: None,
+            deadline: None,
+        }
+    }
+
+    /// Create a timer that fires at an absolute time (Unix seconds)
+    pub fn until(deadline: i64) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..Self::new(0, TimeUnit::Second)
+        }
+    }
+
+    /// Set label
+    pub fn with_label(mut self, label: StringRef) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// Repeat the timer `repeat` more times, `interval` units apart (0 = same as duration)
+    pub fn repeating(mut self, repeat: u16, interval: u32) -> Self {
+        self.repeat = repeat;
+        self.interval = interval;
+        self
+    }
+
+    /// Set absolute deadline (Unix seconds)
+    pub fn with_deadline(mut self, deadline: i64) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Check if the timer fires more than once
+    pub fn is_repeating(&self) -> bool {
+        self.repeat > 0
+    }
+
+    /// Duration in seconds
+    pub fn duration_seconds(&self) -> i64 {
+        self.duration as i64 * self.unit.seconds()
+    }
+
+    /// Interval between repeated firings in seconds
+    pub fn interval_seconds(&self) -> i64 {
+        if self.interval == 0 {
+            self.duration_seconds()
+        } else {
+            self.interval as i64 * self.unit.seconds()
+        }
+    }
+
+    /// Timestamp of the first firing for a timer started at `start` (Unix seconds)
+    ///
+    /// Returns `None` if the first firing would fall after the deadline.
+    pub fn first_fire(&self, start: i64) -> Option<i64> {
+        let at = match (self.duration, self.deadline) {
+            (0, Some(deadline)) => deadline,
+            _ => start + self.duration_seconds(),
+        };
+        match self.deadline {
+            Some(deadline) if at > deadline => None,
+            _ => Some(at),
+        }
+    }
+
+    /// Timestamp of the first firing for a timer started now on `clock`
+    pub fn first_fire_with(&self, clock: &(impl Clock + ?Sized)) -> Option<i64> {
+        self.first_fire(clock.now())
+    }
+
+    /// Timestamp of the first firing for a timer started now on `clock`,
+    /// failing if it cannot be read
+    pub fn try_first_fire_with(
+        &self,
+        clock: &(impl Clock + ?Sized),
+    ) -> Result<Option<i64>, ClockError> {
+        clock.try_now().map(|now| self.first_fire(now))
+    }
+
+    /// Validate field combinations
+    pub fn validate(&self) -> Result<(), InstructionError> {
+        if self.duration == 0 && self.deadline.is_none() {
+            return Err(InstructionError::InvalidPayload(
+                "Timer needs a duration or a deadline".to_string(),
+            ));
+        }
+        if self.is_repeating() && self.interval_seconds() == 0 {
+            return Err(InstructionError::InvalidPayload(
+                "Repeating timer needs a non-zero interval".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Serialize to bytes: [DUR:4][UNIT:1][REPEAT:2][INTERVAL:4][FLAGS:1][LABEL:2][DEADLINE:8] = 22 bytes
+    pub fn to_bytes(&self) -> [u8; 22] {
+        let mut bytes = [0u8; 22];
+        let mut flags = 0u8;
+        if self.label.is_some() {
+            flags |= Self::FLAG_LABEL;
+        }
+        if self.deadline.is_some() {
+            flags |= Self::FLAG_DEADLINE;
+        }
+        bytes[0..4].copy_from_slice(&self.duration.to_be_bytes());
+        bytes[4] = self.unit.to_byte();
+        bytes[5..7].copy_from_slice(&self.repeat.to_be_bytes());
+        bytes[7..11].copy_from_slice(&self.interval.to_be_bytes());
+        bytes[11] = flags;
+        bytes[12..14].copy_from_slice(&self.label.map_or(0, |l| l.0).to_be_bytes());
+        bytes[14..22].copy_from_slice(&self.deadline.unwrap_or(0).to_be_bytes());
+        bytes
+    }
+
+    /// Parse from bytes
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 22 {
+            return None;
+        }
+        let flags = bytes[11];
+        let label = u16::from_be_bytes(bytes[12..14].try_into().ok()?);
+        let deadline = i64::from_be_bytes(bytes[14..22].try_into().ok()?);
+        Some(Self {
+            duration: u32::from_be_bytes(bytes[0..4].try_into().ok()?),
+            unit: TimeUnit::from_byte(bytes[4])?,
+            repeat: u16::from_be_bytes(bytes[5..7].try_into().ok()?),
+            interval: u32::from_be_bytes(bytes[7..11].try_into().ok()?),
+            label: (flags & Self::FLAG_LABEL != 0).then_some(StringRef(label)),
+            deadline: (flags & Self::FLAG_DEADLINE != 0).then_some(deadline),
         })
     }
 }
 
+impl fmt::Display for TimerPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "timer {} {}", self.duration, self.unit.name())?;
+        match self.repeat {
+            0 => {}
+            Self::REPEAT_FOREVER => write!(f, " repeat forever")?,
+            n => write!(f, " repeat {}", n)?,
+        }
+        if self.is_repeating() && self.interval != 0 {
+            write!(f, " every {} {}", self.interval, self.unit.name())?;
+        }
+        if let Some(label) = self.label {
+            write!(f, " {}", label)?;
+        }
+        if let Some(deadline) = self.deadline {
+            write!(f, " until {}", deadline)?;
+        }
+        Ok(())
+    }
+}
+
 /// Payload variants for extended instructions
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Payload {
     None,
     Calc(CalcPayload),
     Time(TimePayload),
+    Timer(TimerPayload),
+    Conversion(ConversionPayload),
+    Expr(ExprPayload),
+    ExactCalc(ExactCalcPayload),
+    Schedule(SchedulePayload),
+    RagQuery(RagQueryPayload),
+    RagRef(RagAddress),
+    Template(TemplatePayload),
+    /// Application-defined payload (registered or raw bytes)
+    Custom(CustomData),
 }
 
 impl Payload {
@@ -322,25 +942,93 @@ impl Payload {
         match self {
             Payload::None => PayloadType::None,
             Payload::Calc(_) => PayloadType::Calc,
-            Payload::Time(_) => PayloadType::Time,
+            Payload::Time(t) => t.payload_type(),
+            Payload::Timer(_) => PayloadType::Timer,
+            Payload::Conversion(_) => PayloadType::Conversion,
+            Payload::Expr(_) => PayloadType::Expr,
+            Payload::ExactCalc(_) => PayloadType::ExactCalc,
+            Payload::Schedule(_) => PayloadType::Schedule,
+            Payload::RagQuery(_) => PayloadType::RagQuery,
+            Payload::RagRef(_) => PayloadType::RagRef,
+            Payload::Template(_) => PayloadType::Template,
+            Payload::Custom(c) => PayloadType::Custom(c.type_byte()),
+        }
+    }
+
+    /// Get encoded payload size in bytes (not including type byte)
+    pub fn byte_size(&self) -> usize {
+        let payload_type = self.payload_type();
+        if payload_type.is_variable_size() {
+            PayloadType::LENGTH_PREFIX_SIZE + self.data_bytes().len().min(u16::MAX as usize)
+        } else {
+            payload_type.payload_size()
         }
     }
 
     /// Serialize payload to bytes (not including type byte)
+    ///
+    /// Variable-size payloads are prefixed with their [LEN:2] data length.
+    /// Data longer than the prefix can express (custom payloads over
+    /// [`MAX_CUSTOM_PAYLOAD_SIZE`](crate::custom::MAX_CUSTOM_PAYLOAD_SIZE)) is
+    /// truncated; call [`ExtendedInstruction::validate`] first to reject it instead.
     pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = self.data_bytes();
+        if !self.payload_type().is_variable_size() {
+            return data;
+        }
+        data.truncate(u16::MAX as usize);
+        let mut bytes = Vec::with_capacity(PayloadType::LENGTH_PREFIX_SIZE + data.len());
+        bytes.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(&data);
+        bytes
+    }
+
+    /// Serialize payload data (without length prefix)
+    fn data_bytes(&self) -> Vec<u8> {
         match self {
             Payload::None => Vec::new(),
             Payload::Calc(c) => c.to_bytes().to_vec(),
-            Payload::Time(t) => t.to_bytes().to_vec(),
+            Payload::Time(t) => match t.payload_type() {
+                PayloadType::Time => t.to_bytes().to_vec(),
+                _ => t.to_zoned_bytes().to_vec(),
+            },
+            Payload::Timer(t) => t.to_bytes().to_vec(),
+            Payload::Conversion(c) => c.to_bytes().to_vec(),
+            Payload::Expr(e) => e.to_bytes(),
+            Payload::ExactCalc(c) => c.to_bytes().to_vec(),
+            Payload::Schedule(s) => s.to_bytes().to_vec(),
+            Payload::RagQuery(q) => q.to_bytes(),
+            Payload::RagRef(r) => r.to_bytes().to_vec(),
+            Payload::Template(t) => t.to_bytes(),
+            Payload::Custom(c) => c.to_bytes(),
         }
     }
 
     /// Parse payload from type and bytes
     pub fn from_bytes(payload_type: PayloadType, bytes: &[u8]) -> Option<Self> {
+        let data = if payload_type.is_variable_size() {
+            let size = payload_type.encoded_size(bytes)?;
+            bytes.get(PayloadType::LENGTH_PREFIX_SIZE..size)?
+        } else {
+            bytes
+        };
         match payload_type {
             PayloadType::None => Some(Payload::None),
-            PayloadType::Calc => CalcPayload::from_bytes(bytes).map(Payload::Calc),
-            PayloadType::Time => TimePayload::from_bytes(bytes).map(Payload::Time),
+            PayloadType::Calc => CalcPayload::from_bytes(data).map(Payload::Calc),
+            PayloadType::Time => TimePayload::from_bytes(data).map(Payload::Time),
+            PayloadType::ZonedTime => TimePayload::from_zoned_bytes(data).map(Payload::Time),
+            PayloadType::Timer => TimerPayload::from_bytes(data).map(Payload::Timer),
+            PayloadType::Conversion => ConversionPayload::from_bytes(data).map(Payload::Conversion),
+            PayloadType::Expr => ExprPayload::from_bytes(data).map(Payload::Expr),
+            PayloadType::ExactCalc => ExactCalcPayload::from_bytes(data).map(Payload::ExactCalc),
+            PayloadType::Schedule => SchedulePayload::from_bytes(data).map(Payload::Schedule),
+            PayloadType::RagQuery => RagQueryPayload::from_bytes(data).map(Payload::RagQuery),
+            PayloadType::RagRef => RagAddress::from_bytes(data).map(Payload::RagRef),
+            PayloadType::Template => TemplatePayload::from_bytes(data).map(Payload::Template),
+            PayloadType::Custom(type_byte) => Some(Payload::Custom(CustomData::Raw {
+                type_byte,
+                bytes: data.to_vec(),
+            })),
         }
     }
 }
@@ -384,9 +1072,73 @@ impl ExtendedInstruction {
         }
     }
 
+    /// Create extended instruction with timer payload
+    pub fn with_timer(base: Instruction, timer: TimerPayload) -> Self {
+        Self {
+            base,
+            payload: Payload::Timer(timer),
+        }
+    }
+
+    /// Create extended instruction with unit-conversion payload
+    pub fn with_conversion(base: Instruction, conversion: ConversionPayload) -> Self {
+        Self {
+            base,
+            payload: Payload::Conversion(conversion),
+        }
+    }
+
+    /// Create extended instruction with expression payload
+    pub fn with_expr(base: Instruction, expr: ExprPayload) -> Self {
+        Self {
+            base,
+            payload: Payload::Expr(expr),
+        }
+    }
+
+    /// Create extended instruction with exact calc payload
+    pub fn with_exact_calc(base: Instruction, calc: ExactCalcPayload) -> Self {
+        Self {
+            base,
+            payload: Payload::ExactCalc(calc),
+        }
+    }
+
+    /// Create extended instruction with schedule payload
+    pub fn with_schedule(base: Instruction, schedule: SchedulePayload) -> Self {
+        Self {
+            base,
+            payload: Payload::Schedule(schedule),
+        }
+    }
+
+    /// Create extended instruction with RAG query payload
+    pub fn with_rag_query(base: Instruction, query: RagQueryPayload) -> Self {
+        Self {
+            base,
+            payload: Payload::RagQuery(query),
+        }
+    }
+
+    /// Create extended instruction addressing a RAG document beyond the subject range
+    pub fn with_rag_ref(base: Instruction, address: RagAddress) -> Self {
+        Self {
+            base,
+            payload: Payload::RagRef(address),
+        }
+    }
+
+    /// Create extended instruction with template payload
+    pub fn with_template(base: Instruction, template: TemplatePayload) -> Self {
+        Self {
+            base,
+            payload: Payload::Template(template),
+        }
+    }
+
     /// Get total byte size
     pub fn byte_size(&self) -> usize {
-        self.payload.payload_type().total_size()
+        INSTRUCTION_SIZE + 1 + self.payload.byte_size()
     }
 
     /// Serialize to bytes
@@ -415,7 +1167,9 @@ impl ExtendedInstruction {
             )),
         )?;
 
-        let expected_size = payload_type.total_size();
+        let expected_size = payload_type
+            .encoded_size(&bytes[INSTRUCTION_SIZE + 1..])
+            .map_or(payload_type.total_size(), |size| INSTRUCTION_SIZE + 1 + size);
         if bytes.len() < expected_size {
             return Err(InstructionError::InvalidLength {
                 actual: bytes.len(),
@@ -445,6 +1199,150 @@ impl ExtendedInstruction {
             _ => None,
         }
     }
+
+    /// Get as timer payload if present
+    pub fn as_timer(&self) -> Option<&TimerPayload> {
+        match &self.payload {
+            Payload::Timer(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Get as unit-conversion payload if present
+    pub fn as_conversion(&self) -> Option<&ConversionPayload> {
+        match &self.payload {
+            Payload::Conversion(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Get as exact calc payload if present
+    pub fn as_exact_calc(&self) -> Option<&ExactCalcPayload> {
+        match &self.payload {
+            Payload::ExactCalc(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Get as schedule payload if present
+    pub fn as_schedule(&self) -> Option<&SchedulePayload> {
+        match &self.payload {
+            Payload::Schedule(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get as RAG query payload if present
+    pub fn as_rag_query(&self) -> Option<&RagQueryPayload> {
+        match &self.payload {
+            Payload::RagQuery(q) => Some(q),
+            _ => None,
+        }
+    }
+
+    /// Get as RAG address payload if present
+    pub fn as_rag_ref(&self) -> Option<&RagAddress> {
+        match &self.payload {
+            Payload::RagRef(r) => Some(r),
+            _ => None,
+        }
+    }
+
+    /// Get as template payload if present
+    pub fn as_template(&self) -> Option<&TemplatePayload> {
+        match &self.payload {
+            Payload::Template(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Document this instruction references, from a RAG address payload or a RAG subject
+    pub fn rag_address(&self) -> Option<RagAddress> {
+        match self.payload {
+            Payload::RagRef(address) => Some(address),
+            _ => RagAddress::from_subject(self.base.subject),
+        }
+    }
+
+    /// Check if this instruction needs the RAG system (RAG subject, address or
+    /// query payload, or a template binding a document)
+    pub fn needs_rag(&self) -> bool {
+        self.base.needs_rag()
+            || match &self.payload {
+                Payload::RagQuery(_) | Payload::RagRef(_) => true,
+                Payload::Template(t) => t.documents().next().is_some(),
+                _ => false,
+            }
+    }
+
+    /// Get as expression payload if present
+    pub fn as_expr(&self) -> Option<&ExprPayload> {
+        match &self.payload {
+            Payload::Expr(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Check that the payload matches the action and is internally consistent
+    ///
+    /// - `SET_TIMER` takes only a timer payload (or none), and timer payloads are only
+    ///   valid on `SET_TIMER`
+    /// - Unit conversions must be between known units of the same dimension
+    /// - Expressions must be well-formed postfix programs within the depth bound
+    /// - Time and schedule payloads need an in-range offset, a known zone, and (for
+    ///   schedules) a well-formed recurrence rule
+    /// - RAG queries are only valid on retrieval actions (`SEARCH`, `RETRIEVE`,
+    ///   `KNOWLEDGE_SEARCH`) and must fit the encoding limits
+    /// - A RAG address payload must agree with the subject when both name a document
+    /// - Template payloads are only valid on `TEMPLATE_LOAD` (without bindings)
+    ///   and `TEMPLATE_FILL`
+    /// - Application payloads need an application type byte and must fit the [LEN:2] prefix
+    pub fn validate(&self) -> Result<(), InstructionError> {
+        let action = self.base.action;
+        let mismatch = |payload| Err(InstructionError::PayloadMismatch { action, payload });
+        if action == Action::SET_TIMER {
+            return match &self.payload {
+                Payload::Timer(t) => t.validate(),
+                Payload::None => Ok(()),
+                other => mismatch(other.payload_type()),
+            };
+        }
+        match &self.payload {
+            Payload::None | Payload::Calc(_) | Payload::ExactCalc(_) => Ok(()),
+            Payload::Timer(_) => mismatch(PayloadType::Timer),
+            Payload::Time(t) => t.validate(),
+            Payload::Schedule(s) => s.validate(),
+            Payload::Conversion(c) => c.validate(),
+            Payload::Expr(e) => e
+                .validate()
+                .map_err(|e| InstructionError::InvalidPayload(e.to_string())),
+            Payload::RagQuery(q) if action.is_retrieval() => q.validate(),
+            Payload::RagQuery(_) => mismatch(PayloadType::RagQuery),
+            Payload::RagRef(address) => match RagAddress::from_subject(self.base.subject) {
+                Some(subject) if subject != *address => {
+                    Err(InstructionError::InvalidPayload(format!(
+                        "RAG subject {} conflicts with payload address {}",
+                        subject, address
+                    )))
+                }
+                _ => Ok(()),
+            },
+            Payload::Template(t) if action == Action::TEMPLATE_FILL => t.validate(),
+            Payload::Template(t) if action == Action::TEMPLATE_LOAD => {
+                if t.bindings.is_empty() {
+                    Ok(())
+                } else {
+                    Err(InstructionError::InvalidPayload(
+                        "TEMPLATE_LOAD does not take slot bindings".to_string(),
+                    ))
+                }
+            }
+            Payload::Template(_) => mismatch(PayloadType::Template),
+            Payload::Custom(c) => c
+                .check()
+                .map_err(|e| InstructionError::InvalidPayload(e.to_string())),
+        }
+    }
 }
 
 impl fmt::Display for ExtendedInstruction {
@@ -453,7 +1351,16 @@ impl fmt::Display for ExtendedInstruction {
         match &self.payload {
             Payload::None => Ok(()),
             Payload::Calc(c) => write!(f, " + {}", c),
-            Payload::Time(t) => write!(f, " @ {}", t.target_timestamp()),
+            Payload::Time(t) => write!(f, " @ {}", t.to_iso8601()),
+            Payload::Timer(t) => write!(f, " ~ {}", t),
+            Payload::Conversion(c) => write!(f, " + {}", c),
+            Payload::Expr(e) => write!(f, " + {}", e),
+            Payload::ExactCalc(c) => write!(f, " + {}", c),
+            Payload::Schedule(s) => write!(f, " @ {}", s),
+            Payload::RagQuery(q) => write!(f, " ? {}", q),
+            Payload::RagRef(r) => write!(f, " {}", r),
+            Payload::Template(t) => write!(f, " + {}", t),
+            Payload::Custom(c) => write!(f, " # {}", c),
         }
     }
 }
@@ -518,6 +1425,225 @@ mod tests {
         let parsed = ExtendedInstruction::from_bytes(&bytes).unwrap();
         assert_eq!(ext.base, parsed.base);
         assert_eq!(parsed.as_time().unwrap(), &time);
+        assert!(parsed.to_string().ends_with(" @ 2024-12-27T14:46:40Z"));
+    }
+
+    #[test]
+    fn test_timer_payload_roundtrip() {
+        let timer = TimerPayload::new(25, TimeUnit::Minute)
+            .repeating(3, 5)
+            .with_label(StringRef(7))
+            .with_deadline(1735300000);
+        let bytes = timer.to_bytes();
+        assert_eq!(TimerPayload::from_bytes(&bytes).unwrap(), timer);
+
+        let plain = TimerPayload::new(10, TimeUnit::Second);
+        assert_eq!(TimerPayload::from_bytes(&plain.to_bytes()).unwrap(), plain);
+    }
+
+    #[test]
+    fn test_extended_instruction_timer() {
+        let base = Instruction::new(Action::SET_TIMER, Subject::TIME, Modifier::default());
+        let timer = TimerPayload::new(3, TimeUnit::Hour);
+        let ext = ExtendedInstruction::with_timer(base, timer);
+
+        let bytes = ext.to_bytes();
+        assert_eq!(bytes.len(), 29); // 6 + 1 + 22
+
+        let parsed = ExtendedInstruction::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.as_timer().unwrap(), &timer);
+        assert!(parsed.validate().is_ok());
+    }
+
+    #[test]
+    fn test_timer_firing() {
+        let timer = TimerPayload::new(5, TimeUnit::Minute).repeating(2, 0);
+        assert_eq!(timer.first_fire(1000), Some(1300));
+        assert_eq!(timer.interval_seconds(), 300);
+
+        let past_deadline = TimerPayload::new(1, TimeUnit::Hour).with_deadline(2000);
+        assert_eq!(past_deadline.first_fire(1000), None);
+
+        let absolute = TimerPayload::until(5000);
+        assert_eq!(absolute.first_fire(1000), Some(5000));
+
+        let clock = crate::clock::ManualClock::new(1000);
+        assert_eq!(timer.first_fire_with(&clock), Some(1300));
+        assert_eq!(timer.try_first_fire_with(&clock), Ok(Some(1300)));
+        clock.advance(1000);
+        assert_eq!(past_deadline.try_first_fire_with(&clock), Ok(None));
+    }
+
+    #[test]
+    fn test_time_payload_with_clock() {
+        use crate::clock::{Clock, ClockError, FixedClock};
+
+        let clock = FixedClock(1_700_000_000);
+        let time = TimePayload::now_with(&clock);
+        assert_eq!(time, TimePayload::at(1_700_000_000));
+        assert_eq!(TimePayload::try_now_with(&clock), Ok(time));
+
+        let later = TimePayload::from_now_with(&clock, 2, TimeUnit::Day);
+        assert_eq!(later.target_timestamp(), 1_700_000_000 + 2 * 86_400);
+        assert_eq!(
+            TimePayload::try_from_now_with(&clock, 2, TimeUnit::Day),
+            Ok(later)
+        );
+
+        struct Unset;
+        impl Clock for Unset {
+            fn try_now(&self) -> Result<i64, ClockError> {
+                Err(ClockError::Unavailable("not synchronized".to_string()))
+            }
+        }
+        assert!(TimePayload::try_now_with(&Unset).is_err());
+        let dyn_clock: &dyn Clock = &clock;
+        assert!(TimePayload::try_now_with(dyn_clock).is_ok());
+    }
+
+    #[test]
+    fn test_timer_validation() {
+        let timer_base = Instruction::simple(Action::SET_TIMER, Subject::TIME);
+        let time_ext = ExtendedInstruction::with_time(timer_base, TimePayload::at(0));
+        assert!(matches!(
+            time_ext.validate(),
+            Err(InstructionError::PayloadMismatch {
+                payload: PayloadType::Time,
+                ..
+            })
+        ));
+
+        let respond = Instruction::simple(Action::RESPOND, Subject::TIME);
+        let misplaced =
+            ExtendedInstruction::with_timer(respond, TimerPayload::new(1, TimeUnit::Minute));
+        assert!(misplaced.validate().is_err());
+
+        let empty =
+            ExtendedInstruction::with_timer(timer_base, TimerPayload::new(0, TimeUnit::Minute));
+        assert!(empty.validate().is_err());
+
+        // A bare SET_TIMER is fine; any other payload type is not
+        assert!(ExtendedInstruction::new(timer_base).validate().is_ok());
+        let schedule = ExtendedInstruction {
+            base: timer_base,
+            payload: Payload::Schedule(SchedulePayload::once(0, 60)),
+        };
+        assert!(matches!(
+            schedule.validate(),
+            Err(InstructionError::PayloadMismatch {
+                payload: PayloadType::Schedule,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_extended_instruction_conversion() {
+        use crate::units::Unit;
+
+        // "convert 5 miles to km"
+        let base = Instruction::new(Action::CALCULATE, Subject::NUMBER, Modifier::default());
+        let conversion = ConversionPayload::new(5.0, Unit::MILE, Unit::KILOMETER);
+        let ext = ExtendedInstruction::with_conversion(base, conversion);
+
+        let bytes = ext.to_bytes();
+        assert_eq!(bytes.len(), 19); // 6 + 1 + 12
+
+        let parsed = ExtendedInstruction::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.as_conversion().unwrap(), &conversion);
+        assert!(parsed.validate().is_ok());
+
+        // A conversion is not a timer payload
+        let timer_base = Instruction::simple(Action::SET_TIMER, Subject::TIME);
+        let on_timer = ExtendedInstruction::with_conversion(timer_base, conversion);
+        assert!(matches!(
+            on_timer.validate(),
+            Err(InstructionError::PayloadMismatch {
+                payload: PayloadType::Conversion,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_extended_instruction_expr() {
+        // "(3 + 4) * 2" as a single instruction
+        let base = Instruction::new(Action::CALCULATE, Subject::EQUATION, Modifier::default());
+        let expr = ExprPayload::parse("(3 + 4) * 2").unwrap();
+        let ext = ExtendedInstruction::with_expr(base, expr.clone());
+
+        let bytes = ext.to_bytes();
+        assert_eq!(bytes.len(), ext.byte_size());
+        assert_eq!(bytes.len(), 6 + 1 + 2 + expr.to_bytes().len());
+
+        let parsed = ExtendedInstruction::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.as_expr().unwrap(), &expr);
+        assert_eq!(parsed.as_expr().unwrap().evaluate(), Ok(14.0));
+        assert!(parsed.validate().is_ok());
+
+        // An expression is not a timer payload
+        let timer_base = Instruction::simple(Action::SET_TIMER, Subject::TIME);
+        let on_timer = ExtendedInstruction::with_expr(timer_base, expr);
+        assert!(matches!(
+            on_timer.validate(),
+            Err(InstructionError::PayloadMismatch {
+                payload: PayloadType::Expr,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_extended_instruction_exact_calc() {
+        use crate::exact::{ExactCalcPayload, Number};
+
+        let base = Instruction::new(Action::CALCULATE, Subject::NUMBER, Modifier::default());
+        let calc = ExactCalcPayload::new(
+            Op::Add,
+            Number::parse("0.1").unwrap(),
+            Number::parse("0.2").unwrap(),
+        );
+        let ext = ExtendedInstruction::with_exact_calc(base, calc);
+
+        let bytes = ext.to_bytes();
+        assert_eq!(bytes.len(), PayloadType::ExactCalc.total_size());
+        assert_eq!(bytes[6], 0x06);
+
+        let parsed = ExtendedInstruction::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.as_exact_calc(), Some(&calc));
+        assert_eq!(
+            parsed
+                .as_exact_calc()
+                .unwrap()
+                .evaluate()
+                .unwrap()
+                .to_string(),
+            "0.3"
+        );
+        assert!(parsed.to_string().ends_with(" + 0.1 + 0.2"));
+    }
+
+    #[test]
+    fn test_extended_instruction_schedule() {
+        use crate::schedule::{SchedulePayload, WeekdaySet};
+
+        let base = Instruction::new(Action::RESPOND, Subject::SCHEDULE, Modifier::default());
+        let schedule = SchedulePayload::once(1_792_400_400, 8 * 3600)
+            .daily(1)
+            .on(WeekdaySet::WEEKDAYS);
+        let ext = ExtendedInstruction::with_schedule(base, schedule);
+
+        let bytes = ext.to_bytes();
+        assert_eq!(bytes.len(), PayloadType::Schedule.total_size());
+        let parsed = ExtendedInstruction::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.as_schedule(), Some(&schedule));
+        assert!(parsed.validate().is_ok());
+        assert!(parsed
+            .to_string()
+            .ends_with("RRULE:FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR"));
+
+        let bad = ExtendedInstruction::with_schedule(base, schedule.daily(0));
+        assert!(bad.validate().is_err());
     }
 
     #[test]
@@ -529,6 +1655,7 @@ mod tests {
         assert_eq!(PayloadType::None.total_size(), 7);
         assert_eq!(PayloadType::Calc.total_size(), 24);
         assert_eq!(PayloadType::Time.total_size(), 21);
+        assert_eq!(PayloadType::Timer.total_size(), 29);
     }
 
     #[test]
@@ -538,24 +1665,182 @@ mod tests {
 
         let sqrt = CalcPayload::unary(Op::Sqrt, 144.0);
         assert_eq!(format!("{}", sqrt), "sqrt(144)");
+
+        assert_eq!(CalcPayload::unary(Op::Neg, 3.0).to_string(), "-3");
+        assert_eq!(CalcPayload::unary(Op::Factorial, 5.0).to_string(), "5!");
+        assert_eq!(CalcPayload::unary(Op::Neg, -3.0).to_string(), "-(-3)");
+        assert_eq!(CalcPayload::unary(Op::Factorial, -3.0).to_string(), "(-3)!");
+        assert_eq!(CalcPayload::unary(Op::Abs, -3.0).to_string(), "abs(-3)");
+        assert_eq!(
+            CalcPayload::new(Op::PercentOf, 15.0, 80.0).to_string(),
+            "15% of 80"
+        );
+        assert_eq!(CalcPayload::new(Op::Max, 2.0, 9.0).to_string(), "max(2, 9)");
+    }
+
+    #[test]
+    fn test_op_codes_and_arity() {
+        for op in Op::ALL {
+            assert_eq!(Op::from_byte(op.to_byte()), Some(op));
+            assert_eq!(
+                CalcPayload::from_bytes(&CalcPayload::unary(op, 1.0).to_bytes())
+                    .unwrap()
+                    .op,
+                op
+            );
+        }
+        let mut codes: Vec<u8> = Op::ALL.iter().map(|op| op.to_byte()).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), Op::ALL.len());
+
+        assert_eq!(Op::Add.arity(), 2);
+        assert_eq!(Op::Min.arity(), 2);
+        assert!(Op::Sqrt.is_unary());
+        assert!(Op::Factorial.is_unary());
+        assert!(Op::Sin.is_unary());
     }
 
     #[test]
     fn test_target_timestamp() {
-        let time = TimePayload {
-            reference: 1000000,
-            delta: 5,
-            unit: TimeUnit::Minute,
-            tz_offset: 0,
-        };
+        let time = TimePayload::with_delta(1000000, 5, TimeUnit::Minute);
         assert_eq!(time.target_timestamp(), 1000300); // +300 seconds
 
-        let time_tz = TimePayload {
-            reference: 1000000,
-            delta: 0,
-            unit: TimeUnit::Second,
-            tz_offset: -8,
-        };
-        assert_eq!(time_tz.target_timestamp(), 1000000 - 8 * 3600);
+        // The offset changes the local reading, not the instant
+        let time_tz = TimePayload::at(1000000).with_tz(-8);
+        assert_eq!(time_tz.target_timestamp(), 1000000);
+        assert_eq!(time_tz.local_timestamp(), 1000000 - 8 * 3600);
+    }
+
+    #[test]
+    fn test_sub_hour_offsets_and_zones() {
+        use crate::calendar::DateTime;
+        use crate::tz::ZoneId;
+
+        let ts = |y, mo, d, h| DateTime::new(y, mo, d, h, 0, 0).unwrap().to_timestamp();
+
+        let india = TimePayload::at(ts(2026, 10, 16, 4)).with_offset_minutes(330);
+        assert_eq!(india.local_timestamp(), ts(2026, 10, 16, 4) + 330 * 60);
+
+        // "Tomorrow at this time" across the US fall-back keeps 09:00 local
+        let ny = TimePayload::with_delta(ts(2024, 11, 2, 13), 1, TimeUnit::Day)
+            .with_zone(ZoneId::NEW_YORK);
+        assert_eq!(ny.target_timestamp(), ts(2024, 11, 3, 14));
+        assert_eq!(
+            DateTime::from_timestamp(ny.local_timestamp()),
+            DateTime::new(2024, 11, 3, 9, 0, 0).unwrap()
+        );
+        // ...while hours are exact durations
+        let hours = TimePayload::with_delta(ts(2024, 11, 2, 13), 24, TimeUnit::Hour)
+            .with_zone(ZoneId::NEW_YORK);
+        assert_eq!(hours.target_timestamp(), ts(2024, 11, 3, 13));
+    }
+
+    #[test]
+    fn test_time_payload_versions() {
+        use crate::tz::ZoneId;
+
+        let legacy = TimePayload::with_delta(1000000, 2, TimeUnit::Hour).with_tz(5);
+        assert_eq!(legacy.payload_type(), PayloadType::Time);
+        assert_eq!(legacy.to_bytes().len(), 14);
+        #[allow(deprecated)]
+        let hours = legacy.tz_offset();
+        assert_eq!(hours, 5);
+
+        let base = Instruction::new(Action::RESPOND, Subject::TIME, Modifier::default());
+        for time in [
+            TimePayload::at(1000000).with_offset_minutes(-210),
+            TimePayload::at(1000000).with_offset_minutes(345),
+            TimePayload::at(1000000).with_zone(ZoneId::KOLKATA),
+        ] {
+            assert_eq!(time.payload_type(), PayloadType::ZonedTime);
+            let ext = ExtendedInstruction::with_time(base, time);
+            let bytes = ext.to_bytes();
+            assert_eq!(bytes.len(), PayloadType::ZonedTime.total_size());
+            assert_eq!(bytes[6], 0x07);
+            assert_eq!(&bytes[7..], &time.to_zoned_bytes()[..]);
+            assert_eq!(TimePayload::from_zoned_bytes(&bytes[7..]), Some(time));
+            assert_eq!(ExtendedInstruction::from_bytes(&bytes).unwrap(), ext);
+        }
+
+        // Pre-existing 14-byte encodings still decode
+        let mut old = base.to_bytes().to_vec();
+        old.push(0x02);
+        old.extend_from_slice(&1000000i64.to_be_bytes());
+        old.extend_from_slice(&0i32.to_be_bytes());
+        old.extend_from_slice(&[TimeUnit::Second.to_byte(), (-8i8) as u8]);
+        let parsed = ExtendedInstruction::from_bytes(&old).unwrap();
+        assert_eq!(parsed.as_time().unwrap().offset_minutes, -480);
+        assert_eq!(parsed.to_bytes(), old);
+    }
+
+    #[test]
+    fn test_time_payload_validation() {
+        use crate::tz::ZoneId;
+
+        let base = Instruction::new(Action::RESPOND, Subject::TIME, Modifier::default());
+        let check = |time: TimePayload| ExtendedInstruction::with_time(base, time).validate();
+        assert!(check(TimePayload::at(0).with_offset_minutes(840)).is_ok());
+        assert!(check(TimePayload::at(0).with_offset_minutes(900)).is_err());
+        assert!(check(TimePayload::at(0).with_zone(ZoneId::AUCKLAND)).is_ok());
+        assert!(check(TimePayload::at(0).with_zone(ZoneId(0x7777))).is_err());
+    }
+
+    #[test]
+    fn test_target_timestamp_calendar_months() {
+        use crate::calendar::DateTime;
+
+        // 2024-01-31 00:00 UTC + 2 months lands on March 31, not 60 days later
+        let jan31 = DateTime::new(2024, 1, 31, 0, 0, 0).unwrap().to_timestamp();
+        let time = TimePayload::with_delta(jan31, 2, TimeUnit::Month);
+        let target = DateTime::from_timestamp(time.target_timestamp());
+        assert_eq!(target, DateTime::new(2024, 3, 31, 0, 0, 0).unwrap());
+
+        assert_eq!(
+            time.target_timestamp_with(TimeArithmetic::FixedSeconds),
+            jan31 + 2 * TimeUnit::Month.seconds()
+        );
+
+        let leap = TimePayload::with_delta(jan31, 1, TimeUnit::Year);
+        assert_eq!(leap.target_timestamp() - jan31, 366 * 86_400);
+    }
+
+    #[test]
+    fn test_extreme_time_payload_displays() {
+        use crate::tz::ZoneId;
+
+        let base = Instruction::simple(Action::RESPOND, Subject::TIME);
+        let extremes = [
+            TimePayload::with_delta(-9_176_679_732_556_240_921, -1_670_547_979, TimeUnit::Year),
+            TimePayload::with_delta(i64::MAX, i32::MAX, TimeUnit::Month).with_offset_minutes(-720),
+            TimePayload::with_delta(i64::MIN, i32::MIN, TimeUnit::Week).with_zone(ZoneId::AUCKLAND),
+        ];
+        for time in extremes {
+            let bytes = ExtendedInstruction::with_time(base, time).to_bytes();
+            let parsed = ExtendedInstruction::from_bytes(&bytes).unwrap();
+            assert!(!parsed.to_string().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_payload_type_names() {
+        for b in 0x00..=PayloadType::RESERVED_END {
+            if let Some(payload_type) = PayloadType::from_byte(b) {
+                assert_eq!(
+                    PayloadType::from_name(payload_type.name()),
+                    Some(payload_type)
+                );
+            }
+        }
+        assert_eq!(
+            PayloadType::from_name("zonedtime"),
+            Some(PayloadType::ZonedTime)
+        );
+        assert_eq!(
+            PayloadType::from_name("0x90"),
+            Some(PayloadType::Custom(0x90))
+        );
+        assert_eq!(PayloadType::from_name("0x7F"), None);
+        assert_eq!(PayloadType::from_name("Custom"), None);
     }
 }
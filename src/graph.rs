@@ -0,0 +1,332 @@
+//! Graph export of programs
+//!
+//! Renders a [`Program`] as Graphviz DOT or Mermaid flowchart text, for design
+//! reviews and docs. Each instruction becomes a node labeled with its
+//! position, mnemonic action and subject names, payload and a summary of its
+//! non-default modifier fields. TRM models and RAG documents the program
+//! touches get nodes of their own:
+//!
+//! - `CHAIN`/`FORK` nodes point at the model they call
+//! - `FORK` nodes have a dashed `join` edge to their `MERGE`
+//! - instructions with a RAG address have a dotted edge to the document
+//!
+//! ```rust
+//! use frame_isa::{Action, Instruction, Program, Subject};
+//!
+//! let program = Program::from_instructions(&[
+//!     Instruction::simple(Action::RETRIEVE, Subject::rag_ref(0x42)),
+//!     Instruction::simple(Action::CHAIN, Subject::trm_ref(3)),
+//! ])
+//! .unwrap();
+//!
+//! let mermaid = program.to_mermaid();
+//! assert!(mermaid.starts_with("flowchart TD\n"));
+//! assert!(mermaid.contains(r#"i0["0: RETRIEVE RAG_REF 66"]"#));
+//! assert!(mermaid.contains("i1 -->|chain| m3"));
+//! assert!(program.to_dot().contains("i0 -> r0_66 [style=dotted];"));
+//! ```
+
+use crate::program::{NodeKind, Program};
+use crate::{Action, ExtendedInstruction, Subject};
+use std::fmt::Write;
+
+/// How a node is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Instruction,
+    Exit,
+    Branch,
+    Model,
+    Document,
+}
+
+/// How an edge is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    /// Control flow
+    Flow,
+    /// `CHAIN`/`FORK` to the called model
+    Call(&'static str),
+    /// `FORK` to its `MERGE`
+    Join,
+    /// Instruction to a RAG document
+    Lookup,
+}
+
+#[derive(Debug)]
+struct GraphNode {
+    id: String,
+    lines: Vec<String>,
+    shape: Shape,
+}
+
+#[derive(Debug)]
+struct GraphEdge {
+    from: String,
+    to: String,
+    style: Style,
+}
+
+/// Format-independent graph of a program
+#[derive(Debug, Default)]
+struct Graph {
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+}
+
+impl Graph {
+    fn build(program: &Program) -> Self {
+        let mut graph = Self::default();
+        for (node, instr) in program.nodes().iter().zip(program.instructions()) {
+            let id = format!("i{}", node.index);
+            let shape = match node.kind {
+                NodeKind::Exit => Shape::Exit,
+                NodeKind::Fork { .. } | NodeKind::Merge { .. } => Shape::Branch,
+                NodeKind::Step | NodeKind::Chain { .. } => Shape::Instruction,
+            };
+            graph.node(id.clone(), instruction_lines(node.index, instr), shape);
+
+            for successor in program.successors(node.index) {
+                let style = match node.kind {
+                    NodeKind::Fork { merge, .. } if merge == successor => Style::Join,
+                    _ => Style::Flow,
+                };
+                graph.edge(&id, format!("i{}", successor), style);
+            }
+            let call = match node.kind {
+                NodeKind::Chain { model_id } => Some(("chain", model_id)),
+                NodeKind::Fork { model_id, .. } => Some(("fork", model_id)),
+                _ => None,
+            };
+            if let Some((label, model_id)) = call {
+                let model = format!("m{}", model_id);
+                graph.node(
+                    model.clone(),
+                    vec![format!("TRM 0x{:02X}", model_id)],
+                    Shape::Model,
+                );
+                graph.edge(&id, model, Style::Call(label));
+            }
+            if let Some(address) = instr.rag_address() {
+                let document = format!("r{}_{}", address.collection, address.doc_id);
+                graph.node(document.clone(), vec![address.to_string()], Shape::Document);
+                graph.edge(&id, document, Style::Lookup);
+            }
+        }
+        graph
+    }
+
+    /// Add a node unless one with this ID exists
+    fn node(&mut self, id: String, lines: Vec<String>, shape: Shape) {
+        if !self.nodes.iter().any(|node| node.id == id) {
+            self.nodes.push(GraphNode { id, lines, shape });
+        }
+    }
+
+    fn edge(&mut self, from: &str, to: String, style: Style) {
+        self.edges.push(GraphEdge {
+            from: from.to_string(),
+            to,
+            style,
+        });
+    }
+}
+
+fn action_label(action: Action) -> String {
+    match action.name() {
+        "UNKNOWN" => format!("0x{:04X}", action.0),
+        name => name.to_string(),
+    }
+}
+
+fn subject_label(subject: Subject) -> String {
+    if let Some(model_id) = subject.trm_model_id() {
+        format!("{} 0x{:02X}", subject.name(), model_id)
+    } else if let Some(doc_id) = subject.rag_doc_id() {
+        format!("{} {}", subject.name(), doc_id)
+    } else if subject.name() == "UNKNOWN" {
+        format!("0x{:04X}", subject.0)
+    } else {
+        subject.name().to_string()
+    }
+}
+
+/// Label lines: instruction, payload (if any), modifier summary (if not default)
+fn instruction_lines(index: usize, instr: &ExtendedInstruction) -> Vec<String> {
+    let base = instr.base;
+    let mut lines = vec![format!(
+        "{}: {} {}",
+        index,
+        action_label(base.action),
+        subject_label(base.subject)
+    )];
+    // The payload part of the instruction's Display, after the base
+    let full = instr.to_string();
+    let payload = full[base.to_string().len()..].trim();
+    if !payload.is_empty() {
+        lines.push(payload.to_string());
+    }
+    let modifiers = base.modifier.summary();
+    if !modifiers.is_empty() {
+        lines.push(modifiers);
+    }
+    lines
+}
+
+fn dot_label(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| line.replace('\\', "\\\\").replace('"', "\\\""))
+        .collect::<Vec<_>>()
+        .join("\\n")
+}
+
+fn mermaid_label(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| {
+            line.replace('"', "#quot;")
+                .replace('<', "#lt;")
+                .replace('>', "#gt;")
+        })
+        .collect::<Vec<_>>()
+        .join("<br/>")
+}
+
+impl Program {
+    /// Render the program as a Graphviz DOT digraph
+    pub fn to_dot(&self) -> String {
+        let graph = Graph::build(self);
+        let mut out = String::from("digraph program {\n    node [fontname=\"monospace\"];\n");
+        for node in &graph.nodes {
+            let shape = match node.shape {
+                Shape::Instruction => "box",
+                Shape::Exit => "doubleoctagon",
+                Shape::Branch => "diamond",
+                Shape::Model => "component",
+                Shape::Document => "cylinder",
+            };
+            let _ = writeln!(
+                out,
+                "    {} [label=\"{}\", shape={}];",
+                node.id,
+                dot_label(&node.lines),
+                shape
+            );
+        }
+        for edge in &graph.edges {
+            let attributes = match edge.style {
+                Style::Flow => String::new(),
+                Style::Call(label) => format!(" [label=\"{}\"]", label),
+                Style::Join => " [label=\"join\", style=dashed]".to_string(),
+                Style::Lookup => " [style=dotted]".to_string(),
+            };
+            let _ = writeln!(out, "    {} -> {}{};", edge.from, edge.to, attributes);
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render the program as a Mermaid flowchart
+    pub fn to_mermaid(&self) -> String {
+        let graph = Graph::build(self);
+        let mut out = String::from("flowchart TD\n");
+        for node in &graph.nodes {
+            let (open, close) = match node.shape {
+                Shape::Instruction => ("[", "]"),
+                Shape::Exit => ("([", "])"),
+                Shape::Branch => ("{", "}"),
+                Shape::Model => ("[[", "]]"),
+                Shape::Document => ("[(", ")]"),
+            };
+            let _ = writeln!(
+                out,
+                "    {}{}\"{}\"{}",
+                node.id,
+                open,
+                mermaid_label(&node.lines),
+                close
+            );
+        }
+        for edge in &graph.edges {
+            let arrow = match edge.style {
+                Style::Flow => "-->".to_string(),
+                Style::Call(label) => format!("-->|{}|", label),
+                Style::Join => "-.->|join|".to_string(),
+                Style::Lookup => "-.-".to_string(),
+            };
+            let _ = writeln!(out, "    {} {} {}", edge.from, arrow, edge.to);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Instruction, Modifier, RagQueryPayload};
+
+    fn sample() -> Program {
+        let search = ExtendedInstruction::with_rag_query(
+            Instruction::simple(Action::SEARCH, Subject::DOCUMENTATION),
+            RagQueryPayload::new("refund \"policy\"").with_top_k(3),
+        );
+        Program::new(vec![
+            search,
+            ExtendedInstruction::new(Instruction::simple(Action::FORK, Subject::trm_ref(1))),
+            ExtendedInstruction::new(Instruction::simple(Action::FORK, Subject::trm_ref(2))),
+            ExtendedInstruction::new(Instruction::simple(Action::MERGE, Subject::NULL)),
+            ExtendedInstruction::new(Instruction::new(
+                Action::EXPLAIN,
+                Subject::rag_ref(7),
+                Modifier::professional(),
+            )),
+            ExtendedInstruction::new(Instruction::simple(Action::HALT, Subject::NULL)),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_dot() {
+        let dot = sample().to_dot();
+        assert!(dot.starts_with("digraph program {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains(
+            r#"i0 [label="0: SEARCH DOCUMENTATION\n? \"refund \\\"policy\\\"\" top_k=3", shape=box];"#
+        ));
+        assert!(dot.contains(r#"i1 [label="1: FORK TRM_REF 0x01", shape=diamond];"#));
+        assert!(dot.contains(r#"m2 [label="TRM 0x02", shape=component];"#));
+        assert!(dot.contains(
+            r#"i4 [label="4: EXPLAIN RAG_REF 7\nvoice=Formal, accuracy=High", shape=box];"#
+        ));
+        assert!(dot.contains(r#"i5 [label="5: HALT NULL", shape=doubleoctagon];"#));
+        assert!(dot.contains("    i1 -> i2;\n"));
+        assert!(dot.contains(r#"i1 -> i3 [label="join", style=dashed];"#));
+        assert!(dot.contains(r#"i1 -> m1 [label="fork"];"#));
+        assert!(dot.contains("i4 -> r0_7 [style=dotted];"));
+        assert!(!dot.contains("i5 ->"));
+    }
+
+    #[test]
+    fn test_mermaid() {
+        let mermaid = sample().to_mermaid();
+        assert!(mermaid.contains(
+            r#"i0["0: SEARCH DOCUMENTATION<br/>? #quot;refund \#quot;policy\#quot;#quot; top_k=3"]"#
+        ));
+        assert!(mermaid.contains(r#"i3{"3: MERGE NULL"}"#));
+        assert!(mermaid.contains(r#"m1[["TRM 0x01"]]"#));
+        assert!(mermaid.contains(r#"r0_7[("rag://0/7")]"#));
+        assert!(mermaid.contains(r#"i5(["5: HALT NULL"])"#));
+        assert!(mermaid.contains("i2 -.->|join| i3"));
+        assert!(mermaid.contains("i2 -->|fork| m2"));
+        assert!(mermaid.contains("i4 -.- r0_7"));
+
+        // Models and documents appear once however often they are referenced
+        let program = Program::from_instructions(&[
+            Instruction::simple(Action::CHAIN, Subject::trm_ref(4)),
+            Instruction::simple(Action::CHAIN, Subject::trm_ref(4)),
+        ])
+        .unwrap();
+        assert_eq!(program.to_mermaid().matches("m4[[").count(), 1);
+    }
+}
@@ -105,6 +105,9 @@ impl Instruction {
     }
 
     /// Check if this instruction requires RAG lookup
+    ///
+    /// Only sees the subject; [`ExtendedInstruction::needs_rag`](crate::ExtendedInstruction::needs_rag)
+    /// also covers RAG address and query payloads.
     #[inline]
     pub const fn needs_rag(&self) -> bool {
         self.subject.is_rag_reference()
@@ -171,6 +174,18 @@ pub enum InstructionError {
 
     #[error("Invalid opcode string: {0}")]
     InvalidOpcodeString(String),
+
+    #[error("Payload {payload:?} is not valid for action {action}")]
+    PayloadMismatch {
+        action: Action,
+        payload: crate::PayloadType,
+    },
+
+    #[error("Invalid payload: {0}")]
+    InvalidPayload(String),
+
+    #[error("RAG document id {doc_id} does not fit a RAG subject; use an extended RAG address")]
+    RagDocIdOutOfRange { doc_id: u64 },
 }
 
 /// Builder for constructing instructions fluently
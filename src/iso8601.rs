@@ -0,0 +1,421 @@
+//! ISO 8601 date-times and durations for time payloads
+//!
+//! Date-times use the extended format with an optional UTC offset:
+//!
+//! - `2026-10-16T10:00:00+05:30`, `2026-10-16T04:30:00Z`, `2026-10-16 10:00`
+//! - `2026-10-16` (midnight UTC)
+//!
+//! A missing offset is read as UTC, and fractional seconds are truncated.
+//!
+//! Durations map onto a single [`TimeUnit`]: `PT3H` is 3 hours, `P2M` is 2
+//! months, and combined components are expressed in the smallest unit present
+//! (`PT1H30M` is 90 minutes, `P1Y2M` is 14 months). Durations mixing months or
+//! years with days or times (`P1M2D`) have no single-unit equivalent and are
+//! rejected, as are fractional components.
+//!
+//! ```rust
+//! use frame_isa::iso8601::{format_duration, parse_duration};
+//! use frame_isa::{TimePayload, TimeUnit};
+//!
+//! let time = TimePayload::from_iso8601("2026-10-16T10:00:00+05:30").unwrap();
+//! assert_eq!(time.reference, 1_792_125_000);
+//! assert_eq!(time.offset_minutes, 330);
+//! assert_eq!(time.to_iso8601(), "2026-10-16T10:00:00+05:30");
+//!
+//! assert_eq!(parse_duration("PT1H30M"), Ok((90, TimeUnit::Minute)));
+//! assert_eq!(format_duration(2, TimeUnit::Month), "P2M");
+//! ```
+
+use crate::calendar::{days_from_civil, DateTime, SECONDS_PER_DAY};
+use crate::tz::{format_offset, MAX_OFFSET_MINUTES, MIN_OFFSET_MINUTES};
+use crate::{TimePayload, TimeUnit};
+use thiserror::Error;
+
+/// Errors from parsing ISO 8601 strings
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum Iso8601Error {
+    #[error("Invalid ISO 8601 date-time: {0}")]
+    InvalidDateTime(String),
+
+    #[error("Invalid ISO 8601 duration: {0}")]
+    InvalidDuration(String),
+
+    #[error("Duration {0} mixes months with days or times")]
+    MixedDuration(String),
+
+    #[error("Duration {0} is out of range")]
+    DurationOutOfRange(String),
+}
+
+/// Format a UTC timestamp as local time with its offset (`Z` for UTC)
+pub fn format_datetime(timestamp: i64, offset_minutes: i16) -> String {
+    let local = DateTime::from_timestamp(timestamp.saturating_add(offset_minutes as i64 * 60));
+    let year = if (0..=9999).contains(&local.year) {
+        format!("{:04}", local.year)
+    } else {
+        format!("{:+05}", local.year)
+    };
+    let offset = if offset_minutes == 0 {
+        "Z".to_string()
+    } else {
+        format_offset(offset_minutes)
+    };
+    format!(
+        "{}-{:02}-{:02}T{:02}:{:02}:{:02}{}",
+        year, local.month, local.day, local.hour, local.minute, local.second, offset
+    )
+}
+
+/// Parse a date-time into a UTC timestamp and its offset in minutes
+pub fn parse_datetime(s: &str) -> Result<(i64, i16), Iso8601Error> {
+    let s = s.trim();
+    let invalid = || Iso8601Error::InvalidDateTime(s.to_string());
+
+    let (date, time) = match s.find(['T', 't', ' ']) {
+        Some(i) => (&s[..i], Some(&s[i + 1..])),
+        None => (s, None),
+    };
+    let (year, month, day) = parse_date(date).ok_or_else(invalid)?;
+    let (seconds, offset) = match time {
+        Some(time) => parse_time(time).ok_or_else(invalid)?,
+        None => (0, 0),
+    };
+    DateTime::new(year, month, day, 0, 0, 0).ok_or_else(invalid)?;
+    // Years too far out for a timestamp are rejected rather than saturated
+    let timestamp = days_from_civil(year, month, day)
+        .checked_mul(SECONDS_PER_DAY)
+        .and_then(|midnight| midnight.checked_add(seconds - offset as i64 * 60))
+        .ok_or_else(invalid)?;
+    Ok((timestamp, offset))
+}
+
+/// Parse `YYYY-MM-DD` (years may be signed and longer than four digits)
+fn parse_date(date: &str) -> Option<(i64, u8, u8)> {
+    let mut parts = date.rsplitn(3, '-');
+    let day = parse_digits(parts.next()?, 2)?;
+    let month = parse_digits(parts.next()?, 2)?;
+    let year = parts.next()?;
+    let digits = year.strip_prefix(['+', '-']).unwrap_or(year);
+    if digits.len() < 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((year.parse().ok()?, month as u8, day as u8))
+}
+
+/// Parse `HH:MM[:SS[.fff]][Z|±HH[:MM]]` into seconds after midnight and an offset
+fn parse_time(time: &str) -> Option<(i64, i16)> {
+    let (clock, offset) = if let Some(clock) = time.strip_suffix(['Z', 'z']) {
+        (clock, 0)
+    } else if let Some(i) = time.rfind(['+', '-']) {
+        (&time[..i], parse_offset(&time[i..])?)
+    } else {
+        (time, 0)
+    };
+
+    let clock = clock.split(['.', ',']).next()?;
+    let mut fields = clock.split(':');
+    let hour = parse_digits(fields.next()?, 2)?;
+    let minute = parse_digits(fields.next()?, 2)?;
+    let second = fields.next().map_or(Some(0), |s| parse_digits(s, 2))?;
+    if fields.next().is_some() || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    Some((hour * 3600 + minute * 60 + second, offset))
+}
+
+/// Parse `±HH:MM`, `±HHMM` or `±HH` into minutes
+fn parse_offset(offset: &str) -> Option<i16> {
+    let (sign, digits) = offset.split_at(1);
+    if !digits.is_ascii() {
+        return None;
+    }
+    let (hours, minutes) = match digits.as_bytes() {
+        [_, _] => (parse_digits(digits, 2)?, 0),
+        [_, _, b':', _, _] => (
+            parse_digits(&digits[..2], 2)?,
+            parse_digits(&digits[3..], 2)?,
+        ),
+        [_, _, _, _] => (
+            parse_digits(&digits[..2], 2)?,
+            parse_digits(&digits[2..], 2)?,
+        ),
+        _ => return None,
+    };
+    if minutes > 59 {
+        return None;
+    }
+    let total = (hours * 60 + minutes) as i16;
+    let total = if sign == "-" { -total } else { total };
+    (MIN_OFFSET_MINUTES..=MAX_OFFSET_MINUTES)
+        .contains(&total)
+        .then_some(total)
+}
+
+/// Parse exactly `width` ASCII digits
+fn parse_digits(s: &str, width: usize) -> Option<i64> {
+    (s.len() == width && s.bytes().all(|b| b.is_ascii_digit()))
+        .then(|| s.parse().ok())
+        .flatten()
+}
+
+/// Format an amount of a unit as a duration (`PT3H`, `P2M`, `-P1D`)
+pub fn format_duration(amount: i64, unit: TimeUnit) -> String {
+    let sign = if amount < 0 { "-" } else { "" };
+    let (time, designator) = designator(unit);
+    let t = if time { "T" } else { "" };
+    format!("{}P{}{}{}", sign, t, amount.unsigned_abs(), designator)
+}
+
+/// ISO 8601 designator for a unit: (is a time component, letter)
+fn designator(unit: TimeUnit) -> (bool, char) {
+    match unit {
+        TimeUnit::Second => (true, 'S'),
+        TimeUnit::Minute => (true, 'M'),
+        TimeUnit::Hour => (true, 'H'),
+        TimeUnit::Day => (false, 'D'),
+        TimeUnit::Week => (false, 'W'),
+        TimeUnit::Month => (false, 'M'),
+        TimeUnit::Year => (false, 'Y'),
+    }
+}
+
+/// Parse a duration into an amount of the smallest unit it mentions
+///
+/// Components must appear in ISO 8601 order (`Y M W D T H M S`), each at most once.
+pub fn parse_duration(s: &str) -> Result<(i64, TimeUnit), Iso8601Error> {
+    let s = s.trim();
+    let invalid = || Iso8601Error::InvalidDuration(s.to_string());
+    let out_of_range = || Iso8601Error::DurationOutOfRange(s.to_string());
+
+    let (negative, body) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let body = body.strip_prefix(['P', 'p']).ok_or_else(invalid)?;
+
+    // Totals in months and seconds; the smallest unit seen decides the result unit
+    let (mut months, mut seconds) = (0i64, 0i64);
+    let mut smallest: Option<TimeUnit> = None;
+    let mut previous: Option<TimeUnit> = None;
+    let mut in_time = false;
+    let mut number = String::new();
+    for c in body.chars() {
+        match c.to_ascii_uppercase() {
+            'T' if !in_time && number.is_empty() => in_time = true,
+            d if d.is_ascii_digit() => number.push(d),
+            d => {
+                let unit = match (in_time, d) {
+                    (false, 'Y') => TimeUnit::Year,
+                    (false, 'M') => TimeUnit::Month,
+                    (false, 'W') => TimeUnit::Week,
+                    (false, 'D') => TimeUnit::Day,
+                    (true, 'H') => TimeUnit::Hour,
+                    (true, 'M') => TimeUnit::Minute,
+                    (true, 'S') => TimeUnit::Second,
+                    _ => return Err(invalid()),
+                };
+                // Units are declared smallest first, so ISO order is descending
+                if previous.is_some_and(|prev| unit.to_byte() >= prev.to_byte()) {
+                    return Err(invalid());
+                }
+                previous = Some(unit);
+                let n: i64 = number.parse().map_err(|_| invalid())?;
+                number.clear();
+                let is_calendar = matches!(unit, TimeUnit::Year | TimeUnit::Month);
+                let (total, scale) = match unit {
+                    TimeUnit::Year => (&mut months, 12),
+                    TimeUnit::Month => (&mut months, 1),
+                    other => (&mut seconds, other.seconds()),
+                };
+                *total = n
+                    .checked_mul(scale)
+                    .and_then(|n| total.checked_add(n))
+                    .ok_or_else(out_of_range)?;
+                if smallest.is_some_and(|prev| {
+                    matches!(prev, TimeUnit::Year | TimeUnit::Month) != is_calendar
+                }) {
+                    return Err(Iso8601Error::MixedDuration(s.to_string()));
+                }
+                smallest = Some(match smallest {
+                    Some(prev) if prev.seconds() <= unit.seconds() => prev,
+                    _ => unit,
+                });
+            }
+        }
+    }
+    let has_time_component =
+        previous.is_some_and(|unit| unit.to_byte() <= TimeUnit::Hour.to_byte());
+    let unit = match smallest {
+        Some(unit) if number.is_empty() && in_time == has_time_component => unit,
+        _ => return Err(invalid()),
+    };
+    let amount = match unit {
+        TimeUnit::Year => months / 12,
+        TimeUnit::Month => months,
+        other => seconds / other.seconds(),
+    };
+    Ok((if negative { -amount } else { amount }, unit))
+}
+
+impl TimePayload {
+    /// Parse a date-time as the reference, keeping its UTC offset
+    pub fn from_iso8601(s: &str) -> Result<Self, Iso8601Error> {
+        let (reference, offset) = parse_datetime(s)?;
+        Ok(Self::at(reference).with_offset_minutes(offset))
+    }
+
+    /// Set the delta from an ISO 8601 duration (`PT3H`, `P2M`, ...)
+    pub fn with_iso8601_delta(mut self, duration: &str) -> Result<Self, Iso8601Error> {
+        let (amount, unit) = parse_duration(duration)?;
+        self.delta = i32::try_from(amount)
+            .map_err(|_| Iso8601Error::DurationOutOfRange(duration.to_string()))?;
+        self.unit = unit;
+        Ok(self)
+    }
+
+    /// Target time in ISO 8601, in local time with the offset in effect
+    pub fn to_iso8601(&self) -> String {
+        let target = self.target_timestamp();
+        format_datetime(target, self.offset_at(target))
+    }
+
+    /// Delta as an ISO 8601 duration
+    pub fn delta_iso8601(&self) -> String {
+        format_duration(self.delta as i64, self.unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tz::ZoneId;
+
+    #[test]
+    fn test_format_datetime() {
+        assert_eq!(format_datetime(0, 0), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_datetime(1_792_125_000, 330),
+            "2026-10-16T10:00:00+05:30"
+        );
+        assert_eq!(
+            format_datetime(1_792_125_000, -210),
+            "2026-10-16T01:00:00-03:30"
+        );
+        assert_eq!(format_datetime(-SECONDS_PER_DAY, 0), "1969-12-31T00:00:00Z");
+        assert_eq!(
+            format_datetime(253_402_300_800, 0),
+            "+10000-01-01T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn test_parse_datetime() {
+        let expected = Ok((1_792_125_000, 330));
+        assert_eq!(parse_datetime("2026-10-16T10:00:00+05:30"), expected);
+        assert_eq!(parse_datetime("2026-10-16T10:00:00+0530"), expected);
+        assert_eq!(parse_datetime("2026-10-16t10:00:00.999+05:30"), expected);
+        assert_eq!(parse_datetime("2026-10-16 10:00+05:30"), expected);
+        assert_eq!(
+            parse_datetime("2026-10-16T04:30:00Z"),
+            Ok((1_792_125_000, 0))
+        );
+        assert_eq!(
+            parse_datetime("2026-10-16T04:30:00"),
+            Ok((1_792_125_000, 0))
+        );
+        assert_eq!(
+            parse_datetime("2026-10-16T00:00:00-08"),
+            Ok((1_792_137_600, -480))
+        );
+        assert_eq!(parse_datetime("2026-10-16"), Ok((1_792_108_800, 0)));
+
+        for bad in [
+            "2026-02-30",
+            "2026-10-16T25:00:00Z",
+            "2026-10-16T10:00:00+15:00",
+            "26-10-16",
+            "2026/10/16",
+            "2026-10-16T10",
+            "2026-10-16T10:00:00+0:530",
+            "2026-10-16T10:00:00+05:3",
+            "2026-10-16T10:00:00+053",
+            "2026-10-16T10:00:00+05:30:00",
+            "2026-10-16T10:00:00+5",
+            "",
+        ] {
+            assert!(parse_datetime(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_datetime_roundtrip() {
+        for (ts, offset) in [
+            (0, 0),
+            (1_700_000_000, 345),
+            (-86_399, -720),
+            (4_102_444_800, 840),
+        ] {
+            let s = format_datetime(ts, offset);
+            assert_eq!(parse_datetime(&s), Ok((ts, offset)), "{s}");
+        }
+    }
+
+    #[test]
+    fn test_durations() {
+        assert_eq!(parse_duration("PT3H"), Ok((3, TimeUnit::Hour)));
+        assert_eq!(parse_duration("P2M"), Ok((2, TimeUnit::Month)));
+        assert_eq!(parse_duration("PT2M"), Ok((2, TimeUnit::Minute)));
+        assert_eq!(parse_duration("P1Y"), Ok((1, TimeUnit::Year)));
+        assert_eq!(parse_duration("P1Y2M"), Ok((14, TimeUnit::Month)));
+        assert_eq!(parse_duration("P2W"), Ok((2, TimeUnit::Week)));
+        assert_eq!(parse_duration("P1W2D"), Ok((9, TimeUnit::Day)));
+        assert_eq!(parse_duration("P1DT2H"), Ok((26, TimeUnit::Hour)));
+        assert_eq!(parse_duration("-PT90S"), Ok((-90, TimeUnit::Second)));
+
+        assert!(matches!(
+            parse_duration("P1M2D"),
+            Err(Iso8601Error::MixedDuration(_))
+        ));
+        for bad in [
+            "P", "PT", "3H", "PT3", "P3H", "PT3D", "PT0.5H", "P1M1M1", "PT1H1H", "PT30M1H",
+            "P1D1W", "P2D3D", "P1DT", "PT1S2M",
+        ] {
+            assert!(parse_duration(bad).is_err(), "{bad}");
+        }
+
+        for unit in [
+            TimeUnit::Second,
+            TimeUnit::Minute,
+            TimeUnit::Hour,
+            TimeUnit::Day,
+            TimeUnit::Week,
+            TimeUnit::Month,
+            TimeUnit::Year,
+        ] {
+            assert_eq!(parse_duration(&format_duration(-7, unit)), Ok((-7, unit)));
+        }
+        assert_eq!(format_duration(3, TimeUnit::Hour), "PT3H");
+        assert_eq!(format_duration(-1, TimeUnit::Day), "-P1D");
+    }
+
+    #[test]
+    fn test_time_payload_iso() {
+        let time = TimePayload::from_iso8601("2026-10-16T10:00:00+05:30")
+            .unwrap()
+            .with_iso8601_delta("PT3H")
+            .unwrap();
+        assert_eq!(time.to_iso8601(), "2026-10-16T13:00:00+05:30");
+        assert_eq!(time.delta_iso8601(), "PT3H");
+
+        let zoned = TimePayload::from_iso8601("2024-03-09T12:00:00-05:00")
+            .unwrap()
+            .with_zone(ZoneId::NEW_YORK)
+            .with_iso8601_delta("P1D")
+            .unwrap();
+        assert_eq!(zoned.to_iso8601(), "2024-03-10T12:00:00-04:00");
+
+        assert!(TimePayload::at(0)
+            .with_iso8601_delta("PT9999999999S")
+            .is_err());
+    }
+}
@@ -90,19 +90,68 @@
 //! opcode prediction tasks.
 
 pub mod action;
+pub mod calc;
+pub mod calendar;
+pub mod clock;
+pub mod custom;
+pub mod describe;
+pub mod exact;
+pub mod expr;
 pub mod extended;
+pub mod graph;
 pub mod instruction;
+pub mod iso8601;
+pub mod limits;
+pub mod locale;
 pub mod modifier;
+pub mod program;
+pub mod rag;
+pub mod resolver;
+pub mod schedule;
+pub mod scheduler;
 pub mod subject;
+pub mod template;
+pub mod trace;
+pub mod trm;
+pub mod tz;
+pub mod units;
+pub mod vm;
 
 // Re-export main types
-pub use action::Action;
+pub use action::{Action, ActionCategory};
+pub use calc::CalcError;
+pub use calendar::{DateTime, TimeArithmetic, Weekday};
+pub use clock::{Clock, ClockError, FixedClock, ManualClock, SystemClock};
+pub use custom::{CustomData, CustomPayload, PayloadRegistry, RegistryError};
+pub use describe::{ActionPhrase, Vocabulary};
+pub use exact::{ExactCalcPayload, Number, OperandKind};
+pub use expr::{ExprError, ExprPayload, ExprToken};
 pub use extended::{
-    CalcPayload, ExtendedInstruction, Op, Payload, PayloadType, TimePayload, TimeUnit,
+    CalcPayload, ExtendedInstruction, Op, Payload, PayloadType, StringRef, TimePayload, TimeUnit,
+    TimerPayload,
 };
 pub use instruction::{Instruction, InstructionBuilder, InstructionError, INSTRUCTION_SIZE};
-pub use modifier::{Accuracy, Format, Modifier, Tone, Urgency, Voice, Warmth};
+pub use iso8601::Iso8601Error;
+pub use limits::{LimitExceeded, Limits};
+pub use locale::{Locale, LocaleError};
+pub use modifier::{
+    Accuracy, Format, Modifier, ModifierField, ModifierValue, Tone, Urgency, Voice, Warmth,
+};
+pub use program::{ForkGroup, Node, NodeKind, Program, ProgramError, Segment};
+pub use rag::{FilterOp, MetadataFilter, RagAddress, RagQueryPayload};
+pub use resolver::{DirectoryResolver, Document, MemoryResolver, RagResolver, ResolveError};
+pub use schedule::{Frequency, Recurrence, SchedulePayload, WeekdaySet};
+pub use scheduler::{BranchResult, BranchRunner, MergePolicy, Merged, ScheduleError, Scheduler};
 pub use subject::Subject;
+pub use template::{
+    MemoryTemplateStore, SlotBinding, SlotSource, Template, TemplateError, TemplatePayload,
+    TemplateRenderer, TemplateStore,
+};
+pub use trace::{ReplayHandler, Trace, TraceDiff, TraceEntry, TraceOutcome};
+pub use trm::{TrmContract, TrmError, TrmModel, TrmRegistry};
+pub use tz::ZoneId;
+pub use units::{ConversionPayload, Dimension, Unit};
+pub use vm::{Execution, ExitStatus, Handler, HandlerError, HandlerResult, Vm, VmError};
 
 /// Current ISA version
 pub const ISA_VERSION: &str = "0.1.0";
@@ -110,12 +159,17 @@ pub const ISA_VERSION: &str = "0.1.0";
 /// Convenience prelude for common imports
 pub mod prelude {
     pub use crate::action::Action;
+    pub use crate::custom::{CustomPayload, PayloadRegistry};
+    pub use crate::exact::{ExactCalcPayload, Number};
+    pub use crate::expr::ExprPayload;
     pub use crate::extended::{
-        CalcPayload, ExtendedInstruction, Op, Payload, PayloadType, TimePayload, TimeUnit,
+        CalcPayload, ExtendedInstruction, Op, Payload, PayloadType, StringRef, TimePayload,
+        TimeUnit, TimerPayload,
     };
     pub use crate::instruction::{Instruction, InstructionBuilder, INSTRUCTION_SIZE};
     pub use crate::modifier::{Accuracy, Format, Modifier, Tone, Urgency, Voice, Warmth};
     pub use crate::subject::Subject;
+    pub use crate::units::{ConversionPayload, Unit};
 }
 
 #[cfg(test)]
@@ -0,0 +1,150 @@
+//! Execution budgets for the reference VM
+//!
+//! [`Limits`] bound what a program may consume, so a misbehaving model (say,
+//! one whose `CHAIN` points back at itself) fails with a typed
+//! [`LimitExceeded`] error instead of running forever:
+//!
+//! ```rust
+//! use frame_isa::limits::{LimitExceeded, Limits};
+//! use frame_isa::vm::{Handler, HandlerError, Vm, VmError};
+//! use frame_isa::{Action, ExtendedInstruction, Instruction, Subject};
+//!
+//! /// Model 1 chains to itself
+//! struct Loop;
+//!
+//! impl Handler for Loop {
+//!     type Output = ();
+//!
+//!     fn load_chain(
+//!         &mut self,
+//!         model_id: u8,
+//!         _: &ExtendedInstruction,
+//!     ) -> Result<Option<Vec<ExtendedInstruction>>, HandlerError> {
+//!         let hop = Instruction::simple(Action::CHAIN, Subject::trm_ref(model_id));
+//!         Ok(Some(vec![ExtendedInstruction::new(hop)]))
+//!     }
+//! }
+//!
+//! let start = Instruction::simple(Action::CHAIN, Subject::trm_ref(1));
+//! let mut vm = Vm::new(Loop).with_limits(Limits::default().with_max_chain_depth(8));
+//! assert!(matches!(
+//!     vm.run_instructions(&[start]),
+//!     Err(VmError::LimitExceeded { limit: LimitExceeded::ChainDepth { max: 8 }, .. })
+//! ));
+//! ```
+//!
+//! Every limit is optional; `None` means unbounded. The defaults bound
+//! instruction count, chain depth and fork fan-out, and leave the deadline
+//! and payload size open.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// Execution budgets enforced by the VM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Limits {
+    /// Instructions executed, counting those in chained programs
+    pub max_instructions: Option<usize>,
+    /// Nesting of chained programs; a `CHAIN` to a `trm_ref` at this depth is
+    /// refused before the handler loads anything
+    pub max_chain_depth: Option<usize>,
+    /// `FORK` instructions open at once, counting those in chained programs
+    /// (reset by `MERGE`)
+    pub max_fork_fan_out: Option<usize>,
+    /// Wall-clock time since the run started
+    pub deadline: Option<Duration>,
+    /// Encoded payload size of any instruction, in bytes
+    pub max_payload_bytes: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_instructions: Some(Self::DEFAULT_MAX_INSTRUCTIONS),
+            max_chain_depth: Some(Self::DEFAULT_MAX_CHAIN_DEPTH),
+            max_fork_fan_out: Some(Self::DEFAULT_MAX_FORK_FAN_OUT),
+            deadline: None,
+            max_payload_bytes: None,
+        }
+    }
+}
+
+impl Limits {
+    /// Default instruction budget
+    pub const DEFAULT_MAX_INSTRUCTIONS: usize = 100_000;
+    /// Default chain depth
+    pub const DEFAULT_MAX_CHAIN_DEPTH: usize = 32;
+    /// Default fork fan-out
+    pub const DEFAULT_MAX_FORK_FAN_OUT: usize = 64;
+
+    /// No limits at all
+    pub const fn unlimited() -> Self {
+        Self {
+            max_instructions: None,
+            max_chain_depth: None,
+            max_fork_fan_out: None,
+            deadline: None,
+            max_payload_bytes: None,
+        }
+    }
+
+    /// Bound the number of executed instructions
+    pub fn with_max_instructions(mut self, max: usize) -> Self {
+        self.max_instructions = Some(max);
+        self
+    }
+
+    /// Bound the nesting of chained programs
+    pub fn with_max_chain_depth(mut self, max: usize) -> Self {
+        self.max_chain_depth = Some(max);
+        self
+    }
+
+    /// Bound the number of open `FORK` branches
+    pub fn with_max_fork_fan_out(mut self, max: usize) -> Self {
+        self.max_fork_fan_out = Some(max);
+        self
+    }
+
+    /// Stop runs that take longer than `deadline`
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Bound encoded payload sizes
+    pub fn with_max_payload_bytes(mut self, max: usize) -> Self {
+        self.max_payload_bytes = Some(max);
+        self
+    }
+}
+
+/// Which budget a run exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LimitExceeded {
+    /// More than `max` instructions
+    Instructions { max: usize },
+    /// Chained programs nested deeper than `max`
+    ChainDepth { max: usize },
+    /// More than `max` open `FORK` branches
+    ForkFanOut { max: usize },
+    /// Ran past the deadline
+    Deadline { deadline: Duration },
+    /// A payload of `actual` bytes, over `max`
+    PayloadSize { max: usize, actual: usize },
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitExceeded::Instructions { max } => write!(f, "more than {} instructions", max),
+            LimitExceeded::ChainDepth { max } => write!(f, "chain depth over {}", max),
+            LimitExceeded::ForkFanOut { max } => write!(f, "more than {} open forks", max),
+            LimitExceeded::Deadline { deadline } => write!(f, "deadline of {:?} passed", deadline),
+            LimitExceeded::PayloadSize { max, actual } => {
+                write!(f, "payload of {} bytes (max {})", actual, max)
+            }
+        }
+    }
+}
@@ -0,0 +1,438 @@
+//! Localized mnemonic and modifier names
+//!
+//! Operator consoles show [`Action::name`], [`Subject::name`], modifier field
+//! values and [`TimeUnit::name`] strings. A [`Locale`] holds per-locale tables
+//! for those names. Anything without an entry falls back to the built-in
+//! English name, so a partial translation is always usable.
+//!
+//! Locales load from a small file format, one section per table:
+//!
+//! ```text
+//! # es.locale
+//! locale = es
+//!
+//! [actions]
+//! GREET = SALUDAR
+//!
+//! [subjects]
+//! USER = USUARIO
+//! RAG_REF = REF_RAG
+//!
+//! [modifiers]
+//! voice = voz
+//! voice.Formal = Formal
+//!
+//! [time_units]
+//! minute = minuto
+//! ```
+//!
+//! Keys are the English names, matched case-insensitively. In `[subjects]`,
+//! `RAG_REF`, `TRM_REF` and `UNKNOWN` name whole subject ranges; in
+//! `[actions]`, `UNKNOWN` names every unassigned action code.
+//!
+//! ```rust
+//! use frame_isa::locale::Locale;
+//! use frame_isa::{Action, Modifier, Subject, TimeUnit};
+//!
+//! let spanish = Locale::from_config(
+//!     "locale = es\n[actions]\nGREET = SALUDAR\n[modifiers]\nvoice = voz\naccuracy = precisión\naccuracy.High = Alta\n",
+//! )
+//! .unwrap();
+//! assert_eq!(spanish.tag(), "es");
+//! assert_eq!(spanish.action_name(Action::GREET), "SALUDAR");
+//! assert_eq!(spanish.action_name(Action::HALT), "HALT");
+//! assert_eq!(spanish.subject_name(Subject::USER), "USER");
+//! assert_eq!(spanish.time_unit_name(TimeUnit::Day), "day");
+//! assert_eq!(
+//!     spanish.modifier_summary(Modifier::professional()),
+//!     "voz=Formal, precisión=Alta"
+//! );
+//! ```
+
+use crate::modifier::{ModifierField, ModifierValue};
+use crate::{Action, Modifier, Subject, TimeUnit};
+use std::collections::HashMap;
+use std::path::Path;
+use thiserror::Error;
+
+/// Errors loading a locale file
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum LocaleError {
+    #[error("Locale file line {line}: {message}")]
+    Config { line: usize, message: String },
+
+    #[error("Cannot read locale file: {0}")]
+    Io(String),
+}
+
+/// Name tables for one locale
+///
+/// Action and subject entries are keyed by their English
+/// [`name`](Action::name), so an entry for a RAG or TRM reference names its
+/// whole range.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Locale {
+    tag: String,
+    actions: HashMap<&'static str, String>,
+    subjects: HashMap<&'static str, String>,
+    fields: HashMap<ModifierField, String>,
+    values: HashMap<ModifierValue, String>,
+    time_units: HashMap<TimeUnit, String>,
+}
+
+/// Section of a locale file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Actions,
+    Subjects,
+    Modifiers,
+    TimeUnits,
+}
+
+impl Locale {
+    /// Create an empty locale; every lookup falls back to English
+    pub fn new(tag: impl Into<String>) -> Self {
+        Self {
+            tag: tag.into(),
+            ..Self::default()
+        }
+    }
+
+    /// The built-in English names
+    pub fn english() -> Self {
+        Self::new("en")
+    }
+
+    /// Locale tag, e.g. `es` or `de-AT`
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Add or replace an action name
+    pub fn with_action(mut self, action: Action, name: impl Into<String>) -> Self {
+        self.actions.insert(action.name(), name.into());
+        self
+    }
+
+    /// Add or replace a subject name
+    pub fn with_subject(mut self, subject: Subject, name: impl Into<String>) -> Self {
+        self.subjects.insert(subject.name(), name.into());
+        self
+    }
+
+    /// Add or replace a modifier field name
+    pub fn with_modifier_field(mut self, field: ModifierField, name: impl Into<String>) -> Self {
+        self.fields.insert(field, name.into());
+        self
+    }
+
+    /// Add or replace a modifier value name
+    pub fn with_modifier_value(
+        mut self,
+        value: impl Into<ModifierValue>,
+        name: impl Into<String>,
+    ) -> Self {
+        self.values.insert(value.into(), name.into());
+        self
+    }
+
+    /// Add or replace a time unit name
+    pub fn with_time_unit(mut self, unit: TimeUnit, name: impl Into<String>) -> Self {
+        self.time_units.insert(unit, name.into());
+        self
+    }
+
+    /// Localized [`Action::name`]
+    pub fn action_name(&self, action: Action) -> &str {
+        let name = action.name();
+        self.actions.get(name).map_or(name, String::as_str)
+    }
+
+    /// Localized [`Subject::name`]
+    pub fn subject_name(&self, subject: Subject) -> &str {
+        let name = subject.name();
+        self.subjects.get(name).map_or(name, String::as_str)
+    }
+
+    /// Localized [`ModifierField::name`]
+    pub fn modifier_field_name(&self, field: ModifierField) -> &str {
+        self.fields.get(&field).map_or(field.name(), String::as_str)
+    }
+
+    /// Localized [`ModifierValue::name`]
+    pub fn modifier_value_name(&self, value: impl Into<ModifierValue>) -> &str {
+        let value = value.into();
+        self.values.get(&value).map_or(value.name(), String::as_str)
+    }
+
+    /// Localized [`TimeUnit::name`]
+    pub fn time_unit_name(&self, unit: TimeUnit) -> &str {
+        self.time_units
+            .get(&unit)
+            .map_or(unit.name(), String::as_str)
+    }
+
+    /// Localized [`Modifier::summary`]
+    pub fn modifier_summary(&self, modifier: Modifier) -> String {
+        modifier
+            .values()
+            .into_iter()
+            .zip(Modifier::default().values())
+            .filter(|(value, default)| value != default)
+            .map(|(value, _)| {
+                format!(
+                    "{}={}",
+                    self.modifier_field_name(value.field()),
+                    self.modifier_value_name(value)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Parse a locale file (see the [module docs](self))
+    pub fn from_config(text: &str) -> Result<Self, LocaleError> {
+        let mut tag = None;
+        let mut locale = Self::default();
+        let mut section = None;
+        let mut last_line = 0;
+
+        for (index, raw) in text.lines().enumerate() {
+            let line_no = index + 1;
+            last_line = line_no;
+            let err = |message: String| LocaleError::Config {
+                line: line_no,
+                message,
+            };
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                if tag.is_none() {
+                    return Err(err(
+                        "expected `locale = <tag>` before the first section".to_string()
+                    ));
+                }
+                section = Some(match header.trim() {
+                    "actions" => Section::Actions,
+                    "subjects" => Section::Subjects,
+                    "modifiers" => Section::Modifiers,
+                    "time_units" => Section::TimeUnits,
+                    other => return Err(err(format!("unknown section [{}]", other))),
+                });
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| err("expected `key = value`".to_string()))?;
+            let (key, value) = (key.trim(), value.trim());
+            if value.is_empty() {
+                return Err(err(format!("empty name for `{}`", key)));
+            }
+            let Some(section) = section else {
+                if key != "locale" {
+                    return Err(err(format!("`{}` outside a section", key)));
+                }
+                if tag.replace(value.to_string()).is_some() {
+                    return Err(err("duplicate `locale`".to_string()));
+                }
+                continue;
+            };
+
+            let name = value.to_string();
+            let replaced = match section {
+                Section::Actions => {
+                    let action =
+                        action_key(key).ok_or_else(|| err(format!("unknown action {:?}", key)))?;
+                    locale.actions.insert(action, name)
+                }
+                Section::Subjects => {
+                    let subject = subject_key(key)
+                        .ok_or_else(|| err(format!("unknown subject {:?}", key)))?;
+                    locale.subjects.insert(subject, name)
+                }
+                Section::Modifiers => match key.split_once('.') {
+                    Some((field, value)) => {
+                        let value = ModifierField::from_name(field.trim())
+                            .and_then(|field| field.value(value.trim()))
+                            .ok_or_else(|| err(format!("unknown modifier value {:?}", key)))?;
+                        locale.values.insert(value, name)
+                    }
+                    None => {
+                        let field = ModifierField::from_name(key)
+                            .ok_or_else(|| err(format!("unknown modifier field {:?}", key)))?;
+                        locale.fields.insert(field, name)
+                    }
+                },
+                Section::TimeUnits => {
+                    let unit = TimeUnit::from_name(key)
+                        .ok_or_else(|| err(format!("unknown time unit {:?}", key)))?;
+                    locale.time_units.insert(unit, name)
+                }
+            };
+            if replaced.is_some() {
+                return Err(err(format!("duplicate entry `{}`", key)));
+            }
+        }
+
+        locale.tag = tag.ok_or_else(|| LocaleError::Config {
+            line: last_line.max(1),
+            message: "missing `locale = <tag>`".to_string(),
+        })?;
+        Ok(locale)
+    }
+
+    /// Load a locale from a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LocaleError> {
+        let text = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| LocaleError::Io(format!("{}: {}", path.as_ref().display(), e)))?;
+        Self::from_config(&text)
+    }
+}
+
+/// English action name for a locale file key: a named action or `UNKNOWN`
+fn action_key(key: &str) -> Option<&'static str> {
+    match Action::from_name(key) {
+        Some(action) => Some(action.name()),
+        None => ["UNKNOWN"]
+            .into_iter()
+            .find(|name| name.eq_ignore_ascii_case(key)),
+    }
+}
+
+/// English subject name for a locale file key: a named subject or a range name
+fn subject_key(key: &str) -> Option<&'static str> {
+    match Subject::from_name(key) {
+        Some(subject) => Some(subject.name()),
+        None => ["RAG_REF", "TRM_REF", "UNKNOWN"]
+            .into_iter()
+            .find(|name| name.eq_ignore_ascii_case(key)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modifier::{Accuracy, Format, Voice, Warmth};
+
+    const GERMAN: &str = "\
+# Operator console, German
+locale = de
+
+[actions]
+GREET = BEGRÜSSEN
+set_timer = TIMER_STELLEN
+UNKNOWN = UNBEKANNT
+
+[subjects]
+USER = BENUTZER
+RAG_REF = RAG_VERWEIS
+
+[modifiers]
+voice = Stimme
+warmth = Wärme
+voice.Formal = Förmlich
+warmth.VeryWarm = Sehr warm
+
+[time_units]
+minute = Minute
+week = Woche
+";
+
+    #[test]
+    fn test_from_config() {
+        let german = Locale::from_config(GERMAN).unwrap();
+        assert_eq!(german.tag(), "de");
+        assert_eq!(german.action_name(Action::GREET), "BEGRÜSSEN");
+        assert_eq!(german.action_name(Action::SET_TIMER), "TIMER_STELLEN");
+        assert_eq!(german.action_name(Action::from_u16(0x0110)), "UNBEKANNT");
+        assert_eq!(german.subject_name(Subject::USER), "BENUTZER");
+        assert_eq!(german.subject_name(Subject::rag_ref(42)), "RAG_VERWEIS");
+        assert_eq!(german.modifier_field_name(ModifierField::Voice), "Stimme");
+        assert_eq!(german.modifier_value_name(Voice::Formal), "Förmlich");
+        assert_eq!(german.time_unit_name(TimeUnit::Week), "Woche");
+
+        let modifier = Modifier::default()
+            .with_voice(Voice::Formal)
+            .with_warmth(Warmth::VeryWarm)
+            .with_format(Format::Bulleted);
+        assert_eq!(
+            german.modifier_summary(modifier),
+            "Stimme=Förmlich, Wärme=Sehr warm, format=Bulleted"
+        );
+    }
+
+    #[test]
+    fn test_english_fallback() {
+        let english = Locale::english();
+        for action in Action::ALL {
+            assert_eq!(english.action_name(action), action.name());
+        }
+        for subject in Subject::ALL {
+            assert_eq!(english.subject_name(subject), subject.name());
+        }
+        for unit in TimeUnit::ALL {
+            assert_eq!(english.time_unit_name(unit), unit.name());
+        }
+        for modifier in [
+            Modifier::default(),
+            Modifier::crisis(),
+            Modifier::friendly(),
+        ] {
+            assert_eq!(english.modifier_summary(modifier), modifier.summary());
+        }
+
+        let spanish = Locale::new("es")
+            .with_action(Action::GREET, "SALUDAR")
+            .with_subject(Subject::trm_ref(0), "REF_TRM")
+            .with_modifier_value(Accuracy::High, "Alta")
+            .with_time_unit(TimeUnit::Hour, "hora");
+        assert_eq!(spanish.action_name(Action::GREET), "SALUDAR");
+        assert_eq!(spanish.action_name(Action::THANK), "THANK");
+        assert_eq!(spanish.subject_name(Subject::trm_ref(7)), "REF_TRM");
+        assert_eq!(spanish.subject_name(Subject::rag_ref(7)), "RAG_REF");
+        assert_eq!(spanish.time_unit_name(TimeUnit::Hour), "hora");
+        assert_eq!(spanish.time_unit_name(TimeUnit::Year), "year");
+        assert_eq!(
+            spanish.modifier_summary(Modifier::professional()),
+            "voice=Formal, accuracy=Alta"
+        );
+    }
+
+    #[test]
+    fn test_config_errors() {
+        let line = |text: &str| match Locale::from_config(text) {
+            Err(LocaleError::Config { line, .. }) => line,
+            other => panic!("expected config error, got {:?}", other),
+        };
+        assert_eq!(line(""), 1);
+        assert_eq!(line("# no tag\n\n[actions]"), 3);
+        assert_eq!(line("locale = es\nlocale = de"), 2);
+        assert_eq!(line("locale = es\nGREET = SALUDAR"), 2);
+        assert_eq!(line("locale = es\n[verbs]"), 2);
+        assert_eq!(line("locale = es\n[actions]\nDANCE = BAILAR"), 3);
+        assert_eq!(line("locale = es\n[actions]\nGREET ="), 3);
+        assert_eq!(line("locale = es\n[actions]\nGREET = A\ngreet = B"), 4);
+        assert_eq!(line("locale = es\n[subjects]\nWEATHER"), 3);
+        assert_eq!(line("locale = es\n[modifiers]\ncolour = color"), 3);
+        assert_eq!(line("locale = es\n[modifiers]\nvoice.Warm = cálida"), 3);
+        assert_eq!(line("locale = es\n[time_units]\nfortnight = quincena"), 3);
+
+        assert!(matches!(
+            Locale::load("/nonexistent/es.locale"),
+            Err(LocaleError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_load() {
+        let path = std::env::temp_dir().join(format!("frame-isa-{}.locale", std::process::id()));
+        std::fs::write(&path, GERMAN).unwrap();
+        let loaded = Locale::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), Locale::from_config(GERMAN).unwrap());
+    }
+}
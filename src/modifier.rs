@@ -247,6 +247,31 @@ impl Modifier {
             .with_warmth(Warmth::Warm)
             .with_urgency(Urgency::Normal)
     }
+
+    /// All six field values, in [`ModifierField::ALL`] order
+    pub fn values(&self) -> [ModifierValue; 6] {
+        [
+            ModifierValue::Voice(self.voice()),
+            ModifierValue::Tone(self.tone()),
+            ModifierValue::Warmth(self.warmth()),
+            ModifierValue::Format(self.format()),
+            ModifierValue::Accuracy(self.accuracy()),
+            ModifierValue::Urgency(self.urgency()),
+        ]
+    }
+
+    /// Summarize the fields that differ from the default, e.g. `voice=Formal, accuracy=High`
+    ///
+    /// Returns an empty string for the default modifier.
+    pub fn summary(&self) -> String {
+        self.values()
+            .into_iter()
+            .zip(Self::default().values())
+            .filter(|(value, default)| value != default)
+            .map(|(value, _)| format!("{}={}", value.field().name(), value.name()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 }
 
 impl Default for Modifier {
@@ -297,6 +322,21 @@ pub enum Voice {
     Technical,
 }
 
+impl Voice {
+    /// All values, in bit order
+    pub const ALL: [Self; 4] = [Self::Neutral, Self::Formal, Self::Casual, Self::Technical];
+
+    /// Display name, e.g. `Formal`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Neutral => "Neutral",
+            Self::Formal => "Formal",
+            Self::Casual => "Casual",
+            Self::Technical => "Technical",
+        }
+    }
+}
+
 /// Emotional tone
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Tone {
@@ -310,6 +350,26 @@ pub enum Tone {
     Cautious,
 }
 
+impl Tone {
+    /// All values, in bit order
+    pub const ALL: [Self; 4] = [
+        Self::Neutral,
+        Self::Positive,
+        Self::Empathetic,
+        Self::Cautious,
+    ];
+
+    /// Display name, e.g. `Positive`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Neutral => "Neutral",
+            Self::Positive => "Positive",
+            Self::Empathetic => "Empathetic",
+            Self::Cautious => "Cautious",
+        }
+    }
+}
+
 /// Interpersonal warmth level
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Warmth {
@@ -323,6 +383,21 @@ pub enum Warmth {
     VeryWarm,
 }
 
+impl Warmth {
+    /// All values, in bit order
+    pub const ALL: [Self; 4] = [Self::Cold, Self::Neutral, Self::Warm, Self::VeryWarm];
+
+    /// Display name, e.g. `Neutral`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Cold => "Cold",
+            Self::Neutral => "Neutral",
+            Self::Warm => "Warm",
+            Self::VeryWarm => "VeryWarm",
+        }
+    }
+}
+
 /// Output format
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Format {
@@ -336,6 +411,26 @@ pub enum Format {
     Structured,
 }
 
+impl Format {
+    /// All values, in bit order
+    pub const ALL: [Self; 4] = [
+        Self::Prose,
+        Self::Bulleted,
+        Self::Numbered,
+        Self::Structured,
+    ];
+
+    /// Display name, e.g. `Bulleted`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Prose => "Prose",
+            Self::Bulleted => "Bulleted",
+            Self::Numbered => "Numbered",
+            Self::Structured => "Structured",
+        }
+    }
+}
+
 /// Confidence/accuracy level
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Accuracy {
@@ -349,6 +444,21 @@ pub enum Accuracy {
     Verified,
 }
 
+impl Accuracy {
+    /// All values, in bit order
+    pub const ALL: [Self; 4] = [Self::Low, Self::Medium, Self::High, Self::Verified];
+
+    /// Display name, e.g. `Medium`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Low => "Low",
+            Self::Medium => "Medium",
+            Self::High => "High",
+            Self::Verified => "Verified",
+        }
+    }
+}
+
 /// Urgency/priority level
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Urgency {
@@ -362,6 +472,155 @@ pub enum Urgency {
     Critical,
 }
 
+impl Urgency {
+    /// All values, in bit order
+    pub const ALL: [Self; 4] = [Self::Low, Self::Normal, Self::High, Self::Critical];
+
+    /// Display name, e.g. `Normal`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Low => "Low",
+            Self::Normal => "Normal",
+            Self::High => "High",
+            Self::Critical => "Critical",
+        }
+    }
+}
+
+/// A modifier field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModifierField {
+    Voice,
+    Tone,
+    Warmth,
+    Format,
+    Accuracy,
+    Urgency,
+}
+
+impl ModifierField {
+    /// All fields, from high bits to low
+    pub const ALL: [Self; 6] = [
+        Self::Voice,
+        Self::Tone,
+        Self::Warmth,
+        Self::Format,
+        Self::Accuracy,
+        Self::Urgency,
+    ];
+
+    /// Field name, e.g. `voice`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Voice => "voice",
+            Self::Tone => "tone",
+            Self::Warmth => "warmth",
+            Self::Format => "format",
+            Self::Accuracy => "accuracy",
+            Self::Urgency => "urgency",
+        }
+    }
+
+    /// Look up a field by its [`name`](Self::name) (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|field| field.name().eq_ignore_ascii_case(name))
+    }
+
+    /// All values of this field
+    pub fn values(self) -> [ModifierValue; 4] {
+        match self {
+            Self::Voice => Voice::ALL.map(ModifierValue::Voice),
+            Self::Tone => Tone::ALL.map(ModifierValue::Tone),
+            Self::Warmth => Warmth::ALL.map(ModifierValue::Warmth),
+            Self::Format => Format::ALL.map(ModifierValue::Format),
+            Self::Accuracy => Accuracy::ALL.map(ModifierValue::Accuracy),
+            Self::Urgency => Urgency::ALL.map(ModifierValue::Urgency),
+        }
+    }
+
+    /// Look up a value of this field by name (case-insensitive), e.g. `VeryWarm`
+    pub fn value(self, name: &str) -> Option<ModifierValue> {
+        self.values()
+            .into_iter()
+            .find(|value| value.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// The value of any one modifier field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModifierValue {
+    Voice(Voice),
+    Tone(Tone),
+    Warmth(Warmth),
+    Format(Format),
+    Accuracy(Accuracy),
+    Urgency(Urgency),
+}
+
+impl ModifierValue {
+    /// The field this value belongs to
+    pub fn field(self) -> ModifierField {
+        match self {
+            Self::Voice(_) => ModifierField::Voice,
+            Self::Tone(_) => ModifierField::Tone,
+            Self::Warmth(_) => ModifierField::Warmth,
+            Self::Format(_) => ModifierField::Format,
+            Self::Accuracy(_) => ModifierField::Accuracy,
+            Self::Urgency(_) => ModifierField::Urgency,
+        }
+    }
+
+    /// Display name of the value, e.g. `Formal`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Voice(v) => v.name(),
+            Self::Tone(v) => v.name(),
+            Self::Warmth(v) => v.name(),
+            Self::Format(v) => v.name(),
+            Self::Accuracy(v) => v.name(),
+            Self::Urgency(v) => v.name(),
+        }
+    }
+}
+
+impl From<Voice> for ModifierValue {
+    fn from(value: Voice) -> Self {
+        Self::Voice(value)
+    }
+}
+
+impl From<Tone> for ModifierValue {
+    fn from(value: Tone) -> Self {
+        Self::Tone(value)
+    }
+}
+
+impl From<Warmth> for ModifierValue {
+    fn from(value: Warmth) -> Self {
+        Self::Warmth(value)
+    }
+}
+
+impl From<Format> for ModifierValue {
+    fn from(value: Format) -> Self {
+        Self::Format(value)
+    }
+}
+
+impl From<Accuracy> for ModifierValue {
+    fn from(value: Accuracy) -> Self {
+        Self::Accuracy(value)
+    }
+}
+
+impl From<Urgency> for ModifierValue {
+    fn from(value: Urgency) -> Self {
+        Self::Urgency(value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -420,6 +679,38 @@ mod tests {
         assert_eq!(friendly.warmth(), Warmth::Warm);
     }
 
+    #[test]
+    fn test_summary() {
+        assert_eq!(Modifier::default().summary(), "");
+        assert_eq!(
+            Modifier::professional().summary(),
+            "voice=Formal, accuracy=High"
+        );
+        assert_eq!(
+            Modifier::default().with_format(Format::Bulleted).summary(),
+            "format=Bulleted"
+        );
+    }
+
+    #[test]
+    fn test_field_values() {
+        let values = Modifier::crisis().values();
+        for (field, value) in ModifierField::ALL.into_iter().zip(values) {
+            assert_eq!(value.field(), field);
+            assert_eq!(field.value(value.name()), Some(value));
+        }
+        assert_eq!(
+            ModifierField::from_name("Warmth"),
+            Some(ModifierField::Warmth)
+        );
+        assert_eq!(
+            ModifierField::Warmth.value("verywarm"),
+            Some(ModifierValue::Warmth(Warmth::VeryWarm))
+        );
+        assert_eq!(ModifierField::Voice.value("Warm"), None);
+        assert_eq!(ModifierValue::from(Urgency::Critical).name(), "Critical");
+    }
+
     #[test]
     fn test_serialization() {
         let modifier = Modifier::crisis();
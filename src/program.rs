@@ -0,0 +1,496 @@
+//! Programs with control flow
+//!
+//! A [`Program`] is a validated instruction sequence plus the control-flow
+//! graph implied by its chain actions:
+//!
+//! - `CHAIN trm(n)` hands control to model `n` and continues afterwards
+//! - `FORK trm(n)` starts model `n` as a parallel branch
+//! - `MERGE` joins every branch forked since the previous `MERGE`
+//!
+//! Forks do not nest within one program (a branch forks inside its own
+//! model's program), so a run of `FORK`s and the `MERGE` closing it form one
+//! [`ForkGroup`]. Instructions between them run on the main line while the
+//! branches are in flight.
+//!
+//! ```rust
+//! use frame_isa::program::{NodeKind, Program, Segment};
+//! use frame_isa::{Action, Instruction, Subject};
+//!
+//! let program = Program::from_instructions(&[
+//!     Instruction::simple(Action::GREET, Subject::USER),
+//!     Instruction::simple(Action::FORK, Subject::trm_ref(1)),
+//!     Instruction::simple(Action::FORK, Subject::trm_ref(2)),
+//!     Instruction::simple(Action::MERGE, Subject::NULL),
+//!     Instruction::simple(Action::CHAIN, Subject::trm_ref(3)),
+//! ])
+//! .unwrap();
+//!
+//! let group = &program.fork_groups()[0];
+//! assert_eq!(group.branches, vec![1, 2]);
+//! assert_eq!(program.node(3).unwrap().kind, NodeKind::Merge { forks: vec![1, 2] });
+//! assert!(matches!(program.segments()[1], Segment::Parallel(_)));
+//! ```
+//!
+//! Whether the referenced models exist is a question for a
+//! [`TrmRegistry`]; see [`Program::link`].
+
+use crate::trm::{TrmError, TrmRegistry};
+use crate::{Action, ExtendedInstruction, Instruction, Subject};
+use thiserror::Error;
+
+/// Structural errors in a program
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ProgramError {
+    #[error("Instruction {index}: {message}")]
+    Invalid { index: usize, message: String },
+
+    #[error("Instruction {index}: {action} must target a TRM reference, not {subject}")]
+    InvalidTarget {
+        index: usize,
+        action: Action,
+        subject: Subject,
+    },
+
+    #[error("FORK at instruction {index} is never merged")]
+    UnmatchedFork { index: usize },
+
+    #[error("MERGE at instruction {index} has no open FORK")]
+    UnmatchedMerge { index: usize },
+
+    #[error("Instruction {index} uses the result of instruction {target}, which has not run yet")]
+    ForwardResult { index: usize, target: usize },
+}
+
+/// Role of an instruction in the control-flow graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+    /// Ordinary instruction, runs and falls through
+    Step,
+    /// Hands control to a model and continues afterwards
+    Chain { model_id: u8 },
+    /// Starts a model as a parallel branch, joined by the `MERGE` at `merge`
+    Fork { model_id: u8, merge: usize },
+    /// Joins the branches started by the `FORK`s at `forks`
+    Merge { forks: Vec<usize> },
+    /// `HALT` or `ERROR`: the run ends here
+    Exit,
+}
+
+/// One instruction in the control-flow graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    /// Position in the program
+    pub index: usize,
+    /// Role in the control flow
+    pub kind: NodeKind,
+}
+
+/// Forks joined by one `MERGE`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForkGroup {
+    /// Positions of the `FORK` instructions
+    pub forks: Vec<usize>,
+    /// Model started by each fork, in fork order
+    pub branches: Vec<u8>,
+    /// Position of the `MERGE`
+    pub merge: usize,
+}
+
+impl ForkGroup {
+    /// Positions of main-line instructions between the first fork and the merge
+    pub fn interleaved(&self) -> impl Iterator<Item = usize> + '_ {
+        (self.forks[0]..self.merge).filter(|index| !self.forks.contains(index))
+    }
+}
+
+/// Unit of scheduling: one instruction, or a fork group run in parallel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment<'a> {
+    /// Instruction at this position, run on the main line
+    Sequential(usize),
+    /// Branches run in parallel, then merged
+    Parallel(&'a ForkGroup),
+}
+
+/// Validated program with its control-flow graph
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    instructions: Vec<ExtendedInstruction>,
+    nodes: Vec<Node>,
+    groups: Vec<ForkGroup>,
+}
+
+impl Program {
+    /// Validate a program and build its graph
+    ///
+    /// Every instruction must be valid on its own, `CHAIN` and `FORK` must
+    /// target a TRM reference, every `FORK` must be closed by a `MERGE`, and
+    /// template result bindings must refer to earlier instructions.
+    pub fn new(instructions: Vec<ExtendedInstruction>) -> Result<Self, ProgramError> {
+        let mut nodes = Vec::with_capacity(instructions.len());
+        let mut groups = Vec::new();
+        let mut open: Vec<(usize, u8)> = Vec::new();
+
+        for (index, instr) in instructions.iter().enumerate() {
+            instr.validate().map_err(|e| ProgramError::Invalid {
+                index,
+                message: e.to_string(),
+            })?;
+            if let Some(target) = instr
+                .as_template()
+                .and_then(|t| t.results().map(usize::from).find(|&target| target >= index))
+            {
+                return Err(ProgramError::ForwardResult { index, target });
+            }
+            let action = instr.base.action;
+            let target = || {
+                instr
+                    .base
+                    .subject
+                    .trm_model_id()
+                    .ok_or(ProgramError::InvalidTarget {
+                        index,
+                        action,
+                        subject: instr.base.subject,
+                    })
+            };
+            let kind = match action {
+                Action::CHAIN => NodeKind::Chain {
+                    model_id: target()?,
+                },
+                Action::FORK => {
+                    let model_id = target()?;
+                    open.push((index, model_id));
+                    // `merge` is filled in once the MERGE is found
+                    NodeKind::Fork { model_id, merge: 0 }
+                }
+                Action::MERGE => {
+                    if open.is_empty() {
+                        return Err(ProgramError::UnmatchedMerge { index });
+                    }
+                    let (forks, branches): (Vec<usize>, Vec<u8>) = open.drain(..).unzip();
+                    groups.push(ForkGroup {
+                        forks: forks.clone(),
+                        branches,
+                        merge: index,
+                    });
+                    NodeKind::Merge { forks }
+                }
+                Action::HALT | Action::ERROR => NodeKind::Exit,
+                _ => NodeKind::Step,
+            };
+            nodes.push(Node { index, kind });
+        }
+        if let Some(&(index, _)) = open.first() {
+            return Err(ProgramError::UnmatchedFork { index });
+        }
+
+        for group in &groups {
+            for &fork in &group.forks {
+                if let NodeKind::Fork { merge, .. } = &mut nodes[fork].kind {
+                    *merge = group.merge;
+                }
+            }
+        }
+        Ok(Self {
+            instructions,
+            nodes,
+            groups,
+        })
+    }
+
+    /// Validate a program of base instructions (no payloads)
+    pub fn from_instructions(program: &[Instruction]) -> Result<Self, ProgramError> {
+        Self::new(
+            program
+                .iter()
+                .copied()
+                .map(ExtendedInstruction::new)
+                .collect(),
+        )
+    }
+
+    /// Instructions, in order
+    pub fn instructions(&self) -> &[ExtendedInstruction] {
+        &self.instructions
+    }
+
+    /// Consume the program, returning its instructions
+    pub fn into_instructions(self) -> Vec<ExtendedInstruction> {
+        self.instructions
+    }
+
+    /// Number of instructions
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    /// Check if the program has no instructions
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    /// Graph nodes, one per instruction
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Graph node of the instruction at `index`
+    pub fn node(&self, index: usize) -> Option<&Node> {
+        self.nodes.get(index)
+    }
+
+    /// Fork groups, in program order
+    pub fn fork_groups(&self) -> &[ForkGroup] {
+        &self.groups
+    }
+
+    /// Fork group a `FORK` or `MERGE` at `index` belongs to
+    pub fn fork_group(&self, index: usize) -> Option<&ForkGroup> {
+        self.groups
+            .iter()
+            .find(|group| group.merge == index || group.forks.contains(&index))
+    }
+
+    /// `(position, model ID)` of every `CHAIN`
+    pub fn chains(&self) -> impl Iterator<Item = (usize, u8)> + '_ {
+        self.nodes.iter().filter_map(|node| match node.kind {
+            NodeKind::Chain { model_id } => Some((node.index, model_id)),
+            _ => None,
+        })
+    }
+
+    /// Every model the program chains or forks to, sorted and deduplicated
+    pub fn models(&self) -> Vec<u8> {
+        let mut models: Vec<u8> = self
+            .nodes
+            .iter()
+            .filter_map(|node| match node.kind {
+                NodeKind::Chain { model_id } | NodeKind::Fork { model_id, .. } => Some(model_id),
+                _ => None,
+            })
+            .collect();
+        models.sort_unstable();
+        models.dedup();
+        models
+    }
+
+    /// Control-flow successors of the instruction at `index`
+    ///
+    /// Control falls through to the next instruction unless `index` is an
+    /// exit or the last instruction. A `FORK` also flows to its `MERGE`.
+    pub fn successors(&self, index: usize) -> Vec<usize> {
+        let Some(node) = self.nodes.get(index) else {
+            return Vec::new();
+        };
+        let mut successors = Vec::new();
+        if node.kind != NodeKind::Exit && index + 1 < self.nodes.len() {
+            successors.push(index + 1);
+        }
+        if let NodeKind::Fork { merge, .. } = node.kind {
+            if merge != index + 1 {
+                successors.push(merge);
+            }
+        }
+        successors
+    }
+
+    /// Control-flow predecessors of the instruction at `index`
+    pub fn predecessors(&self, index: usize) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&from| self.successors(from).contains(&index))
+            .collect()
+    }
+
+    /// Positions reachable from the start, in program order
+    pub fn reachable(&self) -> Vec<usize> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut stack: Vec<usize> = (!self.nodes.is_empty()).then_some(0).into_iter().collect();
+        while let Some(index) = stack.pop() {
+            if !std::mem::replace(&mut seen[index], true) {
+                stack.extend(self.successors(index));
+            }
+        }
+        (0..seen.len()).filter(|&index| seen[index]).collect()
+    }
+
+    /// Split the program into scheduling units, in order
+    ///
+    /// Each fork group becomes one [`Segment::Parallel`] at the position of
+    /// its first fork; instructions interleaved with its branches belong to
+    /// the group.
+    pub fn segments(&self) -> Vec<Segment<'_>> {
+        let mut segments = Vec::new();
+        let mut index = 0;
+        while index < self.nodes.len() {
+            match self.groups.iter().find(|group| group.forks[0] == index) {
+                Some(group) => {
+                    segments.push(Segment::Parallel(group));
+                    index = group.merge + 1;
+                }
+                None => {
+                    segments.push(Segment::Sequential(index));
+                    index += 1;
+                }
+            }
+        }
+        segments
+    }
+
+    /// Check that every chained and forked model is registered and accepts its payload
+    pub fn link(&self, registry: &TrmRegistry) -> Result<(), TrmError> {
+        registry.link_extended(&self.instructions)
+    }
+}
+
+impl TryFrom<Vec<ExtendedInstruction>> for Program {
+    type Error = ProgramError;
+
+    fn try_from(instructions: Vec<ExtendedInstruction>) -> Result<Self, ProgramError> {
+        Self::new(instructions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extended::{TimeUnit, TimerPayload};
+    use crate::template::TemplatePayload;
+    use crate::trm::TrmModel;
+
+    fn instr(action: Action, subject: Subject) -> Instruction {
+        Instruction::simple(action, subject)
+    }
+
+    fn sample() -> Program {
+        Program::from_instructions(&[
+            instr(Action::GREET, Subject::USER),
+            instr(Action::FORK, Subject::trm_ref(1)),
+            instr(Action::RESPOND, Subject::USER),
+            instr(Action::FORK, Subject::trm_ref(2)),
+            instr(Action::MERGE, Subject::NULL),
+            instr(Action::CHAIN, Subject::trm_ref(3)),
+            instr(Action::HALT, Subject::NULL),
+            instr(Action::THANK, Subject::USER),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_graph() {
+        let program = sample();
+        assert_eq!(program.len(), 8);
+        assert_eq!(
+            program.fork_groups(),
+            &[ForkGroup {
+                forks: vec![1, 3],
+                branches: vec![1, 2],
+                merge: 4,
+            }]
+        );
+        assert_eq!(
+            program.node(1).unwrap().kind,
+            NodeKind::Fork {
+                model_id: 1,
+                merge: 4
+            }
+        );
+        assert_eq!(
+            program.node(4).unwrap().kind,
+            NodeKind::Merge { forks: vec![1, 3] }
+        );
+        assert_eq!(program.fork_group(3).unwrap().merge, 4);
+        assert!(program.fork_group(2).is_none());
+        assert_eq!(
+            program.fork_groups()[0].interleaved().collect::<Vec<_>>(),
+            vec![2]
+        );
+        assert_eq!(program.chains().collect::<Vec<_>>(), vec![(5, 3)]);
+        assert_eq!(program.models(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_traversal() {
+        let program = sample();
+        assert_eq!(program.successors(0), vec![1]);
+        assert_eq!(program.successors(1), vec![2, 4]);
+        assert_eq!(program.successors(3), vec![4]);
+        assert_eq!(program.successors(6), Vec::<usize>::new());
+        assert_eq!(program.predecessors(4), vec![1, 3]);
+        assert_eq!(program.reachable(), vec![0, 1, 2, 3, 4, 5, 6]);
+
+        assert_eq!(
+            program.segments(),
+            vec![
+                Segment::Sequential(0),
+                Segment::Parallel(&program.fork_groups()[0]),
+                Segment::Sequential(5),
+                Segment::Sequential(6),
+                Segment::Sequential(7),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_structure_errors() {
+        let fork = instr(Action::FORK, Subject::trm_ref(1));
+        let merge = instr(Action::MERGE, Subject::NULL);
+        assert_eq!(
+            Program::from_instructions(&[fork, merge, fork]),
+            Err(ProgramError::UnmatchedFork { index: 2 })
+        );
+        assert_eq!(
+            Program::from_instructions(&[fork, merge, merge]),
+            Err(ProgramError::UnmatchedMerge { index: 2 })
+        );
+        assert_eq!(
+            Program::from_instructions(&[instr(Action::CHAIN, Subject::USER)]),
+            Err(ProgramError::InvalidTarget {
+                index: 0,
+                action: Action::CHAIN,
+                subject: Subject::USER
+            })
+        );
+
+        let timer_on_greet = ExtendedInstruction::with_timer(
+            instr(Action::GREET, Subject::USER),
+            TimerPayload::new(5, TimeUnit::Minute),
+        );
+        assert!(matches!(
+            Program::new(vec![timer_on_greet]),
+            Err(ProgramError::Invalid { index: 0, .. })
+        ));
+        assert!(Program::new(Vec::new()).unwrap().segments().is_empty());
+
+        let fill = ExtendedInstruction::with_template(
+            instr(Action::TEMPLATE_FILL, Subject::USER),
+            TemplatePayload::new(1).with_result("answer", 1),
+        );
+        assert_eq!(
+            Program::new(vec![ExtendedInstruction::new(fork), fill.clone()]),
+            Err(ProgramError::ForwardResult {
+                index: 1,
+                target: 1
+            })
+        );
+        let greet = ExtendedInstruction::new(instr(Action::GREET, Subject::USER));
+        assert!(Program::new(vec![greet.clone(), greet, fill]).is_ok());
+    }
+
+    #[test]
+    fn test_link() {
+        let mut registry = TrmRegistry::new();
+        for (id, name) in [(1, "search"), (2, "summarize")] {
+            registry.register(TrmModel::new(id, name, "1.0")).unwrap();
+        }
+        let err = sample().link(&registry).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "1 unresolved TRM reference(s): model 0x03 at instruction 5"
+        );
+        registry
+            .register(TrmModel::new(3, "answer", "1.0"))
+            .unwrap();
+        assert!(sample().link(&registry).is_ok());
+    }
+}
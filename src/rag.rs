@@ -0,0 +1,599 @@
+//! RAG query payload for retrieval actions
+//!
+//! [`Subject::rag_ref`](crate::Subject::rag_ref) names a single document. A
+//! [`RagQueryPayload`] describes a retrieval instead: the query text, how many
+//! results to return, a minimum relevance score, and metadata filters. It
+//! attaches to `SEARCH`, `RETRIEVE` and `KNOWLEDGE_SEARCH` instructions, so the
+//! opcode stream fully describes the retrieval step:
+//!
+//! ```rust
+//! use frame_isa::rag::{MetadataFilter, RagQueryPayload};
+//! use frame_isa::{Action, ExtendedInstruction, Instruction, Subject};
+//!
+//! let query = RagQueryPayload::new("refund policy")
+//!     .with_top_k(3)
+//!     .with_min_score(0.7)
+//!     .with_filter(MetadataFilter::eq("lang", "en"));
+//! let base = Instruction::simple(Action::KNOWLEDGE_SEARCH, Subject::DOCUMENTATION);
+//! let ext = ExtendedInstruction::with_rag_query(base, query);
+//! assert!(ext.validate().is_ok());
+//! assert!(ext.needs_rag());
+//!
+//! let parsed = ExtendedInstruction::from_bytes(&ext.to_bytes()).unwrap();
+//! assert_eq!(parsed.as_rag_query().unwrap().query, "refund policy");
+//! ```
+//!
+//! Payload format (variable-size, after the [LEN:2] prefix):
+//!
+//! ```text
+//! [TOP_K:2][MIN_SCORE:4][QUERY_LEN:2][QUERY][COUNT:1][FILTER]...
+//!
+//! FILTER = [OP:1][KEY_LEN:1][KEY][VALUE_LEN:1][VALUE]
+//! ```
+//!
+//! Strings are UTF-8. MIN_SCORE is an f32; NaN means no threshold.
+//!
+//! RAG subjects only hold 12-bit document IDs (up to
+//! [`Subject::MAX_RAG_DOC_ID`](crate::Subject::MAX_RAG_DOC_ID)). Larger
+//! catalogs address documents with a [`RagAddress`] payload, a collection
+//! plus a 64-bit document ID:
+//!
+//! ```rust
+//! use frame_isa::rag::RagAddress;
+//! use frame_isa::{Action, ExtendedInstruction, Instruction, Subject};
+//!
+//! let base = Instruction::simple(Action::RETRIEVE, Subject::DOCUMENTATION);
+//! let ext = ExtendedInstruction::with_rag_ref(base, RagAddress::new(2, 20_000));
+//! assert!(ext.needs_rag());
+//! assert_eq!(ext.rag_address(), Some(RagAddress::new(2, 20_000)));
+//!
+//! // Compact subjects resolve to the default collection
+//! let compact = ExtendedInstruction::new(Instruction::simple(Action::RETRIEVE, Subject::rag_ref(42)));
+//! assert_eq!(compact.rag_address(), Some(RagAddress::document(42)));
+//! ```
+//!
+//! ```text
+//! [COLLECTION:4][DOC_ID:8] = 12 bytes
+//! ```
+
+use crate::{InstructionError, Subject};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Maximum query length in bytes
+pub const MAX_QUERY_LEN: usize = 1024;
+
+/// Maximum number of metadata filters
+pub const MAX_FILTERS: usize = 16;
+
+/// Maximum filter key or value length in bytes
+pub const MAX_FILTER_FIELD_LEN: usize = u8::MAX as usize;
+
+/// Comparison applied by a metadata filter
+///
+/// Byte codes are ASCII mnemonics and are stable across versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum FilterOp {
+    /// Value equals
+    Eq = b'=',
+    /// Value differs (also matches documents without the key)
+    Ne = b'!',
+    /// Value is less than (numerically when both sides are numbers)
+    Lt = b'<',
+    /// Value is greater than (numerically when both sides are numbers)
+    Gt = b'>',
+    /// Value starts with
+    Prefix = b'^',
+    /// Value contains
+    Contains = b'~',
+}
+
+impl FilterOp {
+    /// Parse from byte
+    pub fn from_byte(b: u8) -> Option<Self> {
+        match b {
+            b'=' => Some(FilterOp::Eq),
+            b'!' => Some(FilterOp::Ne),
+            b'<' => Some(FilterOp::Lt),
+            b'>' => Some(FilterOp::Gt),
+            b'^' => Some(FilterOp::Prefix),
+            b'~' => Some(FilterOp::Contains),
+            _ => None,
+        }
+    }
+
+    /// Convert to byte
+    pub fn to_byte(self) -> u8 {
+        self as u8
+    }
+
+    /// Operator symbol used in display output
+    pub fn symbol(self) -> &'static str {
+        match self {
+            FilterOp::Eq => "=",
+            FilterOp::Ne => "!=",
+            FilterOp::Lt => "<",
+            FilterOp::Gt => ">",
+            FilterOp::Prefix => "^=",
+            FilterOp::Contains => "~=",
+        }
+    }
+}
+
+/// Condition on a document metadata field
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MetadataFilter {
+    /// Metadata key
+    pub key: String,
+    /// Comparison
+    pub op: FilterOp,
+    /// Value to compare against
+    pub value: String,
+}
+
+impl MetadataFilter {
+    /// Create a filter
+    pub fn new(key: impl Into<String>, op: FilterOp, value: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            op,
+            value: value.into(),
+        }
+    }
+
+    /// Create an equality filter
+    pub fn eq(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::new(key, FilterOp::Eq, value)
+    }
+
+    /// Check a document's value for this key (`None` when the key is missing)
+    pub fn matches(&self, actual: Option<&str>) -> bool {
+        let Some(actual) = actual else {
+            return self.op == FilterOp::Ne;
+        };
+        let expected = self.value.as_str();
+        match self.op {
+            FilterOp::Eq => actual == expected,
+            FilterOp::Ne => actual != expected,
+            FilterOp::Lt => compare(actual, expected).is_lt(),
+            FilterOp::Gt => compare(actual, expected).is_gt(),
+            FilterOp::Prefix => actual.starts_with(expected),
+            FilterOp::Contains => actual.contains(expected),
+        }
+    }
+}
+
+/// Order two metadata values, numerically when both parse as numbers
+fn compare(a: &str, b: &str) -> std::cmp::Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal),
+        _ => a.cmp(b),
+    }
+}
+
+impl fmt::Display for MetadataFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{:?}", self.key, self.op.symbol(), self.value)
+    }
+}
+
+/// Retrieval request: query text, result count, score threshold and filters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RagQueryPayload {
+    /// Query text
+    pub query: String,
+    /// Maximum number of results
+    pub top_k: u16,
+    /// Minimum relevance score, if any
+    pub min_score: Option<f32>,
+    /// Metadata filters (all must match)
+    pub filters: Vec<MetadataFilter>,
+}
+
+impl RagQueryPayload {
+    /// Results returned when no `top_k` is given
+    pub const DEFAULT_TOP_K: u16 = 5;
+
+    /// Create a query returning the [`DEFAULT_TOP_K`](Self::DEFAULT_TOP_K) best matches
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            top_k: Self::DEFAULT_TOP_K,
+            min_score: None,
+            filters: Vec::new(),
+        }
+    }
+
+    /// Set the maximum number of results
+    pub fn with_top_k(mut self, top_k: u16) -> Self {
+        self.top_k = top_k;
+        self
+    }
+
+    /// Only return results scoring at least `min_score`
+    pub fn with_min_score(mut self, min_score: f32) -> Self {
+        self.min_score = Some(min_score);
+        self
+    }
+
+    /// Add a metadata filter
+    pub fn with_filter(mut self, filter: MetadataFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Check a document's metadata against every filter
+    pub fn matches_metadata<'a>(&self, lookup: impl Fn(&str) -> Option<&'a str>) -> bool {
+        self.filters
+            .iter()
+            .all(|filter| filter.matches(lookup(&filter.key)))
+    }
+
+    /// Check that the query is non-empty and within the encoding limits
+    pub fn validate(&self) -> Result<(), InstructionError> {
+        let invalid = |msg: String| Err(InstructionError::InvalidPayload(msg));
+        if self.query.trim().is_empty() {
+            return invalid("RAG query is empty".to_string());
+        }
+        if self.query.len() > MAX_QUERY_LEN {
+            return invalid(format!(
+                "RAG query is {} bytes (max {})",
+                self.query.len(),
+                MAX_QUERY_LEN
+            ));
+        }
+        if self.top_k == 0 {
+            return invalid("RAG query needs a non-zero top_k".to_string());
+        }
+        if let Some(score) = self.min_score.filter(|score| !score.is_finite()) {
+            return invalid(format!("Invalid minimum score {}", score));
+        }
+        if self.filters.len() > MAX_FILTERS {
+            return invalid(format!(
+                "RAG query has {} filters (max {})",
+                self.filters.len(),
+                MAX_FILTERS
+            ));
+        }
+        for filter in &self.filters {
+            if filter.key.is_empty() {
+                return invalid("Metadata filter key is empty".to_string());
+            }
+            if filter.key.len() > MAX_FILTER_FIELD_LEN || filter.value.len() > MAX_FILTER_FIELD_LEN
+            {
+                return invalid(format!(
+                    "Metadata filter on {:?} exceeds {} bytes",
+                    filter.key, MAX_FILTER_FIELD_LEN
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Serialize to bytes: [TOP_K:2][MIN_SCORE:4][QUERY_LEN:2][QUERY][COUNT:1][FILTER]...
+    ///
+    /// Fields over the encoding limits are truncated (at a character boundary);
+    /// call [`validate`](Self::validate) first to reject them instead.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let query = truncate_utf8(&self.query, MAX_QUERY_LEN);
+        let mut bytes = Vec::with_capacity(9 + query.len() + self.filters.len() * 8);
+        bytes.extend_from_slice(&self.top_k.to_be_bytes());
+        bytes.extend_from_slice(&self.min_score.unwrap_or(f32::NAN).to_be_bytes());
+        bytes.extend_from_slice(&(query.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(query.as_bytes());
+        bytes.push(self.filters.len().min(MAX_FILTERS) as u8);
+        for filter in self.filters.iter().take(MAX_FILTERS) {
+            bytes.push(filter.op.to_byte());
+            for field in [&filter.key, &filter.value] {
+                let field = truncate_utf8(field, MAX_FILTER_FIELD_LEN);
+                bytes.push(field.len() as u8);
+                bytes.extend_from_slice(field.as_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Parse from bytes
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader(bytes);
+        let top_k = u16::from_be_bytes(reader.take(2)?.try_into().ok()?);
+        let min_score = f32::from_be_bytes(reader.take(4)?.try_into().ok()?);
+        let query_len = u16::from_be_bytes(reader.take(2)?.try_into().ok()?) as usize;
+        let query = reader.take_str(query_len)?;
+        let count = reader.take(1)?[0] as usize;
+        let mut filters = Vec::with_capacity(count);
+        for _ in 0..count {
+            let op = FilterOp::from_byte(reader.take(1)?[0])?;
+            let key_len = reader.take(1)?[0] as usize;
+            let key = reader.take_str(key_len)?;
+            let value_len = reader.take(1)?[0] as usize;
+            let value = reader.take_str(value_len)?;
+            filters.push(MetadataFilter { key, op, value });
+        }
+        Some(Self {
+            query,
+            top_k,
+            min_score: (!min_score.is_nan()).then_some(min_score),
+            filters,
+        })
+    }
+}
+
+impl fmt::Display for RagQueryPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} top_k={}", self.query, self.top_k)?;
+        if let Some(score) = self.min_score {
+            write!(f, " score>={}", score)?;
+        }
+        for (i, filter) in self.filters.iter().enumerate() {
+            let sep = if i == 0 { " where " } else { ", " };
+            write!(f, "{}{}", sep, filter)?;
+        }
+        Ok(())
+    }
+}
+
+/// Document address wider than a RAG subject: collection plus 64-bit document ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct RagAddress {
+    /// Collection (catalog, index or namespace) holding the document
+    pub collection: u32,
+    /// Document ID within the collection
+    pub doc_id: u64,
+}
+
+impl RagAddress {
+    /// Collection addressed by compact RAG subjects
+    pub const DEFAULT_COLLECTION: u32 = 0;
+
+    /// Create an address
+    pub const fn new(collection: u32, doc_id: u64) -> Self {
+        Self { collection, doc_id }
+    }
+
+    /// Address a document in the default collection
+    pub const fn document(doc_id: u64) -> Self {
+        Self::new(Self::DEFAULT_COLLECTION, doc_id)
+    }
+
+    /// Address named by a RAG subject, if it is one
+    pub fn from_subject(subject: Subject) -> Option<Self> {
+        subject
+            .rag_doc_id()
+            .map(|doc_id| Self::document(doc_id.into()))
+    }
+
+    /// Equivalent RAG subject, if the address fits the compact form
+    pub fn compact_subject(&self) -> Option<Subject> {
+        if self.collection != Self::DEFAULT_COLLECTION {
+            return None;
+        }
+        Subject::try_rag_ref(self.doc_id).ok()
+    }
+
+    /// Serialize to bytes: [COLLECTION:4][DOC_ID:8] = 12 bytes
+    pub fn to_bytes(&self) -> [u8; 12] {
+        let mut bytes = [0u8; 12];
+        bytes[0..4].copy_from_slice(&self.collection.to_be_bytes());
+        bytes[4..12].copy_from_slice(&self.doc_id.to_be_bytes());
+        bytes
+    }
+
+    /// Parse from bytes
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 12 {
+            return None;
+        }
+        Some(Self {
+            collection: u32::from_be_bytes(bytes[0..4].try_into().ok()?),
+            doc_id: u64::from_be_bytes(bytes[4..12].try_into().ok()?),
+        })
+    }
+}
+
+impl fmt::Display for RagAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rag://{}/{}", self.collection, self.doc_id)
+    }
+}
+
+/// Longest prefix of `s` that fits in `max` bytes
+pub(crate) fn truncate_utf8(s: &str, max: usize) -> &str {
+    if s.len() <= max {
+        return s;
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+/// Cursor over payload bytes
+pub(crate) struct Reader<'a>(pub(crate) &'a [u8]);
+
+impl<'a> Reader<'a> {
+    pub(crate) fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Some(head)
+    }
+
+    pub(crate) fn take_str(&mut self, n: usize) -> Option<String> {
+        std::str::from_utf8(self.take(n)?).ok().map(str::to_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extended::{ExtendedInstruction, PayloadType};
+    use crate::{Action, Instruction, Subject};
+
+    fn sample() -> RagQueryPayload {
+        RagQueryPayload::new("refund policy for café orders")
+            .with_top_k(3)
+            .with_min_score(0.75)
+            .with_filter(MetadataFilter::eq("lang", "en"))
+            .with_filter(MetadataFilter::new("year", FilterOp::Gt, "2020"))
+    }
+
+    #[test]
+    fn test_rag_query_roundtrip() {
+        let query = sample();
+        assert_eq!(RagQueryPayload::from_bytes(&query.to_bytes()), Some(query));
+
+        let plain = RagQueryPayload::new("weather");
+        let parsed = RagQueryPayload::from_bytes(&plain.to_bytes()).unwrap();
+        assert_eq!(parsed.min_score, None);
+        assert_eq!(parsed.top_k, RagQueryPayload::DEFAULT_TOP_K);
+
+        // Truncated and malformed payloads are rejected
+        let bytes = sample().to_bytes();
+        assert!(RagQueryPayload::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+        let mut bad_op = bytes.clone();
+        let op_index = 2 + 4 + 2 + sample().query.len() + 1;
+        bad_op[op_index] = b'?';
+        assert!(RagQueryPayload::from_bytes(&bad_op).is_none());
+    }
+
+    #[test]
+    fn test_rag_query_extended_instruction() {
+        let base = Instruction::simple(Action::RETRIEVE, Subject::DOCUMENTATION);
+        let ext = ExtendedInstruction::with_rag_query(base, sample());
+        assert!(ext.needs_rag());
+        assert!(!base.needs_rag());
+
+        let bytes = ext.to_bytes();
+        assert_eq!(bytes[6], PayloadType::RagQuery.to_byte());
+        assert_eq!(bytes.len(), ext.byte_size());
+        let parsed = ExtendedInstruction::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, ext);
+        assert_eq!(parsed.as_rag_query(), Some(&sample()));
+        assert!(ext.to_string().ends_with(
+            " ? \"refund policy for café orders\" top_k=3 score>=0.75 where lang=\"en\", year>\"2020\""
+        ));
+    }
+
+    #[test]
+    fn test_rag_query_validation() {
+        for action in [Action::SEARCH, Action::RETRIEVE, Action::KNOWLEDGE_SEARCH] {
+            let ext = ExtendedInstruction::with_rag_query(
+                Instruction::simple(action, Subject::NULL),
+                sample(),
+            );
+            assert!(ext.validate().is_ok(), "{}", action);
+        }
+
+        let ext = ExtendedInstruction::with_rag_query(
+            Instruction::simple(Action::RESPOND, Subject::NULL),
+            sample(),
+        );
+        assert!(matches!(
+            ext.validate(),
+            Err(InstructionError::PayloadMismatch {
+                action: Action::RESPOND,
+                payload: PayloadType::RagQuery,
+            })
+        ));
+
+        let invalid = [
+            RagQueryPayload::new("  "),
+            RagQueryPayload::new("x".repeat(MAX_QUERY_LEN + 1)),
+            sample().with_top_k(0),
+            sample().with_min_score(f32::INFINITY),
+            sample().with_filter(MetadataFilter::eq("", "x")),
+            sample().with_filter(MetadataFilter::eq("k", "v".repeat(256))),
+        ];
+        for query in invalid {
+            assert!(
+                matches!(query.validate(), Err(InstructionError::InvalidPayload(_))),
+                "{}",
+                query
+            );
+        }
+    }
+
+    #[test]
+    fn test_metadata_filters() {
+        let meta = [("lang", "en"), ("year", "2023"), ("title", "Refund policy")];
+        let lookup = |key: &str| meta.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+        assert!(sample().matches_metadata(lookup));
+        assert!(!sample()
+            .with_filter(MetadataFilter::new("year", FilterOp::Lt, "2023"))
+            .matches_metadata(lookup));
+
+        // Numeric comparison when both sides parse, lexicographic otherwise
+        assert!(MetadataFilter::new("n", FilterOp::Gt, "9").matches(Some("10")));
+        assert!(MetadataFilter::new("n", FilterOp::Lt, "b").matches(Some("a")));
+        assert!(MetadataFilter::new("t", FilterOp::Prefix, "Ref").matches(Some("Refund")));
+        assert!(MetadataFilter::new("t", FilterOp::Contains, "fun").matches(Some("Refund")));
+
+        // Missing keys only satisfy "not equal"
+        assert!(!MetadataFilter::eq("missing", "x").matches(None));
+        assert!(MetadataFilter::new("missing", FilterOp::Ne, "x").matches(None));
+    }
+
+    #[test]
+    fn test_rag_address() {
+        let address = RagAddress::new(7, 20_000);
+        assert_eq!(RagAddress::from_bytes(&address.to_bytes()), Some(address));
+        assert_eq!(RagAddress::from_bytes(&address.to_bytes()[..11]), None);
+        assert_eq!(address.to_string(), "rag://7/20000");
+
+        // Only default-collection IDs up to 0x0FFF have a compact subject
+        assert_eq!(address.compact_subject(), None);
+        assert_eq!(RagAddress::document(20_000).compact_subject(), None);
+        assert_eq!(
+            RagAddress::document(0x42).compact_subject(),
+            Some(Subject::rag_ref(0x42))
+        );
+        assert_eq!(
+            RagAddress::from_subject(Subject::rag_ref(0x42)),
+            Some(RagAddress::document(0x42))
+        );
+        assert_eq!(RagAddress::from_subject(Subject::USER), None);
+    }
+
+    #[test]
+    fn test_rag_address_extended_instruction() {
+        let base = Instruction::simple(Action::RETRIEVE, Subject::DOCUMENTATION);
+        let ext = ExtendedInstruction::with_rag_ref(base, RagAddress::new(1, 5000));
+        assert!(ext.needs_rag());
+        assert!(ext.validate().is_ok());
+        assert_eq!(ext.to_string(), format!("{} rag://1/5000", base));
+
+        let bytes = ext.to_bytes();
+        assert_eq!(bytes.len(), PayloadType::RagRef.total_size());
+        let parsed = ExtendedInstruction::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.as_rag_ref(), Some(&RagAddress::new(1, 5000)));
+        assert_eq!(parsed.rag_address(), Some(RagAddress::new(1, 5000)));
+
+        // A RAG subject must agree with the payload address
+        let agreeing = Instruction::simple(Action::RETRIEVE, Subject::rag_ref(0x42));
+        let ext = ExtendedInstruction::with_rag_ref(agreeing, RagAddress::document(0x42));
+        assert!(ext.validate().is_ok());
+        let conflicting = ExtendedInstruction::with_rag_ref(agreeing, RagAddress::document(5000));
+        assert!(matches!(
+            conflicting.validate(),
+            Err(InstructionError::InvalidPayload(_))
+        ));
+
+        let plain = ExtendedInstruction::new(Instruction::simple(Action::RETRIEVE, Subject::USER));
+        assert!(!plain.needs_rag());
+        assert_eq!(plain.rag_address(), None);
+    }
+
+    #[test]
+    fn test_truncate_utf8() {
+        assert_eq!(truncate_utf8("café", 4), "caf");
+        assert_eq!(truncate_utf8("café", 5), "café");
+        let long = RagQueryPayload::new("é".repeat(MAX_QUERY_LEN));
+        let parsed = RagQueryPayload::from_bytes(&long.to_bytes()).unwrap();
+        assert_eq!(parsed.query.len(), MAX_QUERY_LEN);
+    }
+}
@@ -0,0 +1,348 @@
+//! RAG reference resolution
+//!
+//! A [`RagResolver`] turns a RAG reference (a RAG subject or a
+//! [`RagAddress`] payload) into the referenced [`Document`]. Pipelines take a
+//! resolver so tests can run against [`MemoryResolver`] or
+//! [`DirectoryResolver`] and production can plug in the real catalog:
+//!
+//! ```rust
+//! use frame_isa::resolver::{Document, MemoryResolver, RagResolver};
+//! use frame_isa::{Action, Instruction, RagAddress, Subject};
+//!
+//! let mut docs = MemoryResolver::new();
+//! docs.insert(Document::new(RagAddress::document(0x42), "Refunds take 5 days.").with_metadata("lang", "en"));
+//!
+//! let instr = Instruction::simple(Action::RETRIEVE, Subject::rag_ref(0x42));
+//! let doc = docs.resolve(&instr).unwrap();
+//! assert_eq!(doc.content, "Refunds take 5 days.");
+//! assert_eq!(doc.metadata("lang"), Some("en"));
+//! ```
+
+use crate::{ExtendedInstruction, Instruction, RagAddress};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Errors resolving a RAG reference
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ResolveError {
+    #[error("Instruction does not reference a RAG document")]
+    NotRagReference,
+
+    #[error("RAG document {0} not found")]
+    NotFound(RagAddress),
+
+    #[error("Invalid RAG document {address}: {message}")]
+    InvalidDocument {
+        address: RagAddress,
+        message: String,
+    },
+
+    #[error("RAG backend unavailable: {0}")]
+    Unavailable(String),
+}
+
+/// Resolved RAG document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Document {
+    /// Address the document was resolved from
+    pub address: RagAddress,
+    /// Document text
+    pub content: String,
+    /// Metadata fields (language, title, year, ...)
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl Document {
+    /// Create a document without metadata
+    pub fn new(address: RagAddress, content: impl Into<String>) -> Self {
+        Self {
+            address,
+            content: content.into(),
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    /// Add a metadata field
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Get a metadata field
+    pub fn metadata(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(String::as_str)
+    }
+}
+
+/// Source of RAG documents
+pub trait RagResolver {
+    /// Fetch the document at `address`
+    fn fetch(&self, address: RagAddress) -> Result<Document, ResolveError>;
+
+    /// Resolve the document named by an instruction's RAG subject
+    fn resolve(&self, instr: &Instruction) -> Result<Document, ResolveError> {
+        let address =
+            RagAddress::from_subject(instr.subject).ok_or(ResolveError::NotRagReference)?;
+        self.fetch(address)
+    }
+
+    /// Resolve the document named by a RAG address payload or RAG subject
+    fn resolve_extended(&self, ext: &ExtendedInstruction) -> Result<Document, ResolveError> {
+        let address = ext.rag_address().ok_or(ResolveError::NotRagReference)?;
+        self.fetch(address)
+    }
+}
+
+impl<R: RagResolver + ?Sized> RagResolver for &R {
+    fn fetch(&self, address: RagAddress) -> Result<Document, ResolveError> {
+        (**self).fetch(address)
+    }
+}
+
+impl<R: RagResolver + ?Sized> RagResolver for Arc<R> {
+    fn fetch(&self, address: RagAddress) -> Result<Document, ResolveError> {
+        (**self).fetch(address)
+    }
+}
+
+/// Resolver over documents held in memory
+#[derive(Debug, Clone, Default)]
+pub struct MemoryResolver {
+    documents: HashMap<RagAddress, Document>,
+}
+
+impl MemoryResolver {
+    /// Create an empty resolver
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a document, returning the one it replaces
+    pub fn insert(&mut self, document: Document) -> Option<Document> {
+        self.documents.insert(document.address, document)
+    }
+
+    /// Remove a document
+    pub fn remove(&mut self, address: RagAddress) -> Option<Document> {
+        self.documents.remove(&address)
+    }
+
+    /// Number of documents
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Check if there are no documents
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+}
+
+impl FromIterator<Document> for MemoryResolver {
+    fn from_iter<I: IntoIterator<Item = Document>>(iter: I) -> Self {
+        Self {
+            documents: iter.into_iter().map(|doc| (doc.address, doc)).collect(),
+        }
+    }
+}
+
+impl RagResolver for MemoryResolver {
+    fn fetch(&self, address: RagAddress) -> Result<Document, ResolveError> {
+        self.documents
+            .get(&address)
+            .cloned()
+            .ok_or(ResolveError::NotFound(address))
+    }
+}
+
+/// Resolver reading documents from a directory tree
+///
+/// ```text
+/// <root>/<collection>/<doc_id>.txt    document content (UTF-8)
+/// <root>/<collection>/<doc_id>.meta   optional metadata, one `key = value` per line
+/// ```
+///
+/// Blank lines and lines starting with `#` in metadata files are ignored.
+#[derive(Debug, Clone)]
+pub struct DirectoryResolver {
+    root: PathBuf,
+}
+
+impl DirectoryResolver {
+    /// Content file extension
+    pub const CONTENT_EXTENSION: &'static str = "txt";
+    /// Metadata file extension
+    pub const METADATA_EXTENSION: &'static str = "meta";
+
+    /// Create a resolver rooted at `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Root directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of the content file for `address`
+    pub fn content_path(&self, address: RagAddress) -> PathBuf {
+        self.path(address, Self::CONTENT_EXTENSION)
+    }
+
+    /// Path of the metadata file for `address`
+    pub fn metadata_path(&self, address: RagAddress) -> PathBuf {
+        self.path(address, Self::METADATA_EXTENSION)
+    }
+
+    fn path(&self, address: RagAddress, extension: &str) -> PathBuf {
+        self.root
+            .join(address.collection.to_string())
+            .join(format!("{}.{}", address.doc_id, extension))
+    }
+}
+
+/// Parse `key = value` metadata lines
+fn parse_metadata(text: &str) -> Result<BTreeMap<String, String>, String> {
+    let mut metadata = BTreeMap::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("metadata line {} is not `key = value`", number + 1))?;
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(format!("metadata line {} has an empty key", number + 1));
+        }
+        metadata.insert(key.to_string(), value.trim().to_string());
+    }
+    Ok(metadata)
+}
+
+impl RagResolver for DirectoryResolver {
+    fn fetch(&self, address: RagAddress) -> Result<Document, ResolveError> {
+        let read_error = |err: io::Error| match err.kind() {
+            io::ErrorKind::NotFound => ResolveError::NotFound(address),
+            io::ErrorKind::InvalidData => ResolveError::InvalidDocument {
+                address,
+                message: err.to_string(),
+            },
+            _ => ResolveError::Unavailable(err.to_string()),
+        };
+        let content = std::fs::read_to_string(self.content_path(address)).map_err(read_error)?;
+        let metadata = match std::fs::read_to_string(self.metadata_path(address)) {
+            Ok(text) => parse_metadata(&text)
+                .map_err(|message| ResolveError::InvalidDocument { address, message })?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(read_error(err)),
+        };
+        Ok(Document {
+            address,
+            content,
+            metadata,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, Subject};
+
+    /// Fresh directory under the system temp dir, removed on drop
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("frame-isa-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_memory_resolver() {
+        let docs: MemoryResolver = [
+            Document::new(RagAddress::document(0x42), "short id"),
+            Document::new(RagAddress::new(3, 20_000), "wide id").with_metadata("lang", "de"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(docs.len(), 2);
+
+        let instr = Instruction::simple(Action::RETRIEVE, Subject::rag_ref(0x42));
+        assert_eq!(docs.resolve(&instr).unwrap().content, "short id");
+
+        let base = Instruction::simple(Action::RETRIEVE, Subject::DOCUMENTATION);
+        let ext = ExtendedInstruction::with_rag_ref(base, RagAddress::new(3, 20_000));
+        let doc = docs.resolve_extended(&ext).unwrap();
+        assert_eq!(doc.content, "wide id");
+        assert_eq!(doc.metadata("lang"), Some("de"));
+
+        assert_eq!(docs.resolve(&base), Err(ResolveError::NotRagReference));
+        let missing = Instruction::simple(Action::RETRIEVE, Subject::rag_ref(7));
+        assert_eq!(
+            docs.resolve(&missing),
+            Err(ResolveError::NotFound(RagAddress::document(7)))
+        );
+    }
+
+    #[test]
+    fn test_directory_resolver() {
+        let dir = TempDir::new("directory-resolver");
+        let resolver = DirectoryResolver::new(&dir.0);
+        let address = RagAddress::new(2, 5000);
+
+        std::fs::create_dir_all(resolver.content_path(address).parent().unwrap()).unwrap();
+        std::fs::write(resolver.content_path(address), "Refunds take 5 days.").unwrap();
+        assert_eq!(
+            resolver.fetch(address),
+            Ok(Document::new(address, "Refunds take 5 days."))
+        );
+
+        std::fs::write(
+            resolver.metadata_path(address),
+            "# catalog export\nlang = en\n\ntitle = Refund policy\n",
+        )
+        .unwrap();
+        let doc = resolver.fetch(address).unwrap();
+        assert_eq!(doc.metadata("lang"), Some("en"));
+        assert_eq!(doc.metadata("title"), Some("Refund policy"));
+
+        std::fs::write(resolver.metadata_path(address), "not metadata").unwrap();
+        assert!(matches!(
+            resolver.fetch(address),
+            Err(ResolveError::InvalidDocument { .. })
+        ));
+
+        assert_eq!(
+            resolver.fetch(RagAddress::document(1)),
+            Err(ResolveError::NotFound(RagAddress::document(1)))
+        );
+    }
+
+    #[test]
+    fn test_dyn_resolver() {
+        let memory = MemoryResolver::from_iter([Document::new(RagAddress::document(1), "one")]);
+        let shared: Arc<dyn RagResolver> = Arc::new(memory);
+        let resolvers: [&dyn RagResolver; 2] = [&shared, &DirectoryResolver::new("/nonexistent")];
+
+        let instr = Instruction::simple(Action::RETRIEVE, Subject::rag_ref(1));
+        assert!(resolvers[0].resolve(&instr).is_ok());
+        assert!(resolvers[1].resolve(&instr).is_err());
+    }
+}
@@ -0,0 +1,793 @@
+//! Time ranges and recurrence rules for `SCHEDULE` / `DATE` subjects
+//!
+//! A [`SchedulePayload`] is one interval (`start` plus `duration`) that may
+//! repeat by a [`Recurrence`] rule, a small subset of iCalendar's RRULE:
+//! daily/weekly/monthly frequency, an interval, a weekday filter, and a
+//! count and/or until limit.
+//!
+//! Occurrences keep their local wall-clock time: the rule is applied in the
+//! payload's UTC offset or zone, so "every weekday 9-5" in New York stays at
+//! 09:00 local across DST changes.
+//!
+//! ```rust
+//! use frame_isa::iso8601::parse_datetime;
+//! use frame_isa::schedule::{SchedulePayload, WeekdaySet};
+//! use frame_isa::ZoneId;
+//!
+//! // Every weekday 9-5 New York time, starting Monday 2026-10-19
+//! let (start, _) = parse_datetime("2026-10-19T09:00:00-04:00").unwrap();
+//! let schedule = SchedulePayload::once(start, 8 * 3600)
+//!     .with_zone(ZoneId::NEW_YORK)
+//!     .daily(1)
+//!     .on(WeekdaySet::WEEKDAYS);
+//!
+//! let starts: Vec<String> = schedule.occurrences().take(6).map(|o| o.to_string()).collect();
+//! assert_eq!(starts[4], "2026-10-23T13:00:00Z/2026-10-23T21:00:00Z");
+//! assert_eq!(starts[5], "2026-10-26T13:00:00Z/2026-10-26T21:00:00Z"); // skips the weekend
+//! ```
+//!
+//! Monthly steps clamp the day of month like
+//! [`TimeArithmetic::Calendar`](crate::calendar::TimeArithmetic) (Jan 31 is
+//! followed by Feb 28/29).
+//!
+//! Payload format:
+//!
+//! ```text
+//! [START:8][DURATION:4][OFFSET_MIN:2][ZONE:2][FREQ:1][INTERVAL:2][WEEKDAYS:1][COUNT:2][FLAGS:1][UNTIL:8] = 31 bytes
+//! ```
+
+use crate::calendar::{DateTime, Weekday, SECONDS_PER_DAY};
+use crate::iso8601::format_datetime;
+use crate::tz::{format_offset, ZoneId, MAX_OFFSET_MINUTES, MIN_OFFSET_MINUTES};
+use crate::InstructionError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How often a schedule repeats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Frequency {
+    Daily = 1,
+    Weekly = 2,
+    Monthly = 3,
+}
+
+impl Frequency {
+    /// Parse from byte
+    pub fn from_byte(b: u8) -> Option<Self> {
+        match b {
+            1 => Some(Frequency::Daily),
+            2 => Some(Frequency::Weekly),
+            3 => Some(Frequency::Monthly),
+            _ => None,
+        }
+    }
+
+    /// Convert to byte
+    pub fn to_byte(self) -> u8 {
+        self as u8
+    }
+
+    /// iCalendar name (`DAILY`, `WEEKLY`, `MONTHLY`)
+    pub fn name(self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        }
+    }
+}
+
+/// Set of weekdays as a bitmask (bit 0 = Monday ... bit 6 = Sunday)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(transparent)]
+pub struct WeekdaySet(pub u8);
+
+impl WeekdaySet {
+    pub const EMPTY: Self = Self(0);
+    pub const WEEKDAYS: Self = Self(0b001_1111);
+    pub const WEEKEND: Self = Self(0b110_0000);
+    pub const ALL: Self = Self(0b111_1111);
+
+    /// Set containing a single day
+    pub fn single(day: Weekday) -> Self {
+        Self(1 << (day.number() - 1))
+    }
+
+    /// Add a day
+    pub fn with(self, day: Weekday) -> Self {
+        Self(self.0 | Self::single(day).0)
+    }
+
+    /// Check if a day is in the set
+    pub fn contains(self, day: Weekday) -> bool {
+        self.0 & Self::single(day).0 != 0
+    }
+
+    /// Check if no day is set
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Days in the set, Monday first
+    pub fn iter(self) -> impl Iterator<Item = Weekday> {
+        Weekday::ALL
+            .into_iter()
+            .filter(move |&day| self.contains(day))
+    }
+}
+
+impl FromIterator<Weekday> for WeekdaySet {
+    fn from_iter<I: IntoIterator<Item = Weekday>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::EMPTY, Self::with)
+    }
+}
+
+/// iCalendar two-letter weekday code
+fn ical_day(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Monday => "MO",
+        Weekday::Tuesday => "TU",
+        Weekday::Wednesday => "WE",
+        Weekday::Thursday => "TH",
+        Weekday::Friday => "FR",
+        Weekday::Saturday => "SA",
+        Weekday::Sunday => "SU",
+    }
+}
+
+/// Repetition rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    /// Step between periods (every `interval` days/weeks/months)
+    pub interval: u16,
+    /// Only these weekdays (empty = no filter; weekly defaults to the start's weekday)
+    pub weekdays: WeekdaySet,
+    /// Maximum number of occurrences (0 = unlimited)
+    pub count: u16,
+    /// Last allowed occurrence start (Unix seconds)
+    pub until: Option<i64>,
+}
+
+impl Recurrence {
+    /// Create a rule with no weekday filter or limit
+    pub fn new(frequency: Frequency, interval: u16) -> Self {
+        Self {
+            frequency,
+            interval,
+            weekdays: WeekdaySet::EMPTY,
+            count: 0,
+            until: None,
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RRULE:FREQ={}", self.frequency.name())?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.weekdays.is_empty() {
+            let days: Vec<_> = self.weekdays.iter().map(ical_day).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if self.count != 0 {
+            write!(f, ";COUNT={}", self.count)?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", format_datetime(until, 0))?;
+        }
+        Ok(())
+    }
+}
+
+/// One occurrence of a schedule (UTC, end exclusive)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Occurrence {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Occurrence {
+    /// Check if a timestamp falls inside the occurrence
+    pub fn contains(&self, timestamp: i64) -> bool {
+        (self.start..self.end).contains(&timestamp) || timestamp == self.start
+    }
+}
+
+impl fmt::Display for Occurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}",
+            format_datetime(self.start, 0),
+            format_datetime(self.end, 0)
+        )
+    }
+}
+
+/// Time interval with an optional recurrence rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SchedulePayload {
+    /// Start of the first occurrence (Unix seconds)
+    pub start: i64,
+    /// Length of each occurrence in seconds (0 = a point in time)
+    pub duration: u32,
+    /// Fixed UTC offset in minutes for local-time rules, used when `zone` is `None`
+    pub offset_minutes: i16,
+    /// Named zone for local-time rules (takes precedence over `offset_minutes`)
+    pub zone: Option<ZoneId>,
+    /// Repetition rule (`None` = a single interval)
+    pub recurrence: Option<Recurrence>,
+}
+
+impl SchedulePayload {
+    const FLAG_UNTIL: u8 = 0x01;
+
+    /// A single interval
+    pub fn once(start: i64, duration: u32) -> Self {
+        Self {
+            start,
+            duration,
+            offset_minutes: 0,
+            zone: None,
+            recurrence: None,
+        }
+    }
+
+    /// A single interval from `start` to `end`, or `None` if `end` is before
+    /// `start` or too far after it
+    pub fn between(start: i64, end: i64) -> Option<Self> {
+        let duration = u32::try_from(end.checked_sub(start)?).ok()?;
+        Some(Self::once(start, duration))
+    }
+
+    /// Set timezone offset in minutes
+    pub fn with_offset_minutes(mut self, minutes: i16) -> Self {
+        self.offset_minutes = minutes;
+        self
+    }
+
+    /// Set a named zone
+    pub fn with_zone(mut self, zone: ZoneId) -> Self {
+        self.zone = Some(zone);
+        self
+    }
+
+    /// Set a recurrence rule
+    pub fn repeating(mut self, recurrence: Recurrence) -> Self {
+        self.recurrence = Some(recurrence);
+        self
+    }
+
+    /// Repeat every `interval` days
+    pub fn daily(self, interval: u16) -> Self {
+        self.repeating(Recurrence::new(Frequency::Daily, interval))
+    }
+
+    /// Repeat every `interval` weeks
+    pub fn weekly(self, interval: u16) -> Self {
+        self.repeating(Recurrence::new(Frequency::Weekly, interval))
+    }
+
+    /// Repeat every `interval` months
+    pub fn monthly(self, interval: u16) -> Self {
+        self.repeating(Recurrence::new(Frequency::Monthly, interval))
+    }
+
+    /// Restrict occurrences to these weekdays (no effect without a recurrence)
+    pub fn on(mut self, weekdays: WeekdaySet) -> Self {
+        if let Some(rule) = &mut self.recurrence {
+            rule.weekdays = weekdays;
+        }
+        self
+    }
+
+    /// Limit the number of occurrences (no effect without a recurrence)
+    pub fn count(mut self, count: u16) -> Self {
+        if let Some(rule) = &mut self.recurrence {
+            rule.count = count;
+        }
+        self
+    }
+
+    /// Limit occurrences to those starting at or before `until` (no effect without a recurrence)
+    pub fn until(mut self, until: i64) -> Self {
+        if let Some(rule) = &mut self.recurrence {
+            rule.until = Some(until);
+        }
+        self
+    }
+
+    /// End of the first occurrence (saturating at the end of time)
+    pub fn end(&self) -> i64 {
+        self.start.saturating_add(self.duration as i64)
+    }
+
+    /// UTC offset in minutes in effect at a UTC timestamp
+    pub fn offset_at(&self, timestamp: i64) -> i16 {
+        self.zone
+            .map_or(self.offset_minutes, |zone| zone.offset_at(timestamp))
+    }
+
+    fn local_to_utc(&self, local: i64) -> i64 {
+        match self.zone {
+            Some(zone) => zone.to_utc(local),
+            None => local.saturating_sub(self.offset_minutes as i64 * 60),
+        }
+    }
+
+    /// Iterate over occurrences in order (unbounded without count or until)
+    ///
+    /// A rule with a zero interval never advances, so it yields only the
+    /// occurrences in its first period. Iteration also ends once occurrences
+    /// would run past the `i64` range.
+    pub fn occurrences(&self) -> Occurrences {
+        Occurrences {
+            schedule: *self,
+            local_start: self
+                .start
+                .saturating_add(self.offset_at(self.start) as i64 * 60),
+            period: 0,
+            slot: 0,
+            emitted: 0,
+            done: false,
+        }
+    }
+
+    /// Occurrence containing `timestamp`, if any
+    pub fn occurrence_at(&self, timestamp: i64) -> Option<Occurrence> {
+        self.occurrences()
+            .take_while(|o| o.start <= timestamp)
+            .find(|o| o.contains(timestamp))
+    }
+
+    /// Validate the offset, zone and rule
+    pub fn validate(&self) -> Result<(), InstructionError> {
+        let invalid = |msg: String| Err(InstructionError::InvalidPayload(msg));
+        if !(MIN_OFFSET_MINUTES..=MAX_OFFSET_MINUTES).contains(&self.offset_minutes) {
+            return invalid(format!(
+                "UTC offset {} out of range",
+                format_offset(self.offset_minutes)
+            ));
+        }
+        if let Some(zone) = self.zone.filter(|zone| !zone.is_known()) {
+            return invalid(format!("Unknown time zone 0x{:04X}", zone.0));
+        }
+        let Some(rule) = self.recurrence else {
+            return Ok(());
+        };
+        if rule.interval == 0 {
+            return invalid("Recurrence needs a non-zero interval".to_string());
+        }
+        if rule.weekdays.0 & !WeekdaySet::ALL.0 != 0 {
+            return invalid(format!("Invalid weekday mask 0x{:02X}", rule.weekdays.0));
+        }
+        match rule.until {
+            Some(until) if until < self.start => {
+                invalid("Recurrence ends before the first occurrence".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Serialize to bytes:
+    /// [START:8][DURATION:4][OFFSET_MIN:2][ZONE:2][FREQ:1][INTERVAL:2][WEEKDAYS:1][COUNT:2][FLAGS:1][UNTIL:8] = 31 bytes
+    ///
+    /// FREQ 0 means no recurrence; ZONE 0 means no zone.
+    pub fn to_bytes(&self) -> [u8; 31] {
+        let mut bytes = [0u8; 31];
+        bytes[0..8].copy_from_slice(&self.start.to_be_bytes());
+        bytes[8..12].copy_from_slice(&self.duration.to_be_bytes());
+        bytes[12..14].copy_from_slice(&self.offset_minutes.to_be_bytes());
+        bytes[14..16].copy_from_slice(&self.zone.map_or(0, |zone| zone.0).to_be_bytes());
+        if let Some(rule) = self.recurrence {
+            bytes[16] = rule.frequency.to_byte();
+            bytes[17..19].copy_from_slice(&rule.interval.to_be_bytes());
+            bytes[19] = rule.weekdays.0;
+            bytes[20..22].copy_from_slice(&rule.count.to_be_bytes());
+            if let Some(until) = rule.until {
+                bytes[22] = Self::FLAG_UNTIL;
+                bytes[23..31].copy_from_slice(&until.to_be_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Parse from bytes
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 31 {
+            return None;
+        }
+        let zone = match u16::from_be_bytes([bytes[14], bytes[15]]) {
+            0 => None,
+            code => Some(ZoneId(code)),
+        };
+        let until = match bytes[22] & Self::FLAG_UNTIL {
+            0 => None,
+            _ => Some(i64::from_be_bytes(bytes[23..31].try_into().ok()?)),
+        };
+        let interval = u16::from_be_bytes([bytes[17], bytes[18]]);
+        let recurrence = match bytes[16] {
+            0 => None,
+            _ if interval == 0 => return None,
+            freq => Some(Recurrence {
+                frequency: Frequency::from_byte(freq)?,
+                interval,
+                weekdays: WeekdaySet(bytes[19]),
+                count: u16::from_be_bytes([bytes[20], bytes[21]]),
+                until,
+            }),
+        };
+        Some(Self {
+            start: i64::from_be_bytes(bytes[0..8].try_into().ok()?),
+            duration: u32::from_be_bytes(bytes[8..12].try_into().ok()?),
+            offset_minutes: i16::from_be_bytes([bytes[12], bytes[13]]),
+            zone,
+            recurrence,
+        })
+    }
+}
+
+impl fmt::Display for SchedulePayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}",
+            format_datetime(self.start, self.offset_at(self.start)),
+            format_datetime(self.end(), self.offset_at(self.end()))
+        )?;
+        if let Some(zone) = self.zone {
+            write!(f, " {}", zone.name())?;
+        }
+        if let Some(rule) = self.recurrence {
+            write!(f, " {}", rule)?;
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over the occurrences of a [`SchedulePayload`]
+#[derive(Debug, Clone)]
+pub struct Occurrences {
+    schedule: SchedulePayload,
+    /// First start in local wall-clock seconds
+    local_start: i64,
+    /// Index of the current day/week/month period
+    period: i64,
+    /// Next weekday slot within the current week (weekly rules)
+    slot: u8,
+    emitted: u16,
+    done: bool,
+}
+
+impl Occurrences {
+    /// Next candidate start in local wall-clock seconds (before the weekday
+    /// filter for daily and monthly rules)
+    fn next_candidate(&mut self, rule: &Recurrence) -> Option<i64> {
+        if rule.interval == 0 && self.period > 0 {
+            return None;
+        }
+        let start = DateTime::from_timestamp(self.local_start);
+        match rule.frequency {
+            Frequency::Daily => {
+                let step = self.period.checked_mul(rule.interval as i64)?;
+                self.period += 1;
+                self.local_start
+                    .checked_add(step.checked_mul(SECONDS_PER_DAY)?)
+            }
+            Frequency::Monthly => {
+                let step = self.period.checked_mul(rule.interval as i64)?;
+                self.period += 1;
+                // A saturated timestamp means the month is past the i64 range
+                Some(start.add_months(step).to_timestamp())
+                    .filter(|&local| local != i64::MIN && local != i64::MAX)
+            }
+            Frequency::Weekly => {
+                let days = match weekday_filter(rule) {
+                    days if days.is_empty() => WeekdaySet::single(start.weekday()),
+                    days => days,
+                };
+                let monday = self
+                    .local_start
+                    .checked_sub((start.weekday().number() as i64 - 1) * SECONDS_PER_DAY)?;
+                loop {
+                    if self.slot >= 7 {
+                        self.slot = 0;
+                        self.period += 1;
+                        if rule.interval == 0 {
+                            return None;
+                        }
+                    }
+                    let slot = self.slot;
+                    self.slot += 1;
+                    if days.contains(Weekday::ALL[slot as usize]) {
+                        let step = self.period.checked_mul(rule.interval as i64)?;
+                        let week = monday.checked_add(step.checked_mul(7 * SECONDS_PER_DAY)?)?;
+                        return week.checked_add(slot as i64 * SECONDS_PER_DAY);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Weekday filter with bits outside Monday-Sunday ignored
+fn weekday_filter(rule: &Recurrence) -> WeekdaySet {
+    WeekdaySet(rule.weekdays.0 & WeekdaySet::ALL.0)
+}
+
+/// Consecutive filtered-out candidates after which iteration stops (a
+/// monthly rule whose weekday filter can never match)
+const MAX_SKIPPED: u32 = 10_000;
+
+impl Iterator for Occurrences {
+    type Item = Occurrence;
+
+    fn next(&mut self) -> Option<Occurrence> {
+        if self.done {
+            return None;
+        }
+        let schedule = self.schedule;
+        let duration = schedule.duration as i64;
+        let Some(rule) = schedule.recurrence else {
+            self.done = true;
+            return Some(Occurrence {
+                start: schedule.start,
+                end: schedule.end(),
+            });
+        };
+        if rule.count != 0 && self.emitted >= rule.count {
+            self.done = true;
+            return None;
+        }
+
+        let weekdays = weekday_filter(&rule);
+        for _ in 0..MAX_SKIPPED {
+            let Some(local) = self.next_candidate(&rule) else {
+                break;
+            };
+            let filtered = rule.frequency != Frequency::Weekly
+                && !weekdays.is_empty()
+                && !weekdays.contains(DateTime::from_timestamp(local).weekday());
+            if local < self.local_start || filtered {
+                continue;
+            }
+            let start = schedule.local_to_utc(local);
+            if rule.until.is_some_and(|until| start > until) {
+                self.done = true;
+                return None;
+            }
+            // Only counted rules need the tally; it would overflow on long unbounded runs
+            if rule.count != 0 {
+                self.emitted += 1;
+            }
+            let Some(end) = start.checked_add(duration) else {
+                break;
+            };
+            return Some(Occurrence { start, end });
+        }
+        self.done = true;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(year: i64, month: u8, day: u8, hour: u8) -> i64 {
+        DateTime::new(year, month, day, hour, 0, 0)
+            .unwrap()
+            .to_timestamp()
+    }
+
+    fn starts(schedule: &SchedulePayload, n: usize) -> Vec<i64> {
+        schedule.occurrences().take(n).map(|o| o.start).collect()
+    }
+
+    #[test]
+    fn test_single_interval() {
+        // "between Monday and Friday"
+        let schedule = SchedulePayload::between(ts(2026, 10, 19, 0), ts(2026, 10, 24, 0)).unwrap();
+        let all: Vec<_> = schedule.occurrences().collect();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].end - all[0].start, 5 * SECONDS_PER_DAY);
+        assert!(schedule.occurrence_at(ts(2026, 10, 21, 12)).is_some());
+        assert!(schedule.occurrence_at(ts(2026, 10, 24, 0)).is_none());
+        assert!(SchedulePayload::between(10, 5).is_none());
+    }
+
+    #[test]
+    fn test_unbounded_daily() {
+        // More occurrences than a u16 count can hold
+        let schedule = SchedulePayload::once(0, 3600).daily(1);
+        let last = schedule.occurrences().take(70_000).last().unwrap();
+        assert_eq!(last.start, 69_999 * SECONDS_PER_DAY);
+        let far = 69_999 * SECONDS_PER_DAY + 60;
+        assert_eq!(schedule.occurrence_at(far), Some(last));
+    }
+
+    #[test]
+    fn test_daily_weekdays() {
+        // Starting on a Saturday: the first occurrence is the following Monday
+        let schedule = SchedulePayload::once(ts(2026, 10, 17, 9), 8 * 3600)
+            .daily(1)
+            .on(WeekdaySet::WEEKDAYS)
+            .count(6);
+        assert_eq!(
+            starts(&schedule, 10),
+            vec![
+                ts(2026, 10, 19, 9),
+                ts(2026, 10, 20, 9),
+                ts(2026, 10, 21, 9),
+                ts(2026, 10, 22, 9),
+                ts(2026, 10, 23, 9),
+                ts(2026, 10, 26, 9),
+            ]
+        );
+        assert!(schedule.occurrence_at(ts(2026, 10, 20, 16)).is_some());
+        assert!(schedule.occurrence_at(ts(2026, 10, 20, 17)).is_none());
+        assert!(schedule.occurrence_at(ts(2026, 10, 24, 10)).is_none());
+    }
+
+    #[test]
+    fn test_weekly_by_weekday() {
+        // Every other week on Tuesday and Thursday, from a Wednesday
+        let schedule = SchedulePayload::once(ts(2026, 10, 21, 18), 3600)
+            .weekly(2)
+            .on(WeekdaySet::single(Weekday::Tuesday).with(Weekday::Thursday));
+        assert_eq!(
+            starts(&schedule, 4),
+            vec![
+                ts(2026, 10, 22, 18),
+                ts(2026, 11, 3, 18),
+                ts(2026, 11, 5, 18),
+                ts(2026, 11, 17, 18),
+            ]
+        );
+
+        // Without a filter, weekly repeats on the start's weekday
+        let plain = SchedulePayload::once(ts(2026, 10, 21, 18), 0).weekly(1);
+        assert_eq!(
+            starts(&plain, 2),
+            vec![ts(2026, 10, 21, 18), ts(2026, 10, 28, 18)]
+        );
+    }
+
+    #[test]
+    fn test_monthly_until() {
+        let schedule = SchedulePayload::once(ts(2024, 1, 31, 12), 0)
+            .monthly(1)
+            .until(ts(2024, 4, 30, 12));
+        assert_eq!(
+            starts(&schedule, 10),
+            vec![
+                ts(2024, 1, 31, 12),
+                ts(2024, 2, 29, 12),
+                ts(2024, 3, 31, 12),
+                ts(2024, 4, 30, 12),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_local_time_across_dst() {
+        // 09:00 in New York is 13:00 UTC in summer and 14:00 UTC in winter
+        let schedule = SchedulePayload::once(ts(2024, 11, 1, 13), 3600)
+            .with_zone(ZoneId::NEW_YORK)
+            .daily(1)
+            .on(WeekdaySet::WEEKDAYS);
+        assert_eq!(
+            starts(&schedule, 2),
+            vec![ts(2024, 11, 1, 13), ts(2024, 11, 4, 14)]
+        );
+
+        let kolkata = SchedulePayload::once(ts(2024, 1, 1, 3), 0)
+            .with_offset_minutes(330)
+            .daily(1)
+            .on(WeekdaySet::single(Weekday::Monday));
+        // 08:30 IST Monday 2024-01-01, then the next Monday
+        assert_eq!(
+            starts(&kolkata, 2),
+            vec![ts(2024, 1, 1, 3), ts(2024, 1, 8, 3)]
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let schedules = [
+            SchedulePayload::once(1_000_000, 60),
+            SchedulePayload::once(-5, 0)
+                .with_zone(ZoneId::SYDNEY)
+                .weekly(3)
+                .on(WeekdaySet::WEEKEND)
+                .count(9)
+                .until(1_000_000),
+            SchedulePayload::once(7, 7)
+                .with_offset_minutes(-210)
+                .monthly(2),
+        ];
+        for schedule in schedules {
+            assert_eq!(
+                SchedulePayload::from_bytes(&schedule.to_bytes()),
+                Some(schedule)
+            );
+        }
+
+        let mut bytes = schedules[0].to_bytes();
+        bytes[16] = 9;
+        assert_eq!(SchedulePayload::from_bytes(&bytes), None);
+        assert_eq!(SchedulePayload::from_bytes(&bytes[..30]), None);
+    }
+
+    #[test]
+    fn test_zero_interval() {
+        let start = 1_700_000_000;
+        let bytes = SchedulePayload::once(start, 60).daily(0).to_bytes();
+        assert_eq!(SchedulePayload::from_bytes(&bytes), None);
+
+        // Built directly, a zero interval yields its first period and stops
+        let daily = SchedulePayload::once(start, 60).daily(0);
+        assert_eq!(starts(&daily, 5), vec![start]);
+        assert_eq!(daily.occurrence_at(start + 3 * SECONDS_PER_DAY), None);
+        let monthly = SchedulePayload::once(start, 60).monthly(0);
+        assert_eq!(starts(&monthly, 5), vec![start]);
+
+        // 2023-11-14 is a Tuesday: only that week's Tuesday and Friday
+        let weekly = SchedulePayload::once(start, 60)
+            .weekly(0)
+            .on(WeekdaySet::single(Weekday::Tuesday).with(Weekday::Friday));
+        assert_eq!(starts(&weekly, 5), vec![start, start + 3 * SECONDS_PER_DAY]);
+    }
+
+    #[test]
+    fn test_extreme_start() {
+        for start in [i64::MIN, i64::MAX] {
+            let mut bytes = SchedulePayload::once(start, u32::MAX)
+                .with_offset_minutes(840)
+                .weekly(1)
+                .to_bytes();
+            let decoded = SchedulePayload::from_bytes(&bytes).unwrap();
+            assert!(!decoded.to_string().is_empty());
+            assert!(decoded.occurrences().take(3).count() <= 3);
+            assert_eq!(decoded.occurrence_at(0), None);
+
+            for freq in [Frequency::Daily, Frequency::Monthly] {
+                bytes[16] = freq.to_byte();
+                let decoded = SchedulePayload::from_bytes(&bytes).unwrap();
+                assert!(decoded.occurrences().take(3).count() <= 3);
+            }
+        }
+        let last = SchedulePayload::once(i64::MAX, 0).daily(1);
+        assert_eq!(last.end(), i64::MAX);
+        assert!(last.occurrences().count() <= 1);
+    }
+
+    #[test]
+    fn test_validation() {
+        let base = SchedulePayload::once(1000, 60);
+        assert!(base.daily(1).validate().is_ok());
+        assert!(base.daily(0).validate().is_err());
+        assert!(base.daily(1).on(WeekdaySet(0x80)).validate().is_err());
+        assert!(base.daily(1).until(999).validate().is_err());
+        assert!(base.with_offset_minutes(-800).validate().is_err());
+        assert!(base.with_zone(ZoneId(0x7777)).validate().is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let schedule = SchedulePayload::once(ts(2026, 10, 19, 13), 8 * 3600)
+            .with_zone(ZoneId::NEW_YORK)
+            .daily(1)
+            .on(WeekdaySet::WEEKDAYS)
+            .count(10);
+        assert_eq!(
+            schedule.to_string(),
+            "2026-10-19T09:00:00-04:00/2026-10-19T17:00:00-04:00 America/New_York \
+             RRULE:FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR;COUNT=10"
+        );
+    }
+}
@@ -0,0 +1,539 @@
+//! Concurrent scheduling of `FORK` branches
+//!
+//! A [`Scheduler`] runs the branches of a [`Program`]'s fork groups on a pool
+//! of scoped std threads and merges their results under a [`MergePolicy`].
+//! How a branch runs is up to a [`BranchRunner`] (an in-process model, an
+//! RPC, a nested [`Vm`](crate::Vm)); the scheduler only needs it to be `Sync`.
+//!
+//! Results are collected in fork order regardless of which branch finishes
+//! first, so merges are deterministic:
+//!
+//! ```rust
+//! use frame_isa::scheduler::Scheduler;
+//! use frame_isa::vm::HandlerError;
+//! use frame_isa::{Action, ExtendedInstruction, Instruction, Program, Subject};
+//!
+//! let program = Program::from_instructions(&[
+//!     Instruction::simple(Action::FORK, Subject::trm_ref(1)),
+//!     Instruction::simple(Action::FORK, Subject::trm_ref(2)),
+//!     Instruction::simple(Action::FORK, Subject::trm_ref(3)),
+//!     Instruction::simple(Action::MERGE, Subject::NULL),
+//! ])
+//! .unwrap();
+//!
+//! // Models 1 and 3 agree, model 2 fails
+//! let router = |model_id: u8, _: &ExtendedInstruction| match model_id {
+//!     2 => Err(HandlerError::Failed("timeout".to_string())),
+//!     _ => Ok("weather"),
+//! };
+//!
+//! let vote = Scheduler::new(router).with_majority();
+//! let merged = vote.run_group(&program, &program.fork_groups()[0]).unwrap();
+//! assert_eq!(merged.output(), Some(&"weather"));
+//!
+//! let all = Scheduler::new(router);
+//! assert!(all.run(&program).is_err());
+//! ```
+//!
+//! Only [`MergePolicy::Majority`] compares outputs, so it is the only policy
+//! that needs them to be `PartialEq`; select it with
+//! [`with_majority`](Scheduler::with_majority), which supplies the comparison
+//! ([`with_policy`](Scheduler::with_policy) refuses it).
+//!
+//! Branches are not cancelled: every branch of a group runs to completion
+//! before the group is merged, even when the policy has already decided.
+
+use crate::program::{ForkGroup, Program};
+use crate::vm::HandlerError;
+use crate::ExtendedInstruction;
+use std::any::Any;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use thiserror::Error;
+
+/// Runs one `FORK` branch
+pub trait BranchRunner: Sync {
+    /// Branch result
+    type Output: Send;
+
+    /// Run model `model_id`, forked by `fork`
+    fn run_branch(
+        &self,
+        model_id: u8,
+        fork: &ExtendedInstruction,
+    ) -> Result<Self::Output, HandlerError>;
+}
+
+impl<F, O> BranchRunner for F
+where
+    F: Fn(u8, &ExtendedInstruction) -> Result<O, HandlerError> + Sync,
+    O: Send,
+{
+    type Output = O;
+
+    fn run_branch(&self, model_id: u8, fork: &ExtendedInstruction) -> Result<O, HandlerError> {
+        self(model_id, fork)
+    }
+}
+
+/// How a `MERGE` combines branch results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergePolicy {
+    /// Every branch must succeed; all outputs are kept
+    #[default]
+    All,
+    /// The first successful branch, in fork order, wins
+    FirstSuccess,
+    /// More than half of the branches must return the same output (set with
+    /// [`Scheduler::with_majority`], which supplies the comparison)
+    Majority,
+}
+
+/// Errors merging a fork group
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ScheduleError {
+    #[error("Branch TRM 0x{model_id:02X} forked at instruction {fork} failed: {error}")]
+    BranchFailed {
+        fork: usize,
+        model_id: u8,
+        error: HandlerError,
+    },
+
+    #[error("No branch merged at instruction {merge} succeeded")]
+    NoSuccess { merge: usize },
+
+    #[error("No majority among the branches merged at instruction {merge}")]
+    NoMajority { merge: usize },
+}
+
+/// Result of one branch
+#[derive(Debug, Clone, PartialEq)]
+pub struct BranchResult<O> {
+    /// Position of the `FORK`
+    pub fork: usize,
+    /// Model the branch ran
+    pub model_id: u8,
+    /// Output, or why the branch failed (panics become [`HandlerError::Failed`])
+    pub result: Result<O, HandlerError>,
+}
+
+/// Merged fork group
+#[derive(Debug, Clone, PartialEq)]
+pub struct Merged<O> {
+    /// Position of the `MERGE`
+    pub merge: usize,
+    /// Every branch result, in fork order
+    pub branches: Vec<BranchResult<O>>,
+    /// Positions in `branches` the policy selected
+    pub selected: Vec<usize>,
+}
+
+impl<O> Merged<O> {
+    /// Outputs of the selected branches, in fork order
+    pub fn outputs(&self) -> impl Iterator<Item = &O> {
+        self.selected
+            .iter()
+            .filter_map(|&i| self.branches[i].result.as_ref().ok())
+    }
+
+    /// Output of the first selected branch
+    pub fn output(&self) -> Option<&O> {
+        self.outputs().next()
+    }
+}
+
+/// Compares two branch outputs for [`MergePolicy::Majority`]
+type Agree<O> = fn(&O, &O) -> bool;
+
+/// Merge policy as run, with the comparison majority merges need
+enum Merge<O> {
+    All,
+    FirstSuccess,
+    Majority(Agree<O>),
+}
+
+// Derived impls would require `O: Clone` and `O: Debug`
+impl<O> Clone for Merge<O> {
+    fn clone(&self) -> Self {
+        match self {
+            Merge::All => Merge::All,
+            Merge::FirstSuccess => Merge::FirstSuccess,
+            Merge::Majority(agree) => Merge::Majority(*agree),
+        }
+    }
+}
+
+impl<O> fmt::Debug for Merge<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Merge::All => f.write_str("All"),
+            Merge::FirstSuccess => f.write_str("FirstSuccess"),
+            Merge::Majority(_) => f.write_str("Majority"),
+        }
+    }
+}
+
+/// Runs fork groups concurrently
+#[derive(Debug, Clone)]
+pub struct Scheduler<R: BranchRunner> {
+    runner: R,
+    merge: Merge<R::Output>,
+    max_threads: Option<usize>,
+}
+
+impl<R: BranchRunner> Scheduler<R>
+where
+    R::Output: PartialEq,
+{
+    /// Merge by [`MergePolicy::Majority`], comparing outputs with `==`
+    pub fn with_majority(mut self) -> Self {
+        self.merge = Merge::Majority(PartialEq::eq);
+        self
+    }
+}
+
+impl<R: BranchRunner> Scheduler<R> {
+    /// Create a scheduler with [`MergePolicy::All`] and one thread per branch
+    pub fn new(runner: R) -> Self {
+        Self {
+            runner,
+            merge: Merge::All,
+            max_threads: None,
+        }
+    }
+
+    /// Set a merge policy that does not compare outputs
+    ///
+    /// # Panics
+    ///
+    /// On [`MergePolicy::Majority`], which needs to compare outputs; set it
+    /// with [`with_majority`](Scheduler::with_majority) instead.
+    pub fn with_policy(mut self, policy: MergePolicy) -> Self {
+        self.merge = match policy {
+            MergePolicy::All => Merge::All,
+            MergePolicy::FirstSuccess => Merge::FirstSuccess,
+            MergePolicy::Majority => {
+                panic!("MergePolicy::Majority compares outputs; use Scheduler::with_majority")
+            }
+        };
+        self
+    }
+
+    /// Run at most `max` branches at once (at least one)
+    ///
+    /// The branches share a pool of `max` threads, each taking the next
+    /// branch as soon as it is free, so a slow branch holds up only its own
+    /// thread.
+    pub fn with_max_threads(mut self, max: usize) -> Self {
+        self.max_threads = Some(max.max(1));
+        self
+    }
+
+    /// Merge policy
+    pub fn policy(&self) -> MergePolicy {
+        match self.merge {
+            Merge::All => MergePolicy::All,
+            Merge::FirstSuccess => MergePolicy::FirstSuccess,
+            Merge::Majority(_) => MergePolicy::Majority,
+        }
+    }
+
+    /// Branch runner
+    pub fn runner(&self) -> &R {
+        &self.runner
+    }
+
+    /// Run every fork group of `program`, one group after another
+    pub fn run(&self, program: &Program) -> Result<Vec<Merged<R::Output>>, ScheduleError> {
+        program
+            .fork_groups()
+            .iter()
+            .map(|group| self.run_group(program, group))
+            .collect()
+    }
+
+    /// Run the branches of one fork group concurrently and merge them
+    pub fn run_group(
+        &self,
+        program: &Program,
+        group: &ForkGroup,
+    ) -> Result<Merged<R::Output>, ScheduleError> {
+        let branches = self.run_branches(program, group);
+        let selected = self.select(group, &branches)?;
+        Ok(Merged {
+            merge: group.merge,
+            branches,
+            selected,
+        })
+    }
+
+    fn run_branches(&self, program: &Program, group: &ForkGroup) -> Vec<BranchResult<R::Output>> {
+        let forks: Vec<(usize, u8)> = group
+            .forks
+            .iter()
+            .copied()
+            .zip(group.branches.iter().copied())
+            .collect();
+        let workers = self.max_threads.unwrap_or(forks.len()).min(forks.len());
+        let next = AtomicUsize::new(0);
+        let runner = &self.runner;
+        let work = || {
+            let mut done = Vec::new();
+            while let Some(&(fork, model_id)) = forks.get(next.fetch_add(1, Ordering::Relaxed)) {
+                let instr = &program.instructions()[fork];
+                let result =
+                    panic::catch_unwind(AssertUnwindSafe(|| runner.run_branch(model_id, instr)))
+                        .unwrap_or_else(|panic| {
+                            Err(HandlerError::Failed(format!(
+                                "branch panicked: {}",
+                                panic_message(&panic)
+                            )))
+                        });
+                done.push(BranchResult {
+                    fork,
+                    model_id,
+                    result,
+                });
+            }
+            done
+        };
+
+        let mut results: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers).map(|_| scope.spawn(work)).collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("branch panics are caught"))
+                .collect()
+        });
+        // Workers finish in any order; sorting restores fork order
+        results.sort_by_key(|branch| branch.fork);
+        results
+    }
+
+    /// Apply the merge policy, returning the selected branch positions
+    fn select(
+        &self,
+        group: &ForkGroup,
+        branches: &[BranchResult<R::Output>],
+    ) -> Result<Vec<usize>, ScheduleError> {
+        match self.merge {
+            Merge::All => {
+                let failed = branches
+                    .iter()
+                    .find_map(|b| b.result.as_ref().err().map(|error| (b, error)));
+                match failed {
+                    Some((branch, error)) => Err(ScheduleError::BranchFailed {
+                        fork: branch.fork,
+                        model_id: branch.model_id,
+                        error: error.clone(),
+                    }),
+                    None => Ok((0..branches.len()).collect()),
+                }
+            }
+            Merge::FirstSuccess => branches
+                .iter()
+                .position(|b| b.result.is_ok())
+                .map(|i| vec![i])
+                .ok_or(ScheduleError::NoSuccess { merge: group.merge }),
+            Merge::Majority(agree) => branches
+                .iter()
+                .filter_map(|b| b.result.as_ref().ok())
+                .map(|candidate| {
+                    (0..branches.len())
+                        .filter(|&i| {
+                            branches[i]
+                                .result
+                                .as_ref()
+                                .is_ok_and(|output| agree(output, candidate))
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .find(|agreeing| agreeing.len() * 2 > branches.len())
+                .ok_or(ScheduleError::NoMajority { merge: group.merge }),
+        }
+    }
+}
+
+fn panic_message(panic: &Box<dyn Any + Send>) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, Instruction, Subject};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn fork_program(models: &[u8]) -> Program {
+        let mut program: Vec<Instruction> = models
+            .iter()
+            .map(|&id| Instruction::simple(Action::FORK, Subject::trm_ref(id)))
+            .collect();
+        program.push(Instruction::simple(Action::MERGE, Subject::NULL));
+        Program::from_instructions(&program).unwrap()
+    }
+
+    /// Answers with the model ID; model 0 fails, model 9 panics; lower IDs finish later
+    fn answer(model_id: u8, _: &ExtendedInstruction) -> Result<u8, HandlerError> {
+        thread::sleep(Duration::from_millis(u64::from(
+            10u8.saturating_sub(model_id),
+        )));
+        match model_id {
+            0 => Err(HandlerError::Failed("no answer".to_string())),
+            9 => panic!("model 9 crashed"),
+            id => Ok(id),
+        }
+    }
+
+    #[test]
+    fn test_merge_all() {
+        let program = fork_program(&[1, 2, 3]);
+        let merged = Scheduler::new(answer).run(&program).unwrap();
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].merge, 3);
+        // Fork order, although model 3 finishes first
+        assert_eq!(
+            merged[0].outputs().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            merged[0]
+                .branches
+                .iter()
+                .map(|b| b.fork)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+
+        let program = fork_program(&[1, 0, 2]);
+        assert_eq!(
+            Scheduler::new(answer).run(&program),
+            Err(ScheduleError::BranchFailed {
+                fork: 1,
+                model_id: 0,
+                error: HandlerError::Failed("no answer".to_string())
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_first_success() {
+        let scheduler = Scheduler::new(answer).with_policy(MergePolicy::FirstSuccess);
+        let program = fork_program(&[0, 9, 4, 2]);
+        let merged = scheduler
+            .run_group(&program, &program.fork_groups()[0])
+            .unwrap();
+        assert_eq!(merged.output(), Some(&4));
+        assert_eq!(merged.selected, vec![2]);
+        assert_eq!(
+            merged.branches[1].result,
+            Err(HandlerError::Failed(
+                "branch panicked: model 9 crashed".to_string()
+            ))
+        );
+
+        let program = fork_program(&[0, 0]);
+        assert_eq!(
+            scheduler.run(&program),
+            Err(ScheduleError::NoSuccess { merge: 2 })
+        );
+    }
+
+    #[test]
+    fn test_merge_majority() {
+        let parity = |model_id: u8, _: &ExtendedInstruction| match model_id {
+            0 => Err(HandlerError::Unhandled),
+            id => Ok(id % 2),
+        };
+        let scheduler = Scheduler::new(parity).with_majority();
+
+        let program = fork_program(&[1, 2, 3, 5, 0]);
+        let merged = scheduler.run(&program).unwrap();
+        assert_eq!(merged[0].output(), Some(&1));
+        assert_eq!(merged[0].selected, vec![0, 2, 3]);
+
+        // Two of four is not a majority
+        let program = fork_program(&[1, 2, 3, 4]);
+        assert_eq!(
+            scheduler.run(&program),
+            Err(ScheduleError::NoMajority { merge: 4 })
+        );
+    }
+
+    #[test]
+    fn test_incomparable_outputs() {
+        /// Output without `PartialEq`
+        #[derive(Debug)]
+        struct Reply(u8);
+
+        let reply = |model_id: u8, _: &ExtendedInstruction| match model_id {
+            0 => Err(HandlerError::Unhandled),
+            id => Ok(Reply(id)),
+        };
+        let program = fork_program(&[0, 7, 8]);
+        let merged = Scheduler::new(reply)
+            .with_policy(MergePolicy::FirstSuccess)
+            .run(&program)
+            .unwrap();
+        assert_eq!(merged[0].output().map(|r| r.0), Some(7));
+        assert!(Scheduler::new(reply).run(&program).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "with_majority")]
+    fn test_policy_refuses_majority() {
+        let _ = Scheduler::new(answer).with_policy(MergePolicy::Majority);
+    }
+
+    #[test]
+    fn test_max_threads() {
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let runner = |model_id: u8, _: &ExtendedInstruction| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(5));
+            running.fetch_sub(1, Ordering::SeqCst);
+            Ok(model_id)
+        };
+        let program = fork_program(&[1, 2, 3, 4, 5]);
+        let merged = Scheduler::new(&runner)
+            .with_max_threads(2)
+            .run(&program)
+            .unwrap();
+        assert_eq!(
+            merged[0].outputs().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5]
+        );
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+
+        // A slow branch holds up only its own thread, not the branches after it
+        let finished = AtomicUsize::new(0);
+        let runner = |model_id: u8, _: &ExtendedInstruction| {
+            if model_id == 1 {
+                thread::sleep(Duration::from_millis(200));
+                return Ok(finished.load(Ordering::SeqCst));
+            }
+            finished.fetch_add(1, Ordering::SeqCst);
+            Ok(0)
+        };
+        let merged = Scheduler::new(&runner)
+            .with_max_threads(2)
+            .run(&program)
+            .unwrap();
+        assert_eq!(merged[0].output(), Some(&4));
+
+        // Programs without forks have nothing to schedule
+        let program =
+            Program::from_instructions(&[Instruction::simple(Action::GREET, Subject::USER)])
+                .unwrap();
+        assert!(Scheduler::new(&runner).run(&program).unwrap().is_empty());
+    }
+}
@@ -3,6 +3,7 @@
 //! Subjects identify the topic or entity being discussed. Each subject is a 2-byte
 //! code organized into categories by the high byte.
 
+use crate::InstructionError;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -84,6 +85,35 @@ impl Subject {
     /// Anxiety
     pub const ANXIETY: Self = Self(0x0502);
 
+    /// All named subjects, in code order
+    pub const ALL: [Self; 25] = [
+        Self::NULL,
+        Self::SELF,
+        Self::USER,
+        Self::CONTEXT,
+        Self::WEATHER,
+        Self::TIME,
+        Self::DATE,
+        Self::SCHEDULE,
+        Self::HEALTH,
+        Self::HELP,
+        Self::TIMEZONE,
+        Self::NUMBER,
+        Self::EQUATION,
+        Self::PHYSICS,
+        Self::CHEMISTRY,
+        Self::COMPUTER,
+        Self::SOFTWARE,
+        Self::HARDWARE,
+        Self::AI,
+        Self::API,
+        Self::DOCUMENTATION,
+        Self::CONCEPT,
+        Self::FEELINGS,
+        Self::STRESS,
+        Self::ANXIETY,
+    ];
+
     // ========== TRM References (0x0600-0x06FF) ==========
     /// Reference to another TRM model
     pub const TRM_REF_START: u16 = 0x0600;
@@ -94,6 +124,9 @@ impl Subject {
     pub const RAG_START: u16 = 0xE000;
     /// End of RAG reference range
     pub const RAG_END: u16 = 0xEFFF;
+    /// Largest document ID a RAG subject can address (larger IDs need a
+    /// [`RagAddress`](crate::rag::RagAddress) payload)
+    pub const MAX_RAG_DOC_ID: u16 = Self::RAG_END - Self::RAG_START;
 
     /// Create from raw u16 value
     #[inline]
@@ -167,6 +200,13 @@ impl Subject {
         self.0 >= 0x0500 && self.0 <= 0x05FF
     }
 
+    /// Look up a named subject by its [`name`](Self::name) (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|subject| subject.name().eq_ignore_ascii_case(name))
+    }
+
     /// Get the human-readable name for this subject
     pub fn name(&self) -> &'static str {
         match *self {
@@ -202,13 +242,28 @@ impl Subject {
     }
 
     /// Create a RAG reference for a given document ID
+    ///
+    /// IDs above [`MAX_RAG_DOC_ID`](Self::MAX_RAG_DOC_ID) are clamped to it; use
+    /// [`try_rag_ref`](Self::try_rag_ref) to reject them instead.
     #[inline]
     pub const fn rag_ref(doc_id: u16) -> Self {
         // Clamp to RAG range
-        let id = if doc_id > 0x0FFF { 0x0FFF } else { doc_id };
+        let id = if doc_id > Self::MAX_RAG_DOC_ID {
+            Self::MAX_RAG_DOC_ID
+        } else {
+            doc_id
+        };
         Self(Self::RAG_START + id)
     }
 
+    /// Create a RAG reference, failing if the document ID does not fit the subject range
+    pub fn try_rag_ref(doc_id: u64) -> Result<Self, InstructionError> {
+        match u16::try_from(doc_id) {
+            Ok(id) if id <= Self::MAX_RAG_DOC_ID => Ok(Self(Self::RAG_START + id)),
+            _ => Err(InstructionError::RagDocIdOutOfRange { doc_id }),
+        }
+    }
+
     /// Create a TRM reference for a given model ID
     #[inline]
     pub const fn trm_ref(model_id: u8) -> Self {
@@ -275,6 +330,16 @@ mod tests {
         assert!(Subject::STRESS.is_emotion());
     }
 
+    #[test]
+    fn test_from_name() {
+        for subject in Subject::ALL {
+            assert_eq!(Subject::from_name(subject.name()), Some(subject));
+        }
+        assert_eq!(Subject::from_name("weather"), Some(Subject::WEATHER));
+        assert_eq!(Subject::from_name("RAG_REF"), None);
+        assert_eq!(Subject::from_name("UNKNOWN"), None);
+    }
+
     #[test]
     fn test_rag_reference() {
         let rag_ref = Subject::rag_ref(0x0A3);
@@ -286,6 +351,27 @@ mod tests {
         assert_eq!(Subject::USER.rag_doc_id(), None);
     }
 
+    #[test]
+    fn test_try_rag_ref() {
+        assert_eq!(
+            Subject::try_rag_ref(0x0A3).unwrap(),
+            Subject::rag_ref(0x0A3)
+        );
+        assert_eq!(
+            Subject::try_rag_ref(0x0FFF).unwrap().rag_doc_id(),
+            Some(Subject::MAX_RAG_DOC_ID)
+        );
+
+        // The clamping constructor maps 5000 to 4095; the fallible one refuses
+        assert_eq!(Subject::rag_ref(5000).rag_doc_id(), Some(0x0FFF));
+        for doc_id in [0x1000, 5000, 20_000, u64::MAX] {
+            assert!(matches!(
+                Subject::try_rag_ref(doc_id),
+                Err(InstructionError::RagDocIdOutOfRange { doc_id: id }) if id == doc_id
+            ));
+        }
+    }
+
     #[test]
     fn test_trm_reference() {
         let trm_ref = Subject::trm_ref(5);
@@ -0,0 +1,752 @@
+//! Response templates for `TEMPLATE_LOAD` and `TEMPLATE_FILL`
+//!
+//! A [`TemplatePayload`] names a template by ID and binds its slots to
+//! values: literals, results of earlier instructions, or RAG documents.
+//! Templates live in a [`TemplateStore`]; a [`TemplateRenderer`] fetches them
+//! and fills the slots.
+//!
+//! Template bodies use `{slot}` placeholders (`{{` and `}}` for literal
+//! braces). Binding a slot more than once makes it a list, rendered according
+//! to the instruction's [`Modifier::format`]:
+//!
+//! ```rust
+//! use frame_isa::modifier::Format;
+//! use frame_isa::template::{MemoryTemplateStore, Template, TemplatePayload, TemplateRenderer};
+//! use frame_isa::{Action, ExtendedInstruction, Instruction, Modifier, Subject};
+//!
+//! let store: MemoryTemplateStore = [Template::new(7, "Hi {name}! Open: {items}")].into_iter().collect();
+//!
+//! let fill = TemplatePayload::new(7)
+//!     .with_literal("name", "Ada")
+//!     .with_literal("items", "invoices")
+//!     .with_result("items", 0);
+//! let renderer = TemplateRenderer::new(&store).with_result(0, "refunds");
+//!
+//! let prose = Instruction::new(Action::TEMPLATE_FILL, Subject::USER, Modifier::default());
+//! let instr = ExtendedInstruction::with_template(prose, fill.clone());
+//! assert_eq!(renderer.render_instruction(&instr).unwrap(), "Hi Ada! Open: invoices and refunds");
+//!
+//! let bulleted = Modifier::default().with_format(Format::Bulleted);
+//! let instr = ExtendedInstruction::with_template(Instruction::new(Action::TEMPLATE_FILL, Subject::USER, bulleted), fill);
+//! assert_eq!(
+//!     renderer.render_instruction(&instr).unwrap(),
+//!     "Hi Ada! Open: \n- invoices\n- refunds"
+//! );
+//! ```
+
+use crate::modifier::Format;
+use crate::rag::{truncate_utf8, Reader};
+use crate::resolver::{RagResolver, ResolveError};
+use crate::{ExtendedInstruction, InstructionError, Modifier, RagAddress};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use thiserror::Error;
+
+/// Maximum number of slot bindings in a payload
+pub const MAX_BINDINGS: usize = 32;
+/// Maximum slot name length in bytes
+pub const MAX_SLOT_LEN: usize = 64;
+/// Maximum literal value length in bytes
+pub const MAX_LITERAL_LEN: usize = 1024;
+
+/// Where a slot's value comes from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SlotSource {
+    /// Fixed text
+    Literal(String),
+    /// Output of the instruction at this program position
+    Result(u16),
+    /// Content of a RAG document
+    Document(RagAddress),
+}
+
+impl SlotSource {
+    const LITERAL: u8 = b'L';
+    const RESULT: u8 = b'R';
+    const DOCUMENT: u8 = b'D';
+}
+
+impl fmt::Display for SlotSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlotSource::Literal(value) => write!(f, "{:?}", value),
+            SlotSource::Result(index) => write!(f, "${}", index),
+            SlotSource::Document(address) => write!(f, "{}", address),
+        }
+    }
+}
+
+/// Value bound to a template slot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotBinding {
+    /// Slot name
+    pub slot: String,
+    /// Value source
+    pub source: SlotSource,
+}
+
+impl fmt::Display for SlotBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.slot, self.source)
+    }
+}
+
+/// Template reference with slot bindings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplatePayload {
+    /// Template ID in the store
+    pub template_id: u16,
+    /// Slot bindings, in order (repeated slots form lists)
+    pub bindings: Vec<SlotBinding>,
+}
+
+impl TemplatePayload {
+    /// Reference a template without bindings
+    pub fn new(template_id: u16) -> Self {
+        Self {
+            template_id,
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Bind a slot
+    pub fn with_binding(mut self, slot: impl Into<String>, source: SlotSource) -> Self {
+        self.bindings.push(SlotBinding {
+            slot: slot.into(),
+            source,
+        });
+        self
+    }
+
+    /// Bind a slot to fixed text
+    pub fn with_literal(self, slot: impl Into<String>, value: impl Into<String>) -> Self {
+        self.with_binding(slot, SlotSource::Literal(value.into()))
+    }
+
+    /// Bind a slot to the output of the instruction at `index`
+    pub fn with_result(self, slot: impl Into<String>, index: u16) -> Self {
+        self.with_binding(slot, SlotSource::Result(index))
+    }
+
+    /// Bind a slot to a RAG document's content
+    pub fn with_document(self, slot: impl Into<String>, address: RagAddress) -> Self {
+        self.with_binding(slot, SlotSource::Document(address))
+    }
+
+    /// Documents the bindings reference
+    pub fn documents(&self) -> impl Iterator<Item = RagAddress> + '_ {
+        self.bindings.iter().filter_map(|b| match b.source {
+            SlotSource::Document(address) => Some(address),
+            _ => None,
+        })
+    }
+
+    /// Instruction positions the bindings reference
+    pub fn results(&self) -> impl Iterator<Item = u16> + '_ {
+        self.bindings.iter().filter_map(|b| match b.source {
+            SlotSource::Result(index) => Some(index),
+            _ => None,
+        })
+    }
+
+    /// Check that slot names are identifiers and bindings fit the encoding limits
+    pub fn validate(&self) -> Result<(), InstructionError> {
+        let invalid = |msg: String| Err(InstructionError::InvalidPayload(msg));
+        if self.bindings.len() > MAX_BINDINGS {
+            return invalid(format!(
+                "Template payload has {} bindings (max {})",
+                self.bindings.len(),
+                MAX_BINDINGS
+            ));
+        }
+        for binding in &self.bindings {
+            if !is_slot_name(&binding.slot) {
+                return invalid(format!("Invalid template slot name {:?}", binding.slot));
+            }
+            if binding.slot.len() > MAX_SLOT_LEN {
+                return invalid(format!(
+                    "Template slot {:?} exceeds {} bytes",
+                    binding.slot, MAX_SLOT_LEN
+                ));
+            }
+            if let SlotSource::Literal(value) = &binding.source {
+                if value.len() > MAX_LITERAL_LEN {
+                    return invalid(format!(
+                        "Literal for template slot {:?} exceeds {} bytes",
+                        binding.slot, MAX_LITERAL_LEN
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Serialize to bytes: [TEMPLATE_ID:2][COUNT:1][BINDING]...
+    ///
+    /// Each binding is `[SLOT_LEN:1][SLOT][SOURCE:1]` followed by `[LEN:2][VALUE]`
+    /// (`L`iteral), `[INDEX:2]` (`R`esult) or `[COLLECTION:4][DOC_ID:8]`
+    /// (`D`ocument). Fields over the encoding limits are truncated; call
+    /// [`validate`](Self::validate) first to reject them instead.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(3 + self.bindings.len() * 16);
+        bytes.extend_from_slice(&self.template_id.to_be_bytes());
+        bytes.push(self.bindings.len().min(MAX_BINDINGS) as u8);
+        for binding in self.bindings.iter().take(MAX_BINDINGS) {
+            let slot = truncate_utf8(&binding.slot, MAX_SLOT_LEN);
+            bytes.push(slot.len() as u8);
+            bytes.extend_from_slice(slot.as_bytes());
+            match &binding.source {
+                SlotSource::Literal(value) => {
+                    let value = truncate_utf8(value, MAX_LITERAL_LEN);
+                    bytes.push(SlotSource::LITERAL);
+                    bytes.extend_from_slice(&(value.len() as u16).to_be_bytes());
+                    bytes.extend_from_slice(value.as_bytes());
+                }
+                SlotSource::Result(index) => {
+                    bytes.push(SlotSource::RESULT);
+                    bytes.extend_from_slice(&index.to_be_bytes());
+                }
+                SlotSource::Document(address) => {
+                    bytes.push(SlotSource::DOCUMENT);
+                    bytes.extend_from_slice(&address.to_bytes());
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Parse from bytes
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader(bytes);
+        let template_id = u16::from_be_bytes(reader.take(2)?.try_into().ok()?);
+        let count = reader.take(1)?[0] as usize;
+        let mut bindings = Vec::with_capacity(count);
+        for _ in 0..count {
+            let slot_len = reader.take(1)?[0] as usize;
+            let slot = reader.take_str(slot_len)?;
+            let source = match reader.take(1)?[0] {
+                SlotSource::LITERAL => {
+                    let len = u16::from_be_bytes(reader.take(2)?.try_into().ok()?) as usize;
+                    SlotSource::Literal(reader.take_str(len)?)
+                }
+                SlotSource::RESULT => {
+                    SlotSource::Result(u16::from_be_bytes(reader.take(2)?.try_into().ok()?))
+                }
+                SlotSource::DOCUMENT => {
+                    SlotSource::Document(RagAddress::from_bytes(reader.take(12)?)?)
+                }
+                _ => return None,
+            };
+            bindings.push(SlotBinding { slot, source });
+        }
+        Some(Self {
+            template_id,
+            bindings,
+        })
+    }
+}
+
+impl fmt::Display for TemplatePayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "template({})", self.template_id)?;
+        for binding in &self.bindings {
+            write!(f, " {}", binding)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check for an identifier: ASCII letters, digits and `_`, not starting with a digit
+fn is_slot_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Errors loading or rendering templates
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error("Instruction has no template payload")]
+    NotTemplate,
+
+    #[error("Template {0} not found")]
+    NotFound(u16),
+
+    #[error("Template {template_id}: {message}")]
+    Syntax { template_id: u16, message: String },
+
+    #[error("Template {template_id} has no slot {slot:?}")]
+    UnknownSlot { template_id: u16, slot: String },
+
+    #[error("Template {template_id}: slot {slot:?} is not bound")]
+    UnboundSlot { template_id: u16, slot: String },
+
+    #[error("Slot {slot:?}: no result for instruction {index}")]
+    MissingResult { slot: String, index: u16 },
+
+    #[error("Slot {slot:?}: no document resolver to fetch {address}")]
+    NoResolver { slot: String, address: RagAddress },
+
+    #[error("Slot {slot:?}: {error}")]
+    Document { slot: String, error: ResolveError },
+
+    #[error("Template store unavailable: {0}")]
+    Unavailable(String),
+}
+
+/// Piece of a parsed template body
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part<'a> {
+    Text(&'a str),
+    Slot(&'a str),
+}
+
+/// Template text with `{slot}` placeholders
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Template {
+    /// Template ID
+    pub id: u16,
+    /// Body text
+    pub body: String,
+}
+
+impl Template {
+    /// Create a template
+    pub fn new(id: u16, body: impl Into<String>) -> Self {
+        Self {
+            id,
+            body: body.into(),
+        }
+    }
+
+    /// Slot names in order of first appearance
+    pub fn slots(&self) -> Result<Vec<&str>, TemplateError> {
+        let mut slots: Vec<&str> = Vec::new();
+        for part in self.parse()? {
+            if let Part::Slot(name) = part {
+                if !slots.contains(&name) {
+                    slots.push(name);
+                }
+            }
+        }
+        Ok(slots)
+    }
+
+    fn parse(&self) -> Result<Vec<Part<'_>>, TemplateError> {
+        let syntax = |message: String| TemplateError::Syntax {
+            template_id: self.id,
+            message,
+        };
+        let body = self.body.as_str();
+        let mut parts = Vec::new();
+        let mut text_start = 0;
+        let mut pos = 0;
+        while pos < body.len() {
+            let rest = &body[pos..];
+            if rest.starts_with("{{") || rest.starts_with("}}") {
+                parts.push(Part::Text(&body[text_start..pos + 1]));
+                pos += 2;
+                text_start = pos;
+            } else if rest.starts_with('{') {
+                let end = rest
+                    .find('}')
+                    .ok_or_else(|| syntax(format!("unclosed placeholder at byte {}", pos)))?;
+                let name = &rest[1..end];
+                if !is_slot_name(name) {
+                    return Err(syntax(format!("invalid slot name {:?}", name)));
+                }
+                parts.push(Part::Text(&body[text_start..pos]));
+                parts.push(Part::Slot(name));
+                pos += end + 1;
+                text_start = pos;
+            } else if rest.starts_with('}') {
+                return Err(syntax(format!("unmatched '}}' at byte {}", pos)));
+            } else {
+                pos += rest.chars().next().map_or(1, char::len_utf8);
+            }
+        }
+        parts.push(Part::Text(&body[text_start..]));
+        parts.retain(|part| *part != Part::Text(""));
+        Ok(parts)
+    }
+}
+
+/// Source of templates
+pub trait TemplateStore {
+    /// Fetch template `id`
+    fn fetch(&self, id: u16) -> Result<Template, TemplateError>;
+}
+
+impl<S: TemplateStore + ?Sized> TemplateStore for &S {
+    fn fetch(&self, id: u16) -> Result<Template, TemplateError> {
+        (**self).fetch(id)
+    }
+}
+
+/// Templates held in memory
+#[derive(Debug, Clone, Default)]
+pub struct MemoryTemplateStore {
+    templates: HashMap<u16, Template>,
+}
+
+impl MemoryTemplateStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a template, returning the one it replaces
+    pub fn insert(&mut self, template: Template) -> Option<Template> {
+        self.templates.insert(template.id, template)
+    }
+
+    /// Remove a template
+    pub fn remove(&mut self, id: u16) -> Option<Template> {
+        self.templates.remove(&id)
+    }
+
+    /// Number of templates
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    /// Check if there are no templates
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+}
+
+impl FromIterator<Template> for MemoryTemplateStore {
+    fn from_iter<I: IntoIterator<Item = Template>>(iter: I) -> Self {
+        Self {
+            templates: iter.into_iter().map(|t| (t.id, t)).collect(),
+        }
+    }
+}
+
+impl TemplateStore for MemoryTemplateStore {
+    fn fetch(&self, id: u16) -> Result<Template, TemplateError> {
+        self.templates
+            .get(&id)
+            .cloned()
+            .ok_or(TemplateError::NotFound(id))
+    }
+}
+
+/// Fills templates from a store
+///
+/// Result bindings read the outputs registered with
+/// [`with_result`](Self::with_result); document bindings need a resolver.
+pub struct TemplateRenderer<'a> {
+    store: &'a dyn TemplateStore,
+    documents: Option<&'a dyn RagResolver>,
+    results: BTreeMap<u16, String>,
+}
+
+impl<'a> TemplateRenderer<'a> {
+    /// Create a renderer over `store`
+    pub fn new(store: &'a dyn TemplateStore) -> Self {
+        Self {
+            store,
+            documents: None,
+            results: BTreeMap::new(),
+        }
+    }
+
+    /// Resolve document bindings with `resolver`
+    pub fn with_documents(mut self, resolver: &'a dyn RagResolver) -> Self {
+        self.documents = Some(resolver);
+        self
+    }
+
+    /// Register the output of the instruction at `index`
+    pub fn with_result(mut self, index: u16, output: impl Into<String>) -> Self {
+        self.results.insert(index, output.into());
+        self
+    }
+
+    /// Register an output after construction
+    pub fn set_result(&mut self, index: u16, output: impl Into<String>) {
+        self.results.insert(index, output.into());
+    }
+
+    /// Render a `TEMPLATE_LOAD`/`TEMPLATE_FILL` instruction, formatted by its modifier
+    pub fn render_instruction(&self, instr: &ExtendedInstruction) -> Result<String, TemplateError> {
+        let payload = instr.as_template().ok_or(TemplateError::NotTemplate)?;
+        self.render(payload, instr.base.modifier)
+    }
+
+    /// Render a payload, formatting list slots per [`Modifier::format`]
+    ///
+    /// Slots bound once render their value as-is. Slots bound several times
+    /// render as a list: `a, b and c` for prose, one `- item` or `1. item`
+    /// line each for bulleted and numbered, and one item per line for
+    /// structured output.
+    pub fn render(
+        &self,
+        payload: &TemplatePayload,
+        modifier: Modifier,
+    ) -> Result<String, TemplateError> {
+        let template_id = payload.template_id;
+        let template = self.store.fetch(template_id)?;
+        let parts = template.parse()?;
+
+        let mut values: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for binding in &payload.bindings {
+            let known = parts.iter().any(|part| *part == Part::Slot(&binding.slot));
+            if !known {
+                return Err(TemplateError::UnknownSlot {
+                    template_id,
+                    slot: binding.slot.clone(),
+                });
+            }
+            let value = self.value(binding)?;
+            values.entry(&binding.slot).or_default().push(value);
+        }
+
+        let mut out = String::with_capacity(template.body.len());
+        for part in parts {
+            match part {
+                Part::Text(text) => out.push_str(text),
+                Part::Slot(slot) => {
+                    let items = values.get(slot).ok_or_else(|| TemplateError::UnboundSlot {
+                        template_id,
+                        slot: slot.to_string(),
+                    })?;
+                    out.push_str(&format_list(items, modifier.format()));
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn value(&self, binding: &SlotBinding) -> Result<String, TemplateError> {
+        let slot = || binding.slot.clone();
+        match &binding.source {
+            SlotSource::Literal(value) => Ok(value.clone()),
+            SlotSource::Result(index) => {
+                self.results
+                    .get(index)
+                    .cloned()
+                    .ok_or_else(|| TemplateError::MissingResult {
+                        slot: slot(),
+                        index: *index,
+                    })
+            }
+            SlotSource::Document(address) => {
+                let resolver = self.documents.ok_or_else(|| TemplateError::NoResolver {
+                    slot: slot(),
+                    address: *address,
+                })?;
+                resolver
+                    .fetch(*address)
+                    .map(|doc| doc.content)
+                    .map_err(|error| TemplateError::Document {
+                        slot: slot(),
+                        error,
+                    })
+            }
+        }
+    }
+}
+
+/// Render slot values in the requested format
+fn format_list(items: &[String], format: Format) -> String {
+    match (items, format) {
+        ([single], _) => single.clone(),
+        (_, Format::Prose) => match items.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+            None => String::new(),
+        },
+        (_, Format::Bulleted) => items.iter().map(|item| format!("\n- {}", item)).collect(),
+        (_, Format::Numbered) => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| format!("\n{}. {}", i + 1, item))
+            .collect(),
+        (_, Format::Structured) => items.iter().map(|item| format!("\n{}", item)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extended::PayloadType;
+    use crate::resolver::{Document, MemoryResolver};
+    use crate::{Action, Instruction, Subject};
+
+    fn sample() -> TemplatePayload {
+        TemplatePayload::new(0x0102)
+            .with_literal("name", "Zoë")
+            .with_result("total", 3)
+            .with_document("policy", RagAddress::new(2, 70_000))
+    }
+
+    #[test]
+    fn test_template_payload_roundtrip() {
+        let payload = sample();
+        assert_eq!(
+            TemplatePayload::from_bytes(&payload.to_bytes()),
+            Some(payload)
+        );
+        assert_eq!(
+            TemplatePayload::from_bytes(&TemplatePayload::new(9).to_bytes()),
+            Some(TemplatePayload::new(9))
+        );
+
+        let bytes = sample().to_bytes();
+        assert!(TemplatePayload::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+        let mut bad_source = bytes.clone();
+        bad_source[3 + 1 + "name".len()] = b'X';
+        assert!(TemplatePayload::from_bytes(&bad_source).is_none());
+
+        let base = Instruction::simple(Action::TEMPLATE_FILL, Subject::USER);
+        let ext = ExtendedInstruction::with_template(base, sample());
+        assert_eq!(ext.payload.payload_type(), PayloadType::Template);
+        let parsed = ExtendedInstruction::from_bytes(&ext.to_bytes()).unwrap();
+        assert_eq!(parsed, ext);
+        assert!(ext.needs_rag());
+        assert_eq!(
+            ext.to_string(),
+            format!(
+                "{} + template(258) name=\"Zoë\" total=$3 policy=rag://2/70000",
+                base
+            )
+        );
+    }
+
+    #[test]
+    fn test_template_validation() {
+        let fill = Instruction::simple(Action::TEMPLATE_FILL, Subject::USER);
+        assert!(ExtendedInstruction::with_template(fill, sample())
+            .validate()
+            .is_ok());
+
+        let bad_slot = TemplatePayload::new(1).with_literal("2nd", "x");
+        assert!(matches!(
+            ExtendedInstruction::with_template(fill, bad_slot).validate(),
+            Err(InstructionError::InvalidPayload(_))
+        ));
+        let too_long = TemplatePayload::new(1).with_literal("x", "y".repeat(MAX_LITERAL_LEN + 1));
+        assert!(too_long.validate().is_err());
+
+        // Only template actions carry template payloads; loads have no bindings
+        let greet = Instruction::simple(Action::GREET, Subject::USER);
+        assert!(matches!(
+            ExtendedInstruction::with_template(greet, TemplatePayload::new(1)).validate(),
+            Err(InstructionError::PayloadMismatch { .. })
+        ));
+        let load = Instruction::simple(Action::TEMPLATE_LOAD, Subject::USER);
+        assert!(
+            ExtendedInstruction::with_template(load, TemplatePayload::new(1))
+                .validate()
+                .is_ok()
+        );
+        assert!(ExtendedInstruction::with_template(load, sample())
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_template_slots() {
+        let template = Template::new(1, "{greeting}, {name}! {{literal}} {name}");
+        assert_eq!(template.slots().unwrap(), vec!["greeting", "name"]);
+        for bad in ["{unclosed", "{bad name}", "stray }"] {
+            assert!(matches!(
+                Template::new(2, bad).slots(),
+                Err(TemplateError::Syntax { template_id: 2, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let store: MemoryTemplateStore = [
+            Template::new(1, "Dear {name}, your total is {total}. {{ref}}"),
+            Template::new(2, "Policy: {policy}"),
+            Template::new(3, "Next steps:{steps}"),
+        ]
+        .into_iter()
+        .collect();
+        let documents =
+            MemoryResolver::from_iter([Document::new(RagAddress::document(7), "30 days")]);
+        let renderer = TemplateRenderer::new(&store)
+            .with_documents(&documents)
+            .with_result(4, "$12.50");
+
+        let payload = TemplatePayload::new(1)
+            .with_literal("name", "Ada")
+            .with_result("total", 4);
+        assert_eq!(
+            renderer.render(&payload, Modifier::default()).unwrap(),
+            "Dear Ada, your total is $12.50. {ref}"
+        );
+        let policy = TemplatePayload::new(2).with_document("policy", RagAddress::document(7));
+        assert_eq!(
+            renderer.render(&policy, Modifier::default()).unwrap(),
+            "Policy: 30 days"
+        );
+
+        let steps = ["call", "wait", "retry"]
+            .into_iter()
+            .fold(TemplatePayload::new(3), |p, step| {
+                p.with_literal("steps", step)
+            });
+        let render = |format| {
+            renderer
+                .render(&steps, Modifier::default().with_format(format))
+                .unwrap()
+        };
+        assert_eq!(render(Format::Prose), "Next steps:call, wait and retry");
+        assert_eq!(
+            render(Format::Bulleted),
+            "Next steps:\n- call\n- wait\n- retry"
+        );
+        assert_eq!(
+            render(Format::Numbered),
+            "Next steps:\n1. call\n2. wait\n3. retry"
+        );
+        assert_eq!(render(Format::Structured), "Next steps:\ncall\nwait\nretry");
+    }
+
+    #[test]
+    fn test_render_errors() {
+        let store = MemoryTemplateStore::from_iter([Template::new(1, "Hi {name}")]);
+        let renderer = TemplateRenderer::new(&store);
+        let render = |payload: TemplatePayload| renderer.render(&payload, Modifier::default());
+
+        assert_eq!(
+            render(TemplatePayload::new(9)),
+            Err(TemplateError::NotFound(9))
+        );
+        assert_eq!(
+            render(TemplatePayload::new(1)),
+            Err(TemplateError::UnboundSlot {
+                template_id: 1,
+                slot: "name".to_string()
+            })
+        );
+        assert!(matches!(
+            render(TemplatePayload::new(1).with_literal("nmae", "Ada")),
+            Err(TemplateError::UnknownSlot { .. })
+        ));
+        assert!(matches!(
+            render(TemplatePayload::new(1).with_result("name", 0)),
+            Err(TemplateError::MissingResult { index: 0, .. })
+        ));
+        assert!(matches!(
+            render(TemplatePayload::new(1).with_document("name", RagAddress::document(1))),
+            Err(TemplateError::NoResolver { .. })
+        ));
+
+        let greet = ExtendedInstruction::new(Instruction::simple(Action::GREET, Subject::USER));
+        assert_eq!(
+            renderer.render_instruction(&greet),
+            Err(TemplateError::NotTemplate)
+        );
+    }
+}
@@ -0,0 +1,657 @@
+//! Execution traces: record, serialize, diff and replay
+//!
+//! [`Vm::run_traced`] records every executed instruction with its decoded
+//! payload, its chain depth, the outcome (handler output, exit or error) and
+//! how long it took. Instructions of chained programs follow the `CHAIN` that
+//! loaded them, one level deeper.
+//! Traces are serde-serializable, so a production run can be saved and
+//! replayed later without the original handlers:
+//!
+//! ```rust
+//! use frame_isa::trace::Trace;
+//! use frame_isa::vm::{Handler, HandlerResult, Vm};
+//! use frame_isa::{Action, ExtendedInstruction, Instruction, Subject};
+//!
+//! struct Echo;
+//!
+//! impl Handler for Echo {
+//!     type Output = String;
+//!
+//!     fn response(&mut self, instr: &ExtendedInstruction) -> HandlerResult<String> {
+//!         Ok(Some(instr.base.action.name().to_string()))
+//!     }
+//! }
+//!
+//! let program = [ExtendedInstruction::new(Instruction::simple(Action::GREET, Subject::USER))];
+//! let (result, trace) = Vm::new(Echo).run_traced(&program);
+//! assert!(result.is_ok());
+//!
+//! // Replay against stub handlers that return the recorded outcomes
+//! let replayed: Trace<String> = trace.replay();
+//! assert!(trace.diff(&replayed).is_empty());
+//! ```
+//!
+//! Diffs compare instructions and outcomes; timings are informational and
+//! never count as a difference. Replays run under the recorded [`Limits`], so
+//! they reproduce every limit error except a passed deadline.
+
+use crate::limits::Limits;
+use crate::vm::{
+    Event, Execution, ExitStatus, Handler, HandlerError, HandlerResult, Step, Vm, VmError,
+};
+use crate::{Action, ExtendedInstruction};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What happened when an instruction executed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TraceOutcome<O> {
+    /// The handler produced an output
+    Output(O),
+    /// The instruction ran without calling a handler (`NOP`)
+    Empty,
+    /// The handler ran and returned no output
+    NoOutput,
+    /// The run stopped (`HALT` or `ERROR`)
+    Exit(ExitStatus),
+    /// A `CHAIN` loaded a `len`-instruction program from model `model_id`
+    Chained { model_id: u8, len: usize },
+    /// The run failed
+    Error(VmError),
+}
+
+impl<O> TraceOutcome<O> {
+    /// Check if this outcome came from a handler call
+    pub fn reached_handler(&self) -> bool {
+        match self {
+            TraceOutcome::Output(_) | TraceOutcome::NoOutput | TraceOutcome::Chained { .. } => true,
+            TraceOutcome::Empty | TraceOutcome::Exit(_) => false,
+            TraceOutcome::Error(e) => {
+                matches!(e, VmError::Handler { .. } | VmError::Unhandled { .. })
+            }
+        }
+    }
+}
+
+/// One executed instruction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceEntry<O> {
+    /// Position in the program
+    pub index: usize,
+    /// Chain depth (0 for the top-level program)
+    pub depth: usize,
+    /// Instruction with its decoded payload
+    pub instruction: ExtendedInstruction,
+    /// What happened
+    pub outcome: TraceOutcome<O>,
+    /// Time spent executing the instruction
+    pub elapsed_micros: u64,
+}
+
+impl<O: Clone> TraceEntry<O> {
+    fn record(event: Event<'_, O>) -> Self {
+        let outcome = match event.result {
+            Ok(Step::Continue(Some(output))) => TraceOutcome::Output(output.clone()),
+            Ok(Step::Continue(None)) if event.instr.base.action == Action::NOP => {
+                TraceOutcome::Empty
+            }
+            Ok(Step::Continue(None)) => TraceOutcome::NoOutput,
+            Ok(Step::Exit(status)) => TraceOutcome::Exit(*status),
+            Ok(Step::Chain { model_id, program }) => TraceOutcome::Chained {
+                model_id: *model_id,
+                len: program.len(),
+            },
+            Err(e) => TraceOutcome::Error(e.clone()),
+        };
+        Self {
+            index: event.index,
+            depth: event.depth,
+            instruction: event.instr.clone(),
+            outcome,
+            elapsed_micros: u64::try_from(event.elapsed.as_micros()).unwrap_or(u64::MAX),
+        }
+    }
+}
+
+/// Record of a program run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trace<O> {
+    /// Executed instructions, in order
+    pub entries: Vec<TraceEntry<O>>,
+    /// Limits the run was under
+    pub limits: Limits,
+}
+
+impl<O> Default for Trace<O> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            limits: Limits::default(),
+        }
+    }
+}
+
+impl<O> Trace<O> {
+    /// Create an empty trace
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of executed instructions
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if nothing was executed
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// How the run ended
+    pub fn status(&self) -> Result<ExitStatus, &VmError> {
+        let Some(last) = self.entries.last() else {
+            return Ok(ExitStatus::Completed);
+        };
+        match &last.outcome {
+            TraceOutcome::Error(e) => Err(e),
+            TraceOutcome::Exit(status @ ExitStatus::Error { .. }) => Ok(*status),
+            // A HALT in a chained program only returns to the caller
+            TraceOutcome::Exit(status) if last.depth == 0 => Ok(*status),
+            _ => Ok(ExitStatus::Completed),
+        }
+    }
+
+    /// Handler outputs, in order
+    pub fn outputs(&self) -> impl Iterator<Item = &O> {
+        self.entries
+            .iter()
+            .filter_map(|entry| match &entry.outcome {
+                TraceOutcome::Output(output) => Some(output),
+                _ => None,
+            })
+    }
+
+    /// Executed top-level instructions, in order
+    pub fn program(&self) -> Vec<ExtendedInstruction> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.depth == 0)
+            .map(|entry| entry.instruction.clone())
+            .collect()
+    }
+
+    /// Total time spent executing
+    pub fn elapsed_micros(&self) -> u64 {
+        self.entries.iter().fold(0u64, |total, entry| {
+            total.saturating_add(entry.elapsed_micros)
+        })
+    }
+}
+
+impl<O: PartialEq> Trace<O> {
+    /// Differences from `other`, ignoring timings
+    ///
+    /// An entry whose instructions differ is reported once, without comparing
+    /// its outcomes.
+    pub fn diff<'a>(&'a self, other: &'a Self) -> Vec<TraceDiff<'a, O>> {
+        let mut diffs = Vec::new();
+        for (index, (expected, actual)) in self.entries.iter().zip(&other.entries).enumerate() {
+            if (expected.depth, &expected.instruction) != (actual.depth, &actual.instruction) {
+                diffs.push(TraceDiff::Instruction {
+                    index,
+                    expected: &expected.instruction,
+                    actual: &actual.instruction,
+                });
+            } else if expected.outcome != actual.outcome {
+                diffs.push(TraceDiff::Outcome {
+                    index,
+                    expected: &expected.outcome,
+                    actual: &actual.outcome,
+                });
+            }
+        }
+        let common = self.len().min(other.len());
+        diffs.extend(
+            self.entries[common..]
+                .iter()
+                .map(|entry| TraceDiff::Missing { expected: entry }),
+        );
+        diffs.extend(
+            other.entries[common..]
+                .iter()
+                .map(|entry| TraceDiff::Extra { actual: entry }),
+        );
+        diffs
+    }
+}
+
+impl<O: Clone> Trace<O> {
+    /// Re-run the recorded program against stubs returning the recorded outcomes
+    pub fn replay(&self) -> Trace<O> {
+        self.replay_with(ReplayHandler::new(self))
+    }
+
+    /// Re-run the recorded program against another handler, under the recorded limits
+    ///
+    /// [`diff`](Self::diff) the result against this trace to see where the
+    /// handler's behavior changed.
+    pub fn replay_with<H: Handler<Output = O>>(&self, handler: H) -> Trace<O> {
+        Vm::new(handler)
+            .with_limits(self.limits)
+            .run_traced(&self.program())
+            .1
+    }
+}
+
+/// Difference between two traces
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceDiff<'a, O> {
+    /// Different instructions at the same position
+    Instruction {
+        index: usize,
+        expected: &'a ExtendedInstruction,
+        actual: &'a ExtendedInstruction,
+    },
+    /// Same instruction, different outcome
+    Outcome {
+        index: usize,
+        expected: &'a TraceOutcome<O>,
+        actual: &'a TraceOutcome<O>,
+    },
+    /// Executed only in the expected trace
+    Missing { expected: &'a TraceEntry<O> },
+    /// Executed only in the actual trace
+    Extra { actual: &'a TraceEntry<O> },
+}
+
+impl<O: fmt::Debug> fmt::Display for TraceDiff<'_, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceDiff::Instruction {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "instruction {}: expected {}, got {}",
+                index, expected, actual
+            ),
+            TraceDiff::Outcome {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "instruction {}: expected {:?}, got {:?}",
+                index, expected, actual
+            ),
+            TraceDiff::Missing { expected } => write!(
+                f,
+                "instruction {}: {} was not executed",
+                expected.index, expected.instruction
+            ),
+            TraceDiff::Extra { actual } => write!(
+                f,
+                "instruction {}: {} was executed unexpectedly",
+                actual.index, actual.instruction
+            ),
+        }
+    }
+}
+
+/// Stub handler returning the outcomes recorded in a trace
+///
+/// Each call must receive the instruction recorded for it; a different
+/// instruction (or a call beyond the recording) fails with
+/// [`HandlerError::Failed`]. Recorded chains load the recorded programs.
+#[derive(Debug, Clone)]
+pub struct ReplayHandler<'a, O> {
+    entries: &'a [TraceEntry<O>],
+    /// Positions in `entries` of handler calls
+    calls: Vec<usize>,
+    next: usize,
+}
+
+impl<'a, O: Clone> ReplayHandler<'a, O> {
+    /// Create a stub from the handler calls in `trace`
+    pub fn new(trace: &'a Trace<O>) -> Self {
+        Self {
+            entries: &trace.entries,
+            calls: trace
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.outcome.reached_handler())
+                .map(|(position, _)| position)
+                .collect(),
+            next: 0,
+        }
+    }
+
+    /// Number of recorded calls not yet replayed
+    pub fn remaining(&self) -> usize {
+        self.calls.len() - self.next
+    }
+
+    /// Next recorded call, if it is for `instr`
+    fn peek(&self, instr: &ExtendedInstruction) -> Option<(usize, &'a TraceEntry<O>)> {
+        let position = *self.calls.get(self.next)?;
+        let entry = &self.entries[position];
+        (entry.instruction == *instr).then_some((position, entry))
+    }
+
+    fn replay(&mut self, instr: &ExtendedInstruction) -> HandlerResult<O> {
+        let Some(&position) = self.calls.get(self.next) else {
+            return Err(HandlerError::Failed(
+                "no recorded handler call left".to_string(),
+            ));
+        };
+        let entry = &self.entries[position];
+        self.next += 1;
+        if entry.instruction != *instr {
+            return Err(HandlerError::Failed(format!(
+                "diverged from trace: recorded {} at instruction {}",
+                entry.instruction, entry.index
+            )));
+        }
+        match &entry.outcome {
+            TraceOutcome::Output(output) => Ok(Some(output.clone())),
+            TraceOutcome::Error(VmError::Handler { message, .. }) => {
+                Err(HandlerError::Failed(message.clone()))
+            }
+            TraceOutcome::Error(VmError::Unhandled { .. }) => Err(HandlerError::Unhandled),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl<O: Clone> Handler for ReplayHandler<'_, O> {
+    type Output = O;
+
+    fn system(&mut self, instr: &ExtendedInstruction) -> HandlerResult<O> {
+        self.replay(instr)
+    }
+    fn response(&mut self, instr: &ExtendedInstruction) -> HandlerResult<O> {
+        self.replay(instr)
+    }
+    fn query(&mut self, instr: &ExtendedInstruction) -> HandlerResult<O> {
+        self.replay(instr)
+    }
+    fn knowledge(&mut self, instr: &ExtendedInstruction) -> HandlerResult<O> {
+        self.replay(instr)
+    }
+    fn skill(&mut self, instr: &ExtendedInstruction) -> HandlerResult<O> {
+        self.replay(instr)
+    }
+    fn emotion(&mut self, instr: &ExtendedInstruction) -> HandlerResult<O> {
+        self.replay(instr)
+    }
+    fn template(&mut self, instr: &ExtendedInstruction) -> HandlerResult<O> {
+        self.replay(instr)
+    }
+    fn chain(&mut self, instr: &ExtendedInstruction) -> HandlerResult<O> {
+        self.replay(instr)
+    }
+
+    fn load_chain(
+        &mut self,
+        _model_id: u8,
+        instr: &ExtendedInstruction,
+    ) -> Result<Option<Vec<ExtendedInstruction>>, HandlerError> {
+        let Some((position, entry)) = self.peek(instr) else {
+            return Ok(None);
+        };
+        let program = match &entry.outcome {
+            TraceOutcome::Chained { .. } => self.entries[position + 1..]
+                .iter()
+                .take_while(|nested| nested.depth > entry.depth)
+                .filter(|nested| nested.depth == entry.depth + 1)
+                .map(|nested| nested.instruction.clone())
+                .collect(),
+            _ => return Ok(None),
+        };
+        self.next += 1;
+        Ok(Some(program))
+    }
+}
+
+impl<H: Handler> Vm<H>
+where
+    H::Output: Clone,
+{
+    /// [`run`](Vm::run) a program, recording a [`Trace`]
+    ///
+    /// The trace is returned even when the run fails; its last entry holds the error.
+    #[allow(clippy::type_complexity)]
+    pub fn run_traced(
+        &mut self,
+        program: &[ExtendedInstruction],
+    ) -> (Result<Execution<H::Output>, VmError>, Trace<H::Output>) {
+        let mut trace = Trace {
+            entries: Vec::new(),
+            limits: *self.limits(),
+        };
+        let result = self.execute(program, &mut |event| {
+            trace.entries.push(TraceEntry::record(event))
+        });
+        (result, trace)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extended::{CalcPayload, Op};
+    use crate::limits::LimitExceeded;
+    use crate::{Instruction, Subject};
+
+    /// Evaluates calc payloads, optionally rounding results; model 1 doubles
+    /// 21 and halts, model 2 chains to itself
+    struct Calculator {
+        round: bool,
+    }
+
+    impl Handler for Calculator {
+        type Output = f64;
+
+        fn skill(&mut self, instr: &ExtendedInstruction) -> HandlerResult<f64> {
+            let calc = instr.as_calc().ok_or(HandlerError::Unhandled)?;
+            let value = calc
+                .evaluate()
+                .map_err(|e| HandlerError::Failed(e.to_string()))?;
+            Ok(Some(if self.round { value.round() } else { value }))
+        }
+
+        fn emotion(&mut self, _: &ExtendedInstruction) -> HandlerResult<f64> {
+            Ok(None)
+        }
+
+        fn load_chain(
+            &mut self,
+            model_id: u8,
+            _: &ExtendedInstruction,
+        ) -> Result<Option<Vec<ExtendedInstruction>>, HandlerError> {
+            Ok(match model_id {
+                1 => Some(vec![calc(Op::Mul, 21.0, 2.0), halt()]),
+                2 => Some(vec![chain(2)]),
+                _ => None,
+            })
+        }
+    }
+
+    fn halt() -> ExtendedInstruction {
+        ExtendedInstruction::new(Instruction::simple(Action::HALT, Subject::NULL))
+    }
+
+    fn chain(model_id: u8) -> ExtendedInstruction {
+        ExtendedInstruction::new(Instruction::simple(
+            Action::CHAIN,
+            Subject::trm_ref(model_id),
+        ))
+    }
+
+    fn calc(op: Op, a: f64, b: f64) -> ExtendedInstruction {
+        ExtendedInstruction::with_calc(
+            Instruction::simple(Action::CALCULATE, Subject::NUMBER),
+            CalcPayload::new(op, a, b),
+        )
+    }
+
+    fn program() -> Vec<ExtendedInstruction> {
+        vec![
+            calc(Op::Div, 7.0, 2.0),
+            ExtendedInstruction::new(Instruction::simple(Action::NOP, Subject::NULL)),
+            calc(Op::Mul, 6.0, 7.0),
+            halt(),
+            calc(Op::Add, 1.0, 1.0),
+        ]
+    }
+
+    #[test]
+    fn test_record_trace() {
+        let (result, trace) = Vm::new(Calculator { round: false }).run_traced(&program());
+        let execution = result.unwrap();
+        assert_eq!(execution.outputs, vec![3.5, 42.0]);
+        assert_eq!(trace.len(), 4);
+        assert_eq!(
+            trace.outputs().copied().collect::<Vec<_>>(),
+            vec![3.5, 42.0]
+        );
+        assert_eq!(
+            trace.status(),
+            Ok(ExitStatus::Halted { index: 3, depth: 0 })
+        );
+        assert_eq!(trace.entries[1].outcome, TraceOutcome::Empty);
+        assert_eq!(trace.entries[0].instruction.as_calc().unwrap().a, 7.0);
+        assert_eq!(trace.program(), program()[..4].to_vec());
+    }
+
+    #[test]
+    fn test_replay_handler_without_output() {
+        let empathy = ExtendedInstruction::new(Instruction::simple(Action::EMPATHY, Subject::USER));
+        let program = [empathy, calc(Op::Add, 1.0, 2.0), halt()];
+        let (result, trace) = Vm::new(Calculator { round: false }).run_traced(&program);
+        assert_eq!(result.unwrap().outputs, vec![3.0]);
+        assert_eq!(trace.entries[0].outcome, TraceOutcome::NoOutput);
+        assert!(trace.entries[0].outcome.reached_handler());
+
+        let replayed = trace.replay();
+        assert!(trace.diff(&replayed).is_empty());
+        assert_eq!(replayed.outputs().copied().collect::<Vec<_>>(), vec![3.0]);
+    }
+
+    #[test]
+    fn test_trace_serialization() {
+        let (_, trace) = Vm::new(Calculator { round: false }).run_traced(&program());
+        let json = serde_json::to_string(&trace).unwrap();
+        let parsed: Trace<f64> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, trace);
+        assert!(parsed.diff(&trace).is_empty());
+    }
+
+    #[test]
+    fn test_replay_and_diff() {
+        let (_, trace) = Vm::new(Calculator { round: false }).run_traced(&program());
+
+        let replayed = trace.replay();
+        assert!(trace.diff(&replayed).is_empty());
+        assert_eq!(
+            replayed.status(),
+            Ok(ExitStatus::Halted { index: 3, depth: 0 })
+        );
+
+        // A changed handler shows up as an outcome difference
+        let changed = trace.replay_with(Calculator { round: true });
+        let diffs = trace.diff(&changed);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(
+            diffs[0].to_string(),
+            "instruction 0: expected Output(3.5), got Output(4.0)"
+        );
+
+        // Missing and extra entries
+        let mut shorter = trace.clone();
+        shorter.entries.truncate(2);
+        let diffs = trace.diff(&shorter);
+        assert!(matches!(
+            diffs[..],
+            [TraceDiff::Missing { .. }, TraceDiff::Missing { .. }]
+        ));
+        assert!(matches!(shorter.diff(&trace)[0], TraceDiff::Extra { .. }));
+    }
+
+    #[test]
+    fn test_replay_failures() {
+        let failing = [calc(Op::Div, 1.0, 0.0)];
+        let (result, trace) = Vm::new(Calculator { round: false }).run_traced(&failing);
+        let err = result.unwrap_err();
+        assert_eq!(trace.status(), Err(&err));
+
+        // Recorded failures replay as failures
+        let replayed = trace.replay();
+        assert!(trace.diff(&replayed).is_empty());
+
+        // Stubs refuse instructions that diverge from the recording
+        let mut stub = ReplayHandler::new(&trace);
+        assert_eq!(stub.remaining(), 1);
+        let run = Vm::new(&mut stub).run(&[calc(Op::Div, 1.0, 3.0)]);
+        assert!(
+            matches!(run, Err(VmError::Handler { message, .. }) if message.starts_with("diverged"))
+        );
+        assert_eq!(stub.remaining(), 0);
+        assert!(Vm::new(&mut stub).run(&[calc(Op::Add, 1.0, 1.0)]).is_err());
+    }
+
+    #[test]
+    fn test_chained_trace() {
+        let program = [calc(Op::Add, 1.0, 2.0), chain(1), calc(Op::Sub, 5.0, 1.0)];
+        let (result, trace) = Vm::new(Calculator { round: false }).run_traced(&program);
+        assert_eq!(result.unwrap().outputs, vec![3.0, 42.0, 4.0]);
+        assert_eq!(
+            trace
+                .entries
+                .iter()
+                .map(|entry| (entry.index, entry.depth))
+                .collect::<Vec<_>>(),
+            vec![(0, 0), (1, 0), (0, 1), (1, 1), (2, 0)]
+        );
+        assert_eq!(
+            trace.entries[1].outcome,
+            TraceOutcome::Chained {
+                model_id: 1,
+                len: 2
+            }
+        );
+        // The nested HALT only ended the chained program
+        assert_eq!(trace.status(), Ok(ExitStatus::Completed));
+        assert_eq!(trace.program(), program.to_vec());
+
+        let replayed = trace.replay();
+        assert!(trace.diff(&replayed).is_empty());
+        assert!(trace
+            .diff(&trace.replay_with(Calculator { round: false }))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_chain_limit_trace() {
+        let limits = Limits::default().with_max_chain_depth(3);
+        let mut vm = Vm::new(Calculator { round: false }).with_limits(limits);
+        let (result, trace) = vm.run_traced(&[chain(2)]);
+        let err = result.unwrap_err();
+        assert!(matches!(
+            err,
+            VmError::LimitExceeded {
+                limit: LimitExceeded::ChainDepth { max: 3 },
+                ..
+            }
+        ));
+        assert_eq!(trace.len(), 4);
+        assert_eq!(trace.status(), Err(&err));
+        assert_eq!(trace.limits, limits);
+
+        // Replays run under the recorded limits
+        let replayed = trace.replay();
+        assert!(trace.diff(&replayed).is_empty());
+    }
+}
@@ -0,0 +1,577 @@
+//! TRM model registry and linker
+//!
+//! [`Subject::trm_ref`] only carries a `u8` model ID. A [`TrmRegistry`] maps
+//! those IDs to models: name, version, the contract of what each model
+//! accepts and emits, and capability tags. The linker checks that every
+//! `trm_ref` in a program points at a registered model.
+//!
+//! Registries load from a small config format:
+//!
+//! ```text
+//! # models.trm
+//! [model 3]
+//! name = weather
+//! version = 1.2.0
+//! inputs = None, Time
+//! actions = RESPOND, EXPLAIN
+//! payloads = ZonedTime
+//! capabilities = weather, forecast
+//! ```
+//!
+//! `inputs` and `payloads` take [`PayloadType`] names (or `0x..` type bytes),
+//! `actions` takes [`Action`] names. Model IDs may be decimal or `0x..` hex.
+//!
+//! ```rust
+//! use frame_isa::trm::TrmRegistry;
+//! use frame_isa::{Action, Instruction, Subject};
+//!
+//! let registry = TrmRegistry::from_config("[model 3]\nname = weather\nversion = 1.2.0\n").unwrap();
+//! assert_eq!(registry.resolve(Subject::trm_ref(3)).unwrap().name, "weather");
+//!
+//! let program = [
+//!     Instruction::simple(Action::CHAIN, Subject::trm_ref(3)),
+//!     Instruction::simple(Action::CHAIN, Subject::trm_ref(9)),
+//! ];
+//! let err = registry.link(&program).unwrap_err();
+//! assert_eq!(err.to_string(), "1 unresolved TRM reference(s): model 0x09 at instruction 1");
+//! ```
+
+use crate::{Action, ExtendedInstruction, Instruction, PayloadType, Subject};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+use thiserror::Error;
+
+/// Errors building a registry or linking a program against it
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TrmError {
+    #[error("TRM model 0x{0:02X} is already registered")]
+    DuplicateId(u8),
+
+    #[error("TRM model name {0:?} is already registered")]
+    DuplicateName(String),
+
+    #[error("Unknown TRM model 0x{0:02X}")]
+    UnknownModel(u8),
+
+    #[error("TRM config line {line}: {message}")]
+    Config { line: usize, message: String },
+
+    #[error("Cannot read TRM config: {0}")]
+    Io(String),
+
+    #[error("{} unresolved TRM reference(s): {}", .0.len(), join(.0))]
+    Unresolved(Vec<UnresolvedRef>),
+
+    #[error("TRM model 0x{model_id:02X}, instruction {index}: {message}")]
+    Contract {
+        model_id: u8,
+        index: usize,
+        message: String,
+    },
+}
+
+fn join(refs: &[UnresolvedRef]) -> String {
+    refs.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A `trm_ref` that names no registered model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct UnresolvedRef {
+    /// Position of the instruction in the program
+    pub index: usize,
+    /// Referenced model ID
+    pub model_id: u8,
+}
+
+impl fmt::Display for UnresolvedRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "model 0x{:02X} at instruction {}",
+            self.model_id, self.index
+        )
+    }
+}
+
+/// What a model accepts when chained to, and what it emits
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrmContract {
+    /// Payload types accepted on instructions that chain to the model
+    /// (plain instructions are always accepted)
+    pub inputs: BTreeSet<PayloadType>,
+    /// Actions the model emits
+    pub actions: BTreeSet<Action>,
+    /// Payload types the model emits (plain instructions are always allowed)
+    pub payloads: BTreeSet<PayloadType>,
+}
+
+impl TrmContract {
+    /// Check if the model accepts a payload of this type
+    pub fn accepts(&self, payload_type: PayloadType) -> bool {
+        payload_type == PayloadType::None || self.inputs.contains(&payload_type)
+    }
+
+    /// Check if the model may emit this instruction
+    pub fn check_output(&self, instr: &ExtendedInstruction) -> Result<(), String> {
+        if !self.actions.contains(&instr.base.action) {
+            return Err(format!("emits undeclared action {}", instr.base.action));
+        }
+        let payload_type = instr.payload.payload_type();
+        if payload_type != PayloadType::None && !self.payloads.contains(&payload_type) {
+            return Err(format!("emits undeclared payload {:?}", payload_type));
+        }
+        Ok(())
+    }
+}
+
+/// Registered TRM model
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrmModel {
+    /// Model ID used by [`Subject::trm_ref`]
+    pub id: u8,
+    /// Unique model name
+    pub name: String,
+    /// Model version
+    pub version: String,
+    /// Input/output contract
+    pub contract: TrmContract,
+    /// Capability tags (`weather`, `math`, ...)
+    pub capabilities: BTreeSet<String>,
+}
+
+impl TrmModel {
+    /// Create a model with an empty contract
+    pub fn new(id: u8, name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            id,
+            name: name.into(),
+            version: version.into(),
+            contract: TrmContract::default(),
+            capabilities: BTreeSet::new(),
+        }
+    }
+
+    /// Accept a payload type on incoming chains
+    pub fn with_input(mut self, payload_type: PayloadType) -> Self {
+        self.contract.inputs.insert(payload_type);
+        self
+    }
+
+    /// Declare an emitted action
+    pub fn with_action(mut self, action: Action) -> Self {
+        self.contract.actions.insert(action);
+        self
+    }
+
+    /// Declare an emitted payload type
+    pub fn with_payload(mut self, payload_type: PayloadType) -> Self {
+        self.contract.payloads.insert(payload_type);
+        self
+    }
+
+    /// Add a capability tag
+    pub fn with_capability(mut self, tag: impl Into<String>) -> Self {
+        self.capabilities.insert(tag.into());
+        self
+    }
+
+    /// Check for a capability tag
+    pub fn has_capability(&self, tag: &str) -> bool {
+        self.capabilities.contains(tag)
+    }
+
+    /// Subject referencing this model
+    pub fn subject(&self) -> Subject {
+        Subject::trm_ref(self.id)
+    }
+}
+
+impl fmt::Display for TrmModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TRM(0x{:02X}:{}@{})", self.id, self.name, self.version)
+    }
+}
+
+/// Registry of TRM models by ID
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrmRegistry {
+    models: BTreeMap<u8, TrmModel>,
+}
+
+impl TrmRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a model; IDs and names must be unique
+    pub fn register(&mut self, model: TrmModel) -> Result<(), TrmError> {
+        if self.models.contains_key(&model.id) {
+            return Err(TrmError::DuplicateId(model.id));
+        }
+        if self.get_by_name(&model.name).is_some() {
+            return Err(TrmError::DuplicateName(model.name));
+        }
+        self.models.insert(model.id, model);
+        Ok(())
+    }
+
+    /// Get a model by ID
+    pub fn get(&self, id: u8) -> Option<&TrmModel> {
+        self.models.get(&id)
+    }
+
+    /// Get a model by name
+    pub fn get_by_name(&self, name: &str) -> Option<&TrmModel> {
+        self.models.values().find(|model| model.name == name)
+    }
+
+    /// Get the model a `trm_ref` subject points at
+    pub fn resolve(&self, subject: Subject) -> Option<&TrmModel> {
+        self.get(subject.trm_model_id()?)
+    }
+
+    /// Models in ID order
+    pub fn models(&self) -> impl Iterator<Item = &TrmModel> {
+        self.models.values()
+    }
+
+    /// Models with a capability tag, in ID order
+    pub fn with_capability<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a TrmModel> {
+        self.models().filter(move |model| model.has_capability(tag))
+    }
+
+    /// Number of registered models
+    pub fn len(&self) -> usize {
+        self.models.len()
+    }
+
+    /// Check if no models are registered
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+
+    /// Check that every `trm_ref` in a program names a registered model
+    ///
+    /// Reports all unresolved references at once.
+    pub fn link(&self, program: &[Instruction]) -> Result<(), TrmError> {
+        let unresolved: Vec<UnresolvedRef> = program
+            .iter()
+            .enumerate()
+            .filter_map(|(index, instr)| {
+                let model_id = instr.subject.trm_model_id()?;
+                (!self.models.contains_key(&model_id)).then_some(UnresolvedRef { index, model_id })
+            })
+            .collect();
+        if unresolved.is_empty() {
+            Ok(())
+        } else {
+            Err(TrmError::Unresolved(unresolved))
+        }
+    }
+
+    /// [`link`](Self::link) an extended program, also checking that each
+    /// referenced model accepts the payload it is handed
+    pub fn link_extended(&self, program: &[ExtendedInstruction]) -> Result<(), TrmError> {
+        let bases: Vec<Instruction> = program.iter().map(|ext| ext.base).collect();
+        self.link(&bases)?;
+        for (index, ext) in program.iter().enumerate() {
+            let Some(model) = self.resolve(ext.base.subject) else {
+                continue;
+            };
+            let payload_type = ext.payload.payload_type();
+            if !model.contract.accepts(payload_type) {
+                return Err(TrmError::Contract {
+                    model_id: model.id,
+                    index,
+                    message: format!("does not accept {:?} payloads", payload_type),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that a model's output stays within its declared contract
+    pub fn check_output(
+        &self,
+        model_id: u8,
+        output: &[ExtendedInstruction],
+    ) -> Result<(), TrmError> {
+        let model = self.get(model_id).ok_or(TrmError::UnknownModel(model_id))?;
+        for (index, instr) in output.iter().enumerate() {
+            model
+                .contract
+                .check_output(instr)
+                .map_err(|message| TrmError::Contract {
+                    model_id,
+                    index,
+                    message,
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Parse a registry from config text (see the [module docs](self))
+    pub fn from_config(text: &str) -> Result<Self, TrmError> {
+        let mut registry = Self::new();
+        let mut current: Option<(usize, TrmModel, bool)> = None;
+        for (number, line) in text.lines().enumerate() {
+            let line_no = number + 1;
+            let err = |message: String| TrmError::Config {
+                line: line_no,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                if let Some(done) = current.take() {
+                    registry.finish_model(done)?;
+                }
+                let id = header
+                    .trim()
+                    .strip_prefix("model")
+                    .map(str::trim)
+                    .and_then(parse_id)
+                    .ok_or_else(|| err(format!("expected [model <id>], got [{}]", header)))?;
+                current = Some((line_no, TrmModel::new(id, "", ""), false));
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| err("expected `key = value`".to_string()))?;
+            let (key, value) = (key.trim(), value.trim());
+            let Some((_, model, has_name)) = current.as_mut() else {
+                return Err(err(format!("`{}` outside a [model] section", key)));
+            };
+            match key {
+                "name" => {
+                    model.name = value.to_string();
+                    *has_name = !value.is_empty();
+                }
+                "version" => model.version = value.to_string(),
+                "inputs" => {
+                    model.contract.inputs = parse_list(value, PayloadType::from_name)
+                        .map_err(|item| err(format!("unknown payload type {:?}", item)))?
+                }
+                "actions" => {
+                    model.contract.actions = parse_list(value, Action::from_name)
+                        .map_err(|item| err(format!("unknown action {:?}", item)))?
+                }
+                "payloads" => {
+                    model.contract.payloads = parse_list(value, PayloadType::from_name)
+                        .map_err(|item| err(format!("unknown payload type {:?}", item)))?
+                }
+                "capabilities" => {
+                    model.capabilities = parse_list(value, |tag| Some(tag.to_string()))
+                        .map_err(|item| err(format!("invalid capability {:?}", item)))?
+                }
+                _ => return Err(err(format!("unknown key `{}`", key))),
+            }
+        }
+        if let Some(done) = current {
+            registry.finish_model(done)?;
+        }
+        Ok(registry)
+    }
+
+    /// Load a registry from a config file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TrmError> {
+        let text = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| TrmError::Io(format!("{}: {}", path.as_ref().display(), e)))?;
+        Self::from_config(&text)
+    }
+
+    fn finish_model(
+        &mut self,
+        (line, model, has_name): (usize, TrmModel, bool),
+    ) -> Result<(), TrmError> {
+        if !has_name {
+            return Err(TrmError::Config {
+                line,
+                message: format!("model 0x{:02X} has no name", model.id),
+            });
+        }
+        self.register(model)
+    }
+}
+
+/// Parse a decimal or `0x..` model ID
+fn parse_id(s: &str) -> Option<u8> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+/// Parse a comma-separated list, returning the first item that fails to parse
+fn parse_list<T: Ord>(
+    value: &str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<BTreeSet<T>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| parse(item).ok_or_else(|| item.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extended::{CalcPayload, Op, TimePayload};
+
+    const CONFIG: &str = "\
+# Production models
+[model 3]
+name = weather
+version = 1.2.0
+inputs = Time, ZonedTime
+actions = RESPOND, EXPLAIN
+payloads = ZonedTime
+capabilities = weather, forecast
+
+[model 0x10]
+name = calculator
+version = 0.4.1
+actions = CALCULATE, RESPOND
+payloads = Calc, ExactCalc, Expr
+capabilities = math
+";
+
+    #[test]
+    fn test_registry_from_config() {
+        let registry = TrmRegistry::from_config(CONFIG).unwrap();
+        assert_eq!(registry.len(), 2);
+
+        let weather = registry.resolve(Subject::trm_ref(3)).unwrap();
+        assert_eq!(weather.to_string(), "TRM(0x03:weather@1.2.0)");
+        assert!(weather.contract.accepts(PayloadType::ZonedTime));
+        assert!(weather.contract.accepts(PayloadType::None));
+        assert!(!weather.contract.accepts(PayloadType::Calc));
+        assert!(weather.contract.actions.contains(&Action::EXPLAIN));
+
+        let calculator = registry.get_by_name("calculator").unwrap();
+        assert_eq!(calculator.id, 0x10);
+        assert_eq!(calculator.subject(), Subject::trm_ref(0x10));
+        let math: Vec<u8> = registry.with_capability("math").map(|m| m.id).collect();
+        assert_eq!(math, vec![0x10]);
+
+        assert!(registry.resolve(Subject::USER).is_none());
+    }
+
+    #[test]
+    fn test_config_errors() {
+        let line = |text: &str| match TrmRegistry::from_config(text) {
+            Err(TrmError::Config { line, .. }) => line,
+            other => panic!("expected config error, got {:?}", other),
+        };
+        assert_eq!(line("name = orphan"), 1);
+        assert_eq!(line("[model 300]\nname = x"), 1);
+        assert_eq!(line("[model 1]\nversion = 1.0"), 1);
+        assert_eq!(line("[model 1]\nname = a\nactions = RESPOND, DANCE"), 3);
+        assert_eq!(line("[model 1]\nname = a\npayloads = Bogus"), 3);
+        assert_eq!(line("[model 1]\nname = a\ncolour = blue"), 3);
+        assert_eq!(line("[model 1]\nname = a\njust text"), 3);
+
+        assert_eq!(
+            TrmRegistry::from_config("[model 1]\nname = a\n[model 1]\nname = b"),
+            Err(TrmError::DuplicateId(1))
+        );
+        assert_eq!(
+            TrmRegistry::from_config("[model 1]\nname = a\n[model 2]\nname = a"),
+            Err(TrmError::DuplicateName("a".to_string()))
+        );
+        assert!(matches!(
+            TrmRegistry::load("/nonexistent/models.trm"),
+            Err(TrmError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_link() {
+        let registry = TrmRegistry::from_config(CONFIG).unwrap();
+        let program = [
+            Instruction::simple(Action::GREET, Subject::USER),
+            Instruction::simple(Action::CHAIN, Subject::trm_ref(3)),
+            Instruction::simple(Action::FORK, Subject::trm_ref(4)),
+            Instruction::simple(Action::CHAIN, Subject::trm_ref(0x10)),
+            Instruction::simple(Action::CHAIN, Subject::trm_ref(0xFF)),
+        ];
+        assert!(registry.link(&program[..2]).is_ok());
+        assert_eq!(
+            registry.link(&program),
+            Err(TrmError::Unresolved(vec![
+                UnresolvedRef {
+                    index: 2,
+                    model_id: 4
+                },
+                UnresolvedRef {
+                    index: 4,
+                    model_id: 0xFF
+                },
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_link_extended_contracts() {
+        let registry = TrmRegistry::from_config(CONFIG).unwrap();
+        let chain = Instruction::simple(Action::CHAIN, Subject::trm_ref(3));
+        let ok = [
+            ExtendedInstruction::new(chain),
+            ExtendedInstruction::with_time(chain, TimePayload::at(1_700_000_000)),
+        ];
+        assert!(registry.link_extended(&ok).is_ok());
+
+        let calc = CalcPayload::new(Op::Add, 1.0, 2.0);
+        let bad = [ExtendedInstruction::with_calc(chain, calc)];
+        assert!(matches!(
+            registry.link_extended(&bad),
+            Err(TrmError::Contract {
+                model_id: 3,
+                index: 0,
+                ..
+            })
+        ));
+
+        // Output contracts
+        let answer = Instruction::simple(Action::RESPOND, Subject::NUMBER);
+        let output = [
+            ExtendedInstruction::new(Instruction::simple(Action::CALCULATE, Subject::NUMBER)),
+            ExtendedInstruction::with_calc(answer, calc),
+        ];
+        assert!(registry.check_output(0x10, &output).is_ok());
+        assert!(registry.check_output(3, &output).is_err());
+        assert_eq!(
+            registry.check_output(9, &output),
+            Err(TrmError::UnknownModel(9))
+        );
+    }
+
+    #[test]
+    fn test_registry_builder() {
+        let mut registry = TrmRegistry::new();
+        let model = TrmModel::new(1, "greeter", "1.0")
+            .with_action(Action::GREET)
+            .with_input(PayloadType::Custom(0x90))
+            .with_capability("smalltalk");
+        registry.register(model.clone()).unwrap();
+        assert_eq!(registry.register(model), Err(TrmError::DuplicateId(1)));
+        assert!(registry.get(1).unwrap().has_capability("smalltalk"));
+        assert!(registry
+            .get(1)
+            .unwrap()
+            .contract
+            .accepts(PayloadType::Custom(0x90)));
+    }
+}
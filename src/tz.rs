@@ -0,0 +1,326 @@
+//! Time zones: UTC offsets in minutes and a compact built-in zone table
+//!
+//! Zones are 2-byte codes organized by region in the high byte, mirroring the
+//! layout of [`Unit`](crate::Unit):
+//!
+//! - `0x00xx` - UTC
+//! - `0x01xx` - Americas (NEW_YORK, ST_JOHNS, ...)
+//! - `0x02xx` - Europe (LONDON, PARIS, ...)
+//! - `0x03xx` - Asia (KOLKATA, KATHMANDU, ...)
+//! - `0x04xx` - Oceania (SYDNEY, AUCKLAND)
+//!
+//! Each zone has a standard offset and at most one daylight-saving rule
+//! ([`DstRule`]), using current rules for every year.
+//!
+//! ```rust
+//! use frame_isa::calendar::DateTime;
+//! use frame_isa::tz::ZoneId;
+//!
+//! let winter = DateTime::new(2024, 1, 15, 12, 0, 0).unwrap().to_timestamp();
+//! let summer = DateTime::new(2024, 7, 15, 12, 0, 0).unwrap().to_timestamp();
+//! assert_eq!(ZoneId::NEW_YORK.offset_at(winter), -300);
+//! assert_eq!(ZoneId::NEW_YORK.offset_at(summer), -240);
+//! assert_eq!(ZoneId::KOLKATA.offset_at(summer), 330);
+//! ```
+
+use crate::calendar::{days_from_civil, nth_weekday_of_month, DateTime, Weekday, SECONDS_PER_DAY};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Smallest UTC offset in use (UTC-12:00), in minutes
+pub const MIN_OFFSET_MINUTES: i16 = -12 * 60;
+
+/// Largest UTC offset in use (UTC+14:00), in minutes
+pub const MAX_OFFSET_MINUTES: i16 = 14 * 60;
+
+/// Daylight-saving rule: one hour ahead of standard time between two transitions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DstRule {
+    /// United States and Canada: second Sunday of March 02:00 to first Sunday of November 02:00
+    Us,
+    /// European Union and UK: last Sunday of March to last Sunday of October, 01:00 UTC
+    Eu,
+    /// South-east Australia: first Sunday of October 02:00 to first Sunday of April 03:00
+    Australia,
+    /// New Zealand: last Sunday of September 02:00 to first Sunday of April 03:00
+    NewZealand,
+}
+
+impl DstRule {
+    /// Minutes added to standard time while the rule is active
+    pub const SAVE_MINUTES: i16 = 60;
+
+    /// UTC timestamps at which DST starts and ends in a (local) year
+    pub fn transitions(self, year: i64, standard_offset: i16) -> (i64, i64) {
+        // (month, nth Sunday, minutes after local standard midnight)
+        let eu_time = 60 + standard_offset as i64;
+        let (start, end) = match self {
+            DstRule::Us => ((3, 2, 120), (11, 1, 60)),
+            DstRule::Eu => ((3, -1, eu_time), (10, -1, eu_time)),
+            DstRule::Australia => ((10, 1, 120), (4, 1, 120)),
+            DstRule::NewZealand => ((9, -1, 120), (4, 1, 120)),
+        };
+        let at = |(month, nth, minutes): (u8, i8, i64)| {
+            let day = nth_weekday_of_month(year, month, Weekday::Sunday, nth).unwrap_or(1);
+            days_from_civil(year, month, day)
+                .saturating_mul(SECONDS_PER_DAY)
+                .saturating_add((minutes - standard_offset as i64) * 60)
+        };
+        (at(start), at(end))
+    }
+
+    /// Check if DST is in effect at a UTC timestamp
+    pub fn is_active(self, timestamp: i64, standard_offset: i16) -> bool {
+        let local = timestamp.saturating_add(standard_offset as i64 * 60);
+        let year = DateTime::from_timestamp(local).year;
+        let (start, end) = self.transitions(year, standard_offset);
+        if start < end {
+            (start..end).contains(&timestamp)
+        } else {
+            // Southern hemisphere: DST spans the new year
+            timestamp >= start || timestamp < end
+        }
+    }
+}
+
+/// Compact time zone identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[repr(transparent)]
+pub struct ZoneId(pub u16);
+
+impl ZoneId {
+    // UTC (0x00xx)
+    pub const UTC: Self = Self(0x0001);
+
+    // Americas (0x01xx)
+    pub const NEW_YORK: Self = Self(0x0101);
+    pub const CHICAGO: Self = Self(0x0102);
+    pub const DENVER: Self = Self(0x0103);
+    pub const PHOENIX: Self = Self(0x0104);
+    pub const LOS_ANGELES: Self = Self(0x0105);
+    pub const ST_JOHNS: Self = Self(0x0106);
+    pub const SAO_PAULO: Self = Self(0x0107);
+
+    // Europe (0x02xx)
+    pub const LONDON: Self = Self(0x0201);
+    pub const PARIS: Self = Self(0x0202);
+    pub const BERLIN: Self = Self(0x0203);
+    pub const ATHENS: Self = Self(0x0204);
+
+    // Asia (0x03xx)
+    pub const DUBAI: Self = Self(0x0301);
+    pub const KOLKATA: Self = Self(0x0302);
+    pub const KATHMANDU: Self = Self(0x0303);
+    pub const SHANGHAI: Self = Self(0x0304);
+    pub const TOKYO: Self = Self(0x0305);
+
+    // Oceania (0x04xx)
+    pub const SYDNEY: Self = Self(0x0401);
+    pub const AUCKLAND: Self = Self(0x0402);
+
+    /// Every zone in the table
+    pub const ALL: &'static [ZoneId] = &[
+        Self::UTC,
+        Self::NEW_YORK,
+        Self::CHICAGO,
+        Self::DENVER,
+        Self::PHOENIX,
+        Self::LOS_ANGELES,
+        Self::ST_JOHNS,
+        Self::SAO_PAULO,
+        Self::LONDON,
+        Self::PARIS,
+        Self::BERLIN,
+        Self::ATHENS,
+        Self::DUBAI,
+        Self::KOLKATA,
+        Self::KATHMANDU,
+        Self::SHANGHAI,
+        Self::TOKYO,
+        Self::SYDNEY,
+        Self::AUCKLAND,
+    ];
+
+    /// Table entry: (IANA name, standard offset in minutes, DST rule)
+    const fn info(&self) -> Option<(&'static str, i16, Option<DstRule>)> {
+        let info = match *self {
+            Self::UTC => ("UTC", 0, None),
+            Self::NEW_YORK => ("America/New_York", -300, Some(DstRule::Us)),
+            Self::CHICAGO => ("America/Chicago", -360, Some(DstRule::Us)),
+            Self::DENVER => ("America/Denver", -420, Some(DstRule::Us)),
+            Self::PHOENIX => ("America/Phoenix", -420, None),
+            Self::LOS_ANGELES => ("America/Los_Angeles", -480, Some(DstRule::Us)),
+            Self::ST_JOHNS => ("America/St_Johns", -210, Some(DstRule::Us)),
+            Self::SAO_PAULO => ("America/Sao_Paulo", -180, None),
+            Self::LONDON => ("Europe/London", 0, Some(DstRule::Eu)),
+            Self::PARIS => ("Europe/Paris", 60, Some(DstRule::Eu)),
+            Self::BERLIN => ("Europe/Berlin", 60, Some(DstRule::Eu)),
+            Self::ATHENS => ("Europe/Athens", 120, Some(DstRule::Eu)),
+            Self::DUBAI => ("Asia/Dubai", 240, None),
+            Self::KOLKATA => ("Asia/Kolkata", 330, None),
+            Self::KATHMANDU => ("Asia/Kathmandu", 345, None),
+            Self::SHANGHAI => ("Asia/Shanghai", 480, None),
+            Self::TOKYO => ("Asia/Tokyo", 540, None),
+            Self::SYDNEY => ("Australia/Sydney", 600, Some(DstRule::Australia)),
+            Self::AUCKLAND => ("Pacific/Auckland", 720, Some(DstRule::NewZealand)),
+            _ => return None,
+        };
+        Some(info)
+    }
+
+    /// Check if the code is in the zone table
+    pub const fn is_known(&self) -> bool {
+        self.info().is_some()
+    }
+
+    /// IANA name ("unknown" for codes not in the table)
+    pub fn name(&self) -> &'static str {
+        self.info().map_or("unknown", |(name, _, _)| name)
+    }
+
+    /// Standard (non-DST) UTC offset in minutes (0 for unknown codes)
+    pub fn standard_offset(&self) -> i16 {
+        self.info().map_or(0, |(_, offset, _)| offset)
+    }
+
+    /// Daylight-saving rule, if the zone observes DST
+    pub fn dst_rule(&self) -> Option<DstRule> {
+        self.info().and_then(|(_, _, rule)| rule)
+    }
+
+    /// UTC offset in minutes in effect at a UTC timestamp
+    pub fn offset_at(&self, timestamp: i64) -> i16 {
+        let standard = self.standard_offset();
+        match self.dst_rule() {
+            Some(rule) if rule.is_active(timestamp, standard) => standard + DstRule::SAVE_MINUTES,
+            _ => standard,
+        }
+    }
+
+    /// Convert a local wall-clock time (seconds as if UTC) to a UTC timestamp
+    ///
+    /// Ambiguous times (when clocks go back) resolve to standard time; times
+    /// skipped when clocks go forward are read as standard time.
+    pub fn to_utc(&self, local: i64) -> i64 {
+        let as_standard = local.saturating_sub(self.standard_offset() as i64 * 60);
+        let offset = self.offset_at(as_standard);
+        let utc = local.saturating_sub(offset as i64 * 60);
+        if self.offset_at(utc) == offset {
+            utc
+        } else {
+            as_standard
+        }
+    }
+
+    /// Look up a zone by IANA name (case-insensitive)
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim();
+        Self::ALL
+            .iter()
+            .find(|zone| zone.name().eq_ignore_ascii_case(name))
+            .copied()
+    }
+}
+
+impl fmt::Display for ZoneId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ZONE(0x{:04X}:{})", self.0, self.name())
+    }
+}
+
+/// Format a UTC offset in minutes as `+05:30` / `-03:30`
+pub fn format_offset(minutes: i16) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let abs = minutes.unsigned_abs();
+    format!("{}{:02}:{:02}", sign, abs / 60, abs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(year: i64, month: u8, day: u8, hour: u8, minute: u8) -> i64 {
+        DateTime::new(year, month, day, hour, minute, 0)
+            .unwrap()
+            .to_timestamp()
+    }
+
+    #[test]
+    fn test_fixed_zones() {
+        let t = ts(2024, 7, 1, 0, 0);
+        assert_eq!(ZoneId::KOLKATA.offset_at(t), 330);
+        assert_eq!(ZoneId::KATHMANDU.offset_at(t), 345);
+        assert_eq!(ZoneId::PHOENIX.offset_at(t), -420);
+        assert_eq!(ZoneId::UTC.offset_at(t), 0);
+        assert_eq!(ZoneId(0x7777).offset_at(t), 0);
+    }
+
+    #[test]
+    fn test_us_transitions() {
+        // 2024-03-10 02:00 EST = 07:00 UTC; 2024-11-03 02:00 EDT = 06:00 UTC
+        let ny = ZoneId::NEW_YORK;
+        assert_eq!(ny.offset_at(ts(2024, 3, 10, 6, 59)), -300);
+        assert_eq!(ny.offset_at(ts(2024, 3, 10, 7, 0)), -240);
+        assert_eq!(ny.offset_at(ts(2024, 11, 3, 5, 59)), -240);
+        assert_eq!(ny.offset_at(ts(2024, 11, 3, 6, 0)), -300);
+
+        // Newfoundland observes the same rule on a half-hour offset
+        assert_eq!(ZoneId::ST_JOHNS.offset_at(ts(2024, 1, 1, 0, 0)), -210);
+        assert_eq!(ZoneId::ST_JOHNS.offset_at(ts(2024, 7, 1, 0, 0)), -150);
+    }
+
+    #[test]
+    fn test_eu_transitions() {
+        // Switches at 01:00 UTC everywhere in the EU
+        for (zone, standard) in [
+            (ZoneId::LONDON, 0),
+            (ZoneId::PARIS, 60),
+            (ZoneId::ATHENS, 120),
+        ] {
+            assert_eq!(zone.offset_at(ts(2024, 3, 31, 0, 59)), standard);
+            assert_eq!(zone.offset_at(ts(2024, 3, 31, 1, 0)), standard + 60);
+            assert_eq!(zone.offset_at(ts(2024, 10, 27, 0, 59)), standard + 60);
+            assert_eq!(zone.offset_at(ts(2024, 10, 27, 1, 0)), standard);
+        }
+    }
+
+    #[test]
+    fn test_southern_hemisphere() {
+        let sydney = ZoneId::SYDNEY;
+        assert_eq!(sydney.offset_at(ts(2024, 1, 15, 0, 0)), 660);
+        assert_eq!(sydney.offset_at(ts(2024, 7, 15, 0, 0)), 600);
+        // 2024-10-06 02:00 AEST = 2024-10-05 16:00 UTC
+        assert_eq!(sydney.offset_at(ts(2024, 10, 5, 15, 59)), 600);
+        assert_eq!(sydney.offset_at(ts(2024, 10, 5, 16, 0)), 660);
+        // 2024-04-07 03:00 AEDT = 2024-04-06 16:00 UTC
+        assert_eq!(sydney.offset_at(ts(2024, 4, 6, 15, 59)), 660);
+        assert_eq!(sydney.offset_at(ts(2024, 4, 6, 16, 0)), 600);
+
+        assert_eq!(ZoneId::AUCKLAND.offset_at(ts(2024, 12, 25, 0, 0)), 780);
+        assert_eq!(ZoneId::AUCKLAND.offset_at(ts(2024, 6, 25, 0, 0)), 720);
+    }
+
+    #[test]
+    fn test_to_utc() {
+        let ny = ZoneId::NEW_YORK;
+        assert_eq!(ny.to_utc(ts(2024, 7, 1, 12, 0)), ts(2024, 7, 1, 16, 0));
+        assert_eq!(ny.to_utc(ts(2024, 1, 1, 12, 0)), ts(2024, 1, 1, 17, 0));
+        // 01:30 on fall-back day happens twice; standard time wins
+        assert_eq!(ny.to_utc(ts(2024, 11, 3, 1, 30)), ts(2024, 11, 3, 6, 30));
+        // 02:30 on spring-forward day never happens; read as standard time
+        assert_eq!(ny.to_utc(ts(2024, 3, 10, 2, 30)), ts(2024, 3, 10, 7, 30));
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(ZoneId::parse("asia/kolkata"), Some(ZoneId::KOLKATA));
+        assert_eq!(ZoneId::parse("Mars/Olympus"), None);
+        assert_eq!(ZoneId::KOLKATA.to_string(), "ZONE(0x0302:Asia/Kolkata)");
+        assert!(ZoneId::ALL.iter().all(ZoneId::is_known));
+        assert!(!ZoneId(0x0000).is_known());
+
+        assert_eq!(format_offset(330), "+05:30");
+        assert_eq!(format_offset(-210), "-03:30");
+        assert_eq!(format_offset(0), "+00:00");
+    }
+}
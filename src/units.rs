@@ -0,0 +1,611 @@
+//! Unit catalog and unit-conversion payload
+//!
+//! Units are 2-byte codes organized by dimension in the high byte, mirroring
+//! the layout of [`Action`](crate::Action) and [`Subject`](crate::Subject):
+//!
+//! - `0x01xx` - Length (METER, KILOMETER, MILE, FOOT, ...)
+//! - `0x02xx` - Mass (KILOGRAM, GRAM, POUND, OUNCE, ...)
+//! - `0x03xx` - Temperature (KELVIN, CELSIUS, FAHRENHEIT)
+//! - `0x04xx` - Volume (LITER, MILLILITER, GALLON, CUP, ...)
+//! - `0x05xx` - Time (SECOND, MINUTE, HOUR, DAY, ...)
+//! - `0x06xx` - Data size (BIT, BYTE, KILOBYTE, KIBIBYTE, ...)
+//!
+//! Because dimensions are encoded in the code itself, compatibility can be
+//! checked at compile time:
+//!
+//! ```rust
+//! use frame_isa::units::Unit;
+//!
+//! const _: () = assert!(Unit::MILE.is_convertible_to(Unit::KILOMETER));
+//! const _: () = assert!(!Unit::MILE.is_convertible_to(Unit::KILOGRAM));
+//! ```
+//!
+//! Conversion payload format:
+//!
+//! ```text
+//! [VALUE:8][FROM:2][TO:2] = 12 bytes
+//! ```
+
+use crate::InstructionError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+
+/// Physical dimension of a unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Dimension {
+    Length = 0x01,
+    Mass = 0x02,
+    Temperature = 0x03,
+    Volume = 0x04,
+    Time = 0x05,
+    DataSize = 0x06,
+}
+
+impl Dimension {
+    /// Parse from byte
+    pub const fn from_byte(b: u8) -> Option<Self> {
+        match b {
+            0x01 => Some(Dimension::Length),
+            0x02 => Some(Dimension::Mass),
+            0x03 => Some(Dimension::Temperature),
+            0x04 => Some(Dimension::Volume),
+            0x05 => Some(Dimension::Time),
+            0x06 => Some(Dimension::DataSize),
+            _ => None,
+        }
+    }
+
+    /// Convert to byte
+    pub const fn to_byte(self) -> u8 {
+        self as u8
+    }
+
+    /// Base unit that all units of this dimension convert through
+    pub const fn base_unit(self) -> Unit {
+        Unit((self as u16) << 8)
+    }
+
+    /// Display name
+    pub fn name(self) -> &'static str {
+        match self {
+            Dimension::Length => "length",
+            Dimension::Mass => "mass",
+            Dimension::Temperature => "temperature",
+            Dimension::Volume => "volume",
+            Dimension::Time => "time",
+            Dimension::DataSize => "data size",
+        }
+    }
+}
+
+/// Unit code (2 bytes)
+///
+/// The high byte is the [`Dimension`], the low byte selects the unit. The
+/// unit with low byte `0x00` is the base unit of its dimension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(transparent)]
+pub struct Unit(pub u16);
+
+impl Unit {
+    // ========== Length (0x0100-0x01FF), base: meter ==========
+    pub const METER: Self = Self(0x0100);
+    pub const KILOMETER: Self = Self(0x0101);
+    pub const CENTIMETER: Self = Self(0x0102);
+    pub const MILLIMETER: Self = Self(0x0103);
+    pub const MILE: Self = Self(0x0104);
+    pub const YARD: Self = Self(0x0105);
+    pub const FOOT: Self = Self(0x0106);
+    pub const INCH: Self = Self(0x0107);
+    pub const NAUTICAL_MILE: Self = Self(0x0108);
+
+    // ========== Mass (0x0200-0x02FF), base: kilogram ==========
+    pub const KILOGRAM: Self = Self(0x0200);
+    pub const GRAM: Self = Self(0x0201);
+    pub const MILLIGRAM: Self = Self(0x0202);
+    pub const TONNE: Self = Self(0x0203);
+    pub const POUND: Self = Self(0x0204);
+    pub const OUNCE: Self = Self(0x0205);
+    pub const STONE: Self = Self(0x0206);
+
+    // ========== Temperature (0x0300-0x03FF), base: kelvin ==========
+    pub const KELVIN: Self = Self(0x0300);
+    pub const CELSIUS: Self = Self(0x0301);
+    pub const FAHRENHEIT: Self = Self(0x0302);
+
+    // ========== Volume (0x0400-0x04FF), base: liter ==========
+    pub const LITER: Self = Self(0x0400);
+    pub const MILLILITER: Self = Self(0x0401);
+    pub const CUBIC_METER: Self = Self(0x0402);
+    pub const GALLON: Self = Self(0x0403);
+    pub const QUART: Self = Self(0x0404);
+    pub const PINT: Self = Self(0x0405);
+    pub const CUP: Self = Self(0x0406);
+    pub const FLUID_OUNCE: Self = Self(0x0407);
+    pub const TABLESPOON: Self = Self(0x0408);
+    pub const TEASPOON: Self = Self(0x0409);
+
+    // ========== Time (0x0500-0x05FF), base: second ==========
+    pub const SECOND: Self = Self(0x0500);
+    pub const MILLISECOND: Self = Self(0x0501);
+    pub const MINUTE: Self = Self(0x0502);
+    pub const HOUR: Self = Self(0x0503);
+    pub const DAY: Self = Self(0x0504);
+    pub const WEEK: Self = Self(0x0505);
+    /// Julian year (365.25 days)
+    pub const YEAR: Self = Self(0x0506);
+
+    // ========== Data Size (0x0600-0x06FF), base: byte ==========
+    pub const BYTE: Self = Self(0x0600);
+    pub const BIT: Self = Self(0x0601);
+    pub const KILOBYTE: Self = Self(0x0602);
+    pub const MEGABYTE: Self = Self(0x0603);
+    pub const GIGABYTE: Self = Self(0x0604);
+    pub const TERABYTE: Self = Self(0x0605);
+    pub const KIBIBYTE: Self = Self(0x0606);
+    pub const MEBIBYTE: Self = Self(0x0607);
+    pub const GIBIBYTE: Self = Self(0x0608);
+    pub const TEBIBYTE: Self = Self(0x0609);
+
+    /// Every unit in the catalog
+    pub const ALL: &'static [Unit] = &[
+        Self::METER,
+        Self::KILOMETER,
+        Self::CENTIMETER,
+        Self::MILLIMETER,
+        Self::MILE,
+        Self::YARD,
+        Self::FOOT,
+        Self::INCH,
+        Self::NAUTICAL_MILE,
+        Self::KILOGRAM,
+        Self::GRAM,
+        Self::MILLIGRAM,
+        Self::TONNE,
+        Self::POUND,
+        Self::OUNCE,
+        Self::STONE,
+        Self::KELVIN,
+        Self::CELSIUS,
+        Self::FAHRENHEIT,
+        Self::LITER,
+        Self::MILLILITER,
+        Self::CUBIC_METER,
+        Self::GALLON,
+        Self::QUART,
+        Self::PINT,
+        Self::CUP,
+        Self::FLUID_OUNCE,
+        Self::TABLESPOON,
+        Self::TEASPOON,
+        Self::SECOND,
+        Self::MILLISECOND,
+        Self::MINUTE,
+        Self::HOUR,
+        Self::DAY,
+        Self::WEEK,
+        Self::YEAR,
+        Self::BYTE,
+        Self::BIT,
+        Self::KILOBYTE,
+        Self::MEGABYTE,
+        Self::GIGABYTE,
+        Self::TERABYTE,
+        Self::KIBIBYTE,
+        Self::MEBIBYTE,
+        Self::GIBIBYTE,
+        Self::TEBIBYTE,
+    ];
+
+    /// Create from raw u16 value
+    #[inline]
+    pub const fn from_u16(value: u16) -> Self {
+        Self(value)
+    }
+
+    /// Get raw u16 value
+    #[inline]
+    pub const fn as_u16(&self) -> u16 {
+        self.0
+    }
+
+    /// Get the dimension (from the high byte)
+    #[inline]
+    pub const fn dimension(&self) -> Option<Dimension> {
+        Dimension::from_byte((self.0 >> 8) as u8)
+    }
+
+    /// Check if values can be converted between this unit and `other`
+    #[inline]
+    pub const fn is_convertible_to(&self, other: Unit) -> bool {
+        let dim = self.0 >> 8;
+        dim == other.0 >> 8 && Dimension::from_byte(dim as u8).is_some()
+    }
+
+    /// Conversion to the base unit: `base = value * scale + offset`
+    ///
+    /// Returns `None` for codes not in the catalog.
+    pub const fn to_base(&self) -> Option<(f64, f64)> {
+        let scale = match *self {
+            Self::METER => 1.0,
+            Self::KILOMETER => 1000.0,
+            Self::CENTIMETER => 0.01,
+            Self::MILLIMETER => 0.001,
+            Self::MILE => 1609.344,
+            Self::YARD => 0.9144,
+            Self::FOOT => 0.3048,
+            Self::INCH => 0.0254,
+            Self::NAUTICAL_MILE => 1852.0,
+
+            Self::KILOGRAM => 1.0,
+            Self::GRAM => 0.001,
+            Self::MILLIGRAM => 0.000001,
+            Self::TONNE => 1000.0,
+            Self::POUND => 0.45359237,
+            Self::OUNCE => 0.028349523125,
+            Self::STONE => 6.35029318,
+
+            Self::KELVIN => 1.0,
+            Self::CELSIUS => return Some((1.0, 273.15)),
+            Self::FAHRENHEIT => return Some((5.0 / 9.0, 273.15 - 32.0 * 5.0 / 9.0)),
+
+            Self::LITER => 1.0,
+            Self::MILLILITER => 0.001,
+            Self::CUBIC_METER => 1000.0,
+            Self::GALLON => 3.785411784,
+            Self::QUART => 0.946352946,
+            Self::PINT => 0.473176473,
+            Self::CUP => 0.2365882365,
+            Self::FLUID_OUNCE => 0.0295735295625,
+            Self::TABLESPOON => 0.01478676478125,
+            Self::TEASPOON => 0.00492892159375,
+
+            Self::SECOND => 1.0,
+            Self::MILLISECOND => 0.001,
+            Self::MINUTE => 60.0,
+            Self::HOUR => 3600.0,
+            Self::DAY => 86400.0,
+            Self::WEEK => 604800.0,
+            Self::YEAR => 31557600.0,
+
+            Self::BYTE => 1.0,
+            Self::BIT => 0.125,
+            Self::KILOBYTE => 1e3,
+            Self::MEGABYTE => 1e6,
+            Self::GIGABYTE => 1e9,
+            Self::TERABYTE => 1e12,
+            Self::KIBIBYTE => 1024.0,
+            Self::MEBIBYTE => 1048576.0,
+            Self::GIBIBYTE => 1073741824.0,
+            Self::TEBIBYTE => 1099511627776.0,
+
+            _ => return None,
+        };
+        Some((scale, 0.0))
+    }
+
+    /// Get the human-readable name for this unit (US customary volumes)
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Self::METER => "meter",
+            Self::KILOMETER => "kilometer",
+            Self::CENTIMETER => "centimeter",
+            Self::MILLIMETER => "millimeter",
+            Self::MILE => "mile",
+            Self::YARD => "yard",
+            Self::FOOT => "foot",
+            Self::INCH => "inch",
+            Self::NAUTICAL_MILE => "nautical mile",
+            Self::KILOGRAM => "kilogram",
+            Self::GRAM => "gram",
+            Self::MILLIGRAM => "milligram",
+            Self::TONNE => "tonne",
+            Self::POUND => "pound",
+            Self::OUNCE => "ounce",
+            Self::STONE => "stone",
+            Self::KELVIN => "kelvin",
+            Self::CELSIUS => "celsius",
+            Self::FAHRENHEIT => "fahrenheit",
+            Self::LITER => "liter",
+            Self::MILLILITER => "milliliter",
+            Self::CUBIC_METER => "cubic meter",
+            Self::GALLON => "gallon",
+            Self::QUART => "quart",
+            Self::PINT => "pint",
+            Self::CUP => "cup",
+            Self::FLUID_OUNCE => "fluid ounce",
+            Self::TABLESPOON => "tablespoon",
+            Self::TEASPOON => "teaspoon",
+            Self::SECOND => "second",
+            Self::MILLISECOND => "millisecond",
+            Self::MINUTE => "minute",
+            Self::HOUR => "hour",
+            Self::DAY => "day",
+            Self::WEEK => "week",
+            Self::YEAR => "year",
+            Self::BYTE => "byte",
+            Self::BIT => "bit",
+            Self::KILOBYTE => "kilobyte",
+            Self::MEGABYTE => "megabyte",
+            Self::GIGABYTE => "gigabyte",
+            Self::TERABYTE => "terabyte",
+            Self::KIBIBYTE => "kibibyte",
+            Self::MEBIBYTE => "mebibyte",
+            Self::GIBIBYTE => "gibibyte",
+            Self::TEBIBYTE => "tebibyte",
+            _ => "unknown",
+        }
+    }
+
+    /// Get the unit symbol
+    pub fn symbol(&self) -> &'static str {
+        match *self {
+            Self::METER => "m",
+            Self::KILOMETER => "km",
+            Self::CENTIMETER => "cm",
+            Self::MILLIMETER => "mm",
+            Self::MILE => "mi",
+            Self::YARD => "yd",
+            Self::FOOT => "ft",
+            Self::INCH => "in",
+            Self::NAUTICAL_MILE => "nmi",
+            Self::KILOGRAM => "kg",
+            Self::GRAM => "g",
+            Self::MILLIGRAM => "mg",
+            Self::TONNE => "t",
+            Self::POUND => "lb",
+            Self::OUNCE => "oz",
+            Self::STONE => "st",
+            Self::KELVIN => "K",
+            Self::CELSIUS => "°C",
+            Self::FAHRENHEIT => "°F",
+            Self::LITER => "L",
+            Self::MILLILITER => "mL",
+            Self::CUBIC_METER => "m³",
+            Self::GALLON => "gal",
+            Self::QUART => "qt",
+            Self::PINT => "pt",
+            Self::CUP => "cup",
+            Self::FLUID_OUNCE => "fl oz",
+            Self::TABLESPOON => "tbsp",
+            Self::TEASPOON => "tsp",
+            Self::SECOND => "s",
+            Self::MILLISECOND => "ms",
+            Self::MINUTE => "min",
+            Self::HOUR => "h",
+            Self::DAY => "d",
+            Self::WEEK => "wk",
+            Self::YEAR => "yr",
+            Self::BYTE => "B",
+            Self::BIT => "bit",
+            Self::KILOBYTE => "kB",
+            Self::MEGABYTE => "MB",
+            Self::GIGABYTE => "GB",
+            Self::TERABYTE => "TB",
+            Self::KIBIBYTE => "KiB",
+            Self::MEBIBYTE => "MiB",
+            Self::GIBIBYTE => "GiB",
+            Self::TEBIBYTE => "TiB",
+            _ => "?",
+        }
+    }
+
+    /// Look up a unit by symbol or name (singular or plural, case-insensitive names)
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if let Some(unit) = Self::ALL.iter().find(|u| u.symbol() == s) {
+            return Some(*unit);
+        }
+        let lower = s.to_ascii_lowercase();
+        let singular = match lower.as_str() {
+            "feet" => "foot",
+            "inches" => "inch",
+            other => other.strip_suffix('s').unwrap_or(other),
+        };
+        Self::ALL
+            .iter()
+            .find(|u| u.name() == lower || u.name() == singular)
+            .copied()
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "UNIT(0x{:04X}:{})", self.0, self.symbol())
+    }
+}
+
+impl From<u16> for Unit {
+    fn from(value: u16) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Unit> for u16 {
+    fn from(unit: Unit) -> Self {
+        unit.0
+    }
+}
+
+/// Reference conversion between catalog units
+///
+/// Converts through the base unit of the dimension, so
+/// `convert(v, a, b)` equals `convert(convert(v, a, base), base, b)`.
+pub fn convert(value: f64, from: Unit, to: Unit) -> Result<f64, ConversionError> {
+    let (from_scale, from_offset) = from.to_base().ok_or(ConversionError::UnknownUnit(from))?;
+    let (to_scale, to_offset) = to.to_base().ok_or(ConversionError::UnknownUnit(to))?;
+    if !from.is_convertible_to(to) {
+        return Err(ConversionError::IncompatibleUnits { from, to });
+    }
+    if from == to {
+        return Ok(value);
+    }
+    let base = value * from_scale + from_offset;
+    Ok((base - to_offset) / to_scale)
+}
+
+/// Errors that can occur when converting between units
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionError {
+    #[error("Unknown unit code: 0x{:04X}", .0.as_u16())]
+    UnknownUnit(Unit),
+
+    #[error("Cannot convert {} to {}", from.name(), to.name())]
+    IncompatibleUnits { from: Unit, to: Unit },
+}
+
+/// Unit-conversion payload: "convert VALUE FROM to TO"
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConversionPayload {
+    /// Value expressed in `from` units
+    pub value: f64,
+    /// Source unit
+    pub from: Unit,
+    /// Target unit
+    pub to: Unit,
+}
+
+impl ConversionPayload {
+    /// Create new conversion payload
+    pub fn new(value: f64, from: Unit, to: Unit) -> Self {
+        Self { value, from, to }
+    }
+
+    /// Compute the converted value
+    pub fn convert(&self) -> Result<f64, ConversionError> {
+        convert(self.value, self.from, self.to)
+    }
+
+    /// Check that both units are known and share a dimension
+    pub fn validate(&self) -> Result<(), InstructionError> {
+        self.convert()
+            .map(|_| ())
+            .map_err(|e| InstructionError::InvalidPayload(e.to_string()))
+    }
+
+    /// Serialize to bytes: [VALUE:8][FROM:2][TO:2] = 12 bytes
+    pub fn to_bytes(&self) -> [u8; 12] {
+        let mut bytes = [0u8; 12];
+        bytes[0..8].copy_from_slice(&self.value.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.from.as_u16().to_be_bytes());
+        bytes[10..12].copy_from_slice(&self.to.as_u16().to_be_bytes());
+        bytes
+    }
+
+    /// Parse from bytes
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 12 {
+            return None;
+        }
+        let value = f64::from_be_bytes(bytes[0..8].try_into().ok()?);
+        let from = Unit(u16::from_be_bytes([bytes[8], bytes[9]]));
+        let to = Unit(u16::from_be_bytes([bytes[10], bytes[11]]));
+        Some(Self { value, from, to })
+    }
+}
+
+impl fmt::Display for ConversionPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} -> {}",
+            self.value,
+            self.from.symbol(),
+            self.to.symbol()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_unit_dimensions() {
+        assert_eq!(Unit::MILE.dimension(), Some(Dimension::Length));
+        assert_eq!(Unit::CELSIUS.dimension(), Some(Dimension::Temperature));
+        assert_eq!(Unit::GIBIBYTE.dimension(), Some(Dimension::DataSize));
+        assert_eq!(Unit(0xFF00).dimension(), None);
+        assert_eq!(Dimension::Mass.base_unit(), Unit::KILOGRAM);
+    }
+
+    #[test]
+    fn test_catalog_is_complete() {
+        for unit in Unit::ALL {
+            assert!(unit.to_base().is_some(), "{} has no factor", unit);
+            assert_ne!(unit.name(), "unknown");
+            assert_eq!(Unit::parse(unit.symbol()), Some(*unit));
+        }
+    }
+
+    #[test]
+    fn test_linear_conversions() {
+        assert_close(convert(5.0, Unit::MILE, Unit::KILOMETER).unwrap(), 8.04672);
+        assert_close(convert(1.0, Unit::POUND, Unit::GRAM).unwrap(), 453.59237);
+        assert_close(convert(1.0, Unit::GALLON, Unit::CUP).unwrap(), 16.0);
+        assert_close(
+            convert(1.0, Unit::GIBIBYTE, Unit::MEGABYTE).unwrap(),
+            1073.741824,
+        );
+        assert_close(convert(90.0, Unit::MINUTE, Unit::HOUR).unwrap(), 1.5);
+    }
+
+    #[test]
+    fn test_temperature_conversions() {
+        assert_close(
+            convert(100.0, Unit::CELSIUS, Unit::FAHRENHEIT).unwrap(),
+            212.0,
+        );
+        assert_close(convert(32.0, Unit::FAHRENHEIT, Unit::CELSIUS).unwrap(), 0.0);
+        assert_close(
+            convert(-40.0, Unit::CELSIUS, Unit::FAHRENHEIT).unwrap(),
+            -40.0,
+        );
+        assert_close(convert(0.0, Unit::KELVIN, Unit::CELSIUS).unwrap(), -273.15);
+    }
+
+    #[test]
+    fn test_conversion_errors() {
+        assert_eq!(
+            convert(1.0, Unit::MILE, Unit::KILOGRAM),
+            Err(ConversionError::IncompatibleUnits {
+                from: Unit::MILE,
+                to: Unit::KILOGRAM
+            })
+        );
+        assert_eq!(
+            convert(1.0, Unit(0x01FF), Unit::METER),
+            Err(ConversionError::UnknownUnit(Unit(0x01FF)))
+        );
+    }
+
+    #[test]
+    fn test_parse_names() {
+        assert_eq!(Unit::parse("miles"), Some(Unit::MILE));
+        assert_eq!(Unit::parse("Feet"), Some(Unit::FOOT));
+        assert_eq!(Unit::parse("km"), Some(Unit::KILOMETER));
+        assert_eq!(Unit::parse("furlong"), None);
+    }
+
+    #[test]
+    fn test_conversion_payload_roundtrip() {
+        let payload = ConversionPayload::new(5.0, Unit::MILE, Unit::KILOMETER);
+        let parsed = ConversionPayload::from_bytes(&payload.to_bytes()).unwrap();
+        assert_eq!(parsed, payload);
+        assert_eq!(format!("{}", payload), "5 mi -> km");
+        assert!(payload.validate().is_ok());
+        assert!(ConversionPayload::new(1.0, Unit::MILE, Unit::LITER)
+            .validate()
+            .is_err());
+    }
+}
@@ -0,0 +1,902 @@
+//! Reference virtual machine for instruction programs
+//!
+//! [`Vm`] runs a program through a [`Handler`], calling the handler method
+//! for each instruction's [`ActionCategory`] and collecting the outputs, so
+//! consumers implement behavior instead of their own dispatch loop.
+//!
+//! System instructions have fixed semantics:
+//!
+//! - `NOP` does nothing; the handler is not called
+//! - `HALT` stops execution successfully; later instructions do not run
+//! - `ERROR` stops execution with [`ExitStatus::Error`], carrying the
+//!   instruction's subject as the error code
+//! - Other system actions (`STATUS`, ...) go to [`Handler::system`]
+//!
+//! A `CHAIN` to a `trm_ref` subject first asks [`Handler::load_chain`] for
+//! the program that model emits. If the handler supplies one, it runs in
+//! place of the `CHAIN` (a `HALT` inside it returns to the caller; an `ERROR`
+//! stops the whole run); otherwise the `CHAIN` goes to [`Handler::chain`].
+//! Runs are bounded by [`Limits`].
+//!
+//! Every instruction is [validated](ExtendedInstruction::validate) before
+//! dispatch. Handler methods default to [`HandlerError::Unhandled`], so a
+//! handler only implements the categories it supports:
+//!
+//! ```rust
+//! use frame_isa::vm::{ExitStatus, Handler, HandlerResult, Vm};
+//! use frame_isa::{Action, ExtendedInstruction, Instruction, Subject};
+//!
+//! struct Echo;
+//!
+//! impl Handler for Echo {
+//!     type Output = String;
+//!
+//!     fn response(&mut self, instr: &ExtendedInstruction) -> HandlerResult<String> {
+//!         Ok(Some(instr.base.action.name().to_string()))
+//!     }
+//! }
+//!
+//! let program = [
+//!     Instruction::simple(Action::GREET, Subject::USER),
+//!     Instruction::simple(Action::NOP, Subject::NULL),
+//!     Instruction::simple(Action::HALT, Subject::NULL),
+//!     Instruction::simple(Action::THANK, Subject::USER),
+//! ];
+//! let run = Vm::new(Echo).run_instructions(&program).unwrap();
+//! assert_eq!(run.outputs, vec!["GREET"]);
+//! assert_eq!(run.status, ExitStatus::Halted { index: 2, depth: 0 });
+//! ```
+
+use crate::action::ActionCategory;
+use crate::limits::{LimitExceeded, Limits};
+use crate::program::Program;
+use crate::{Action, ExtendedInstruction, Instruction, Subject};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Result of a handler call: an optional output
+pub type HandlerResult<O> = Result<Option<O>, HandlerError>;
+
+/// Errors returned by handlers
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum HandlerError {
+    #[error("Not handled")]
+    Unhandled,
+
+    #[error("{0}")]
+    Failed(String),
+}
+
+/// Errors stopping a program run
+///
+/// `index` is the position in the program running at chain `depth` (0 for
+/// the top-level program, 1 for a program it chained to, ...).
+#[derive(Debug, Error, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VmError {
+    #[error("Instruction {index} at depth {depth}: {message}")]
+    Invalid {
+        index: usize,
+        depth: usize,
+        message: String,
+    },
+
+    #[error("Instruction {index} at depth {depth}: {action} is not in a known category")]
+    UnknownCategory {
+        index: usize,
+        depth: usize,
+        action: Action,
+    },
+
+    #[error("Instruction {index} at depth {depth}: no handler for {action}")]
+    Unhandled {
+        index: usize,
+        depth: usize,
+        action: Action,
+    },
+
+    #[error("Instruction {index} at depth {depth}: {action} failed: {message}")]
+    Handler {
+        index: usize,
+        depth: usize,
+        action: Action,
+        message: String,
+    },
+
+    #[error("Instruction {index} at depth {depth}: limit exceeded: {limit}")]
+    LimitExceeded {
+        index: usize,
+        depth: usize,
+        limit: LimitExceeded,
+    },
+}
+
+/// Per-category instruction handlers
+///
+/// Each method receives instructions of one [`ActionCategory`] and may
+/// return an output. Unimplemented categories report
+/// [`HandlerError::Unhandled`].
+pub trait Handler {
+    /// Value produced by handled instructions
+    type Output;
+
+    /// System actions other than `NOP`, `HALT` and `ERROR`
+    fn system(&mut self, instr: &ExtendedInstruction) -> HandlerResult<Self::Output> {
+        let _ = instr;
+        Err(HandlerError::Unhandled)
+    }
+
+    /// Response actions (`GREET`, `CONFIRM`, `RESPOND`, ...)
+    fn response(&mut self, instr: &ExtendedInstruction) -> HandlerResult<Self::Output> {
+        let _ = instr;
+        Err(HandlerError::Unhandled)
+    }
+
+    /// Query actions (`ASK`, `SEARCH`, `RETRIEVE`, ...)
+    fn query(&mut self, instr: &ExtendedInstruction) -> HandlerResult<Self::Output> {
+        let _ = instr;
+        Err(HandlerError::Unhandled)
+    }
+
+    /// Knowledge actions (`DEFINE`, `DESCRIBE`, `SUMMARIZE`, ...)
+    fn knowledge(&mut self, instr: &ExtendedInstruction) -> HandlerResult<Self::Output> {
+        let _ = instr;
+        Err(HandlerError::Unhandled)
+    }
+
+    /// Skill actions (`CALCULATE`, `SET_TIMER`, `KNOWLEDGE_SEARCH`)
+    fn skill(&mut self, instr: &ExtendedInstruction) -> HandlerResult<Self::Output> {
+        let _ = instr;
+        Err(HandlerError::Unhandled)
+    }
+
+    /// Emotion actions (`EMPATHY`, `CONCERN`, ...)
+    fn emotion(&mut self, instr: &ExtendedInstruction) -> HandlerResult<Self::Output> {
+        let _ = instr;
+        Err(HandlerError::Unhandled)
+    }
+
+    /// Template actions (`TEMPLATE_LOAD`, `TEMPLATE_FILL`)
+    fn template(&mut self, instr: &ExtendedInstruction) -> HandlerResult<Self::Output> {
+        let _ = instr;
+        Err(HandlerError::Unhandled)
+    }
+
+    /// Chain actions (`CHAIN`, `FORK`, `MERGE`)
+    fn chain(&mut self, instr: &ExtendedInstruction) -> HandlerResult<Self::Output> {
+        let _ = instr;
+        Err(HandlerError::Unhandled)
+    }
+
+    /// Program emitted by the TRM model a `CHAIN` instruction targets
+    ///
+    /// Returning `None` leaves the `CHAIN` to [`chain`](Self::chain).
+    fn load_chain(
+        &mut self,
+        model_id: u8,
+        instr: &ExtendedInstruction,
+    ) -> Result<Option<Vec<ExtendedInstruction>>, HandlerError> {
+        let _ = (model_id, instr);
+        Ok(None)
+    }
+}
+
+impl<H: Handler + ?Sized> Handler for &mut H {
+    type Output = H::Output;
+
+    fn system(&mut self, instr: &ExtendedInstruction) -> HandlerResult<Self::Output> {
+        (**self).system(instr)
+    }
+    fn response(&mut self, instr: &ExtendedInstruction) -> HandlerResult<Self::Output> {
+        (**self).response(instr)
+    }
+    fn query(&mut self, instr: &ExtendedInstruction) -> HandlerResult<Self::Output> {
+        (**self).query(instr)
+    }
+    fn knowledge(&mut self, instr: &ExtendedInstruction) -> HandlerResult<Self::Output> {
+        (**self).knowledge(instr)
+    }
+    fn skill(&mut self, instr: &ExtendedInstruction) -> HandlerResult<Self::Output> {
+        (**self).skill(instr)
+    }
+    fn emotion(&mut self, instr: &ExtendedInstruction) -> HandlerResult<Self::Output> {
+        (**self).emotion(instr)
+    }
+    fn template(&mut self, instr: &ExtendedInstruction) -> HandlerResult<Self::Output> {
+        (**self).template(instr)
+    }
+    fn chain(&mut self, instr: &ExtendedInstruction) -> HandlerResult<Self::Output> {
+        (**self).chain(instr)
+    }
+    fn load_chain(
+        &mut self,
+        model_id: u8,
+        instr: &ExtendedInstruction,
+    ) -> Result<Option<Vec<ExtendedInstruction>>, HandlerError> {
+        (**self).load_chain(model_id, instr)
+    }
+}
+
+/// How a program run ended
+///
+/// Positions are located like [`VmError`]'s: `index` in the program running
+/// at chain `depth`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExitStatus {
+    /// Every instruction ran
+    Completed,
+    /// A `HALT` instruction stopped the run
+    Halted { index: usize, depth: usize },
+    /// An `ERROR` instruction stopped the run; `code` is its subject
+    Error {
+        index: usize,
+        depth: usize,
+        code: Subject,
+    },
+}
+
+/// Outputs and exit status of a program run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Execution<O> {
+    /// Handler outputs, in program order
+    pub outputs: Vec<O>,
+    /// How the run ended
+    pub status: ExitStatus,
+    /// Number of instructions executed (including `NOP`, `HALT`, `ERROR` and
+    /// instructions in chained programs)
+    pub steps: usize,
+}
+
+impl<O> Execution<O> {
+    /// Check if the run completed or halted (rather than hitting `ERROR`)
+    pub fn is_success(&self) -> bool {
+        !matches!(self.status, ExitStatus::Error { .. })
+    }
+}
+
+/// Effect of executing one instruction
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step<O> {
+    /// Continue, with the handler's output if any
+    Continue(Option<O>),
+    /// Stop with this status
+    Exit(ExitStatus),
+    /// Run the program emitted by the chained model
+    Chain {
+        model_id: u8,
+        program: Vec<ExtendedInstruction>,
+    },
+}
+
+/// Instruction execution reported to a run observer
+pub(crate) struct Event<'a, O> {
+    pub index: usize,
+    pub depth: usize,
+    pub instr: &'a ExtendedInstruction,
+    pub result: &'a Result<Step<O>, VmError>,
+    pub elapsed: Duration,
+}
+
+/// Progress of a run across chained programs
+struct RunState<O> {
+    outputs: Vec<O>,
+    steps: usize,
+    open_forks: usize,
+    started: Instant,
+}
+
+/// Reference executor for instruction programs
+#[derive(Debug, Clone, Default)]
+pub struct Vm<H> {
+    handler: H,
+    limits: Limits,
+}
+
+impl<H: Handler> Vm<H> {
+    /// Create a VM dispatching to `handler`
+    pub fn new(handler: H) -> Self {
+        Self {
+            handler,
+            limits: Limits::default(),
+        }
+    }
+
+    /// Replace the execution limits
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Execution limits
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Borrow the handler
+    pub fn handler(&self) -> &H {
+        &self.handler
+    }
+
+    /// Mutably borrow the handler
+    pub fn handler_mut(&mut self) -> &mut H {
+        &mut self.handler
+    }
+
+    /// Consume the VM, returning the handler
+    pub fn into_handler(self) -> H {
+        self.handler
+    }
+
+    /// Run a program until it completes, halts, hits `ERROR` or exceeds a limit
+    pub fn run(
+        &mut self,
+        program: &[ExtendedInstruction],
+    ) -> Result<Execution<H::Output>, VmError> {
+        self.execute(program, &mut |_| {})
+    }
+
+    /// Run a program, reporting each executed instruction to `observe`
+    pub(crate) fn execute(
+        &mut self,
+        program: &[ExtendedInstruction],
+        observe: &mut dyn FnMut(Event<'_, H::Output>),
+    ) -> Result<Execution<H::Output>, VmError> {
+        let mut run = RunState {
+            outputs: Vec::new(),
+            steps: 0,
+            open_forks: 0,
+            started: Instant::now(),
+        };
+        let status = self.execute_at(program, 0, &mut run, observe)?;
+        Ok(Execution {
+            outputs: run.outputs,
+            status: status.unwrap_or(ExitStatus::Completed),
+            steps: run.steps,
+        })
+    }
+
+    /// Run a (possibly chained) program; `Some` status stops the whole run
+    fn execute_at(
+        &mut self,
+        program: &[ExtendedInstruction],
+        depth: usize,
+        run: &mut RunState<H::Output>,
+        observe: &mut dyn FnMut(Event<'_, H::Output>),
+    ) -> Result<Option<ExitStatus>, VmError> {
+        for (index, instr) in program.iter().enumerate() {
+            let started = Instant::now();
+            let result = self
+                .check_limits(index, depth, instr, run)
+                .and_then(|()| self.step_at(index, depth, instr));
+            observe(Event {
+                index,
+                depth,
+                instr,
+                result: &result,
+                elapsed: started.elapsed(),
+            });
+            match result? {
+                Step::Continue(output) => run.outputs.extend(output),
+                Step::Exit(status @ ExitStatus::Error { .. }) => return Ok(Some(status)),
+                Step::Exit(status) => return Ok((depth == 0).then_some(status)),
+                Step::Chain { program, .. } => {
+                    if let Some(status) = self.execute_at(&program, depth + 1, run, observe)? {
+                        return Ok(Some(status));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Charge one instruction against the limits
+    fn check_limits(
+        &self,
+        index: usize,
+        depth: usize,
+        instr: &ExtendedInstruction,
+        run: &mut RunState<H::Output>,
+    ) -> Result<(), VmError> {
+        let exceeded = |limit| {
+            Err(VmError::LimitExceeded {
+                index,
+                depth,
+                limit,
+            })
+        };
+        let limits = &self.limits;
+        if let Some(max) = limits.max_instructions.filter(|&max| run.steps >= max) {
+            return exceeded(LimitExceeded::Instructions { max });
+        }
+        if let Some(deadline) = limits.deadline.filter(|&d| run.started.elapsed() > d) {
+            return exceeded(LimitExceeded::Deadline { deadline });
+        }
+        let actual = instr.payload.byte_size();
+        if let Some(max) = limits.max_payload_bytes.filter(|&max| actual > max) {
+            return exceeded(LimitExceeded::PayloadSize { max, actual });
+        }
+        match instr.base.action {
+            // Refuse before the handler is asked to load the chained program
+            Action::CHAIN if instr.base.subject.trm_model_id().is_some() => {
+                if let Some(max) = limits.max_chain_depth.filter(|&max| depth >= max) {
+                    return exceeded(LimitExceeded::ChainDepth { max });
+                }
+            }
+            Action::FORK => {
+                run.open_forks += 1;
+                if let Some(max) = limits.max_fork_fan_out.filter(|&max| run.open_forks > max) {
+                    return exceeded(LimitExceeded::ForkFanOut { max });
+                }
+            }
+            Action::MERGE => run.open_forks = 0,
+            _ => {}
+        }
+        run.steps += 1;
+        Ok(())
+    }
+
+    /// Run a program of base instructions (no payloads)
+    pub fn run_instructions(
+        &mut self,
+        program: &[Instruction],
+    ) -> Result<Execution<H::Output>, VmError> {
+        let program: Vec<ExtendedInstruction> = program
+            .iter()
+            .copied()
+            .map(ExtendedInstruction::new)
+            .collect();
+        self.run(&program)
+    }
+
+    /// Run a validated [`Program`]
+    pub fn run_program(&mut self, program: &Program) -> Result<Execution<H::Output>, VmError> {
+        self.run(program.instructions())
+    }
+
+    /// Execute one instruction at position `index` of the top-level program
+    pub fn step(
+        &mut self,
+        index: usize,
+        instr: &ExtendedInstruction,
+    ) -> Result<Step<H::Output>, VmError> {
+        self.step_at(index, 0, instr)
+    }
+
+    /// Execute one instruction at position `index` of the program at chain `depth`
+    fn step_at(
+        &mut self,
+        index: usize,
+        depth: usize,
+        instr: &ExtendedInstruction,
+    ) -> Result<Step<H::Output>, VmError> {
+        let action = instr.base.action;
+        match action {
+            Action::NOP => return Ok(Step::Continue(None)),
+            Action::HALT => return Ok(Step::Exit(ExitStatus::Halted { index, depth })),
+            Action::ERROR => {
+                return Ok(Step::Exit(ExitStatus::Error {
+                    index,
+                    depth,
+                    code: instr.base.subject,
+                }))
+            }
+            _ => {}
+        }
+        instr.validate().map_err(|e| VmError::Invalid {
+            index,
+            depth,
+            message: e.to_string(),
+        })?;
+
+        let category = action.action_category().ok_or(VmError::UnknownCategory {
+            index,
+            depth,
+            action,
+        })?;
+        let handler = &mut self.handler;
+        if let (Action::CHAIN, Some(model_id)) = (action, instr.base.subject.trm_model_id()) {
+            match handler.load_chain(model_id, instr) {
+                Ok(Some(program)) => return Ok(Step::Chain { model_id, program }),
+                Ok(None) => {}
+                Err(HandlerError::Unhandled) => {
+                    return Err(VmError::Unhandled {
+                        index,
+                        depth,
+                        action,
+                    })
+                }
+                Err(HandlerError::Failed(message)) => {
+                    return Err(VmError::Handler {
+                        index,
+                        depth,
+                        action,
+                        message,
+                    })
+                }
+            }
+        }
+        let result = match category {
+            ActionCategory::System => handler.system(instr),
+            ActionCategory::Response => handler.response(instr),
+            ActionCategory::Query => handler.query(instr),
+            ActionCategory::Knowledge => handler.knowledge(instr),
+            ActionCategory::Skill => handler.skill(instr),
+            ActionCategory::Emotion => handler.emotion(instr),
+            ActionCategory::Template => handler.template(instr),
+            ActionCategory::Chain => handler.chain(instr),
+        };
+        match result {
+            Ok(output) => Ok(Step::Continue(output)),
+            Err(HandlerError::Unhandled) => Err(VmError::Unhandled {
+                index,
+                depth,
+                action,
+            }),
+            Err(HandlerError::Failed(message)) => Err(VmError::Handler {
+                index,
+                depth,
+                action,
+                message,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extended::{CalcPayload, Op, TimeUnit, TimerPayload};
+    use std::collections::HashMap;
+
+    /// Records calls; computes calc payloads; fails on DENY; chains to `models`
+    #[derive(Default)]
+    struct Recorder {
+        calls: Vec<&'static str>,
+        models: HashMap<u8, Vec<ExtendedInstruction>>,
+        loads: usize,
+    }
+
+    impl Handler for Recorder {
+        type Output = String;
+
+        fn system(&mut self, instr: &ExtendedInstruction) -> HandlerResult<String> {
+            self.calls.push("system");
+            Ok(Some(format!("status {}", instr.base.subject.name())))
+        }
+
+        fn response(&mut self, instr: &ExtendedInstruction) -> HandlerResult<String> {
+            self.calls.push("response");
+            match instr.base.action {
+                Action::DENY => Err(HandlerError::Failed("refused".to_string())),
+                action => Ok(Some(action.name().to_string())),
+            }
+        }
+
+        fn skill(&mut self, instr: &ExtendedInstruction) -> HandlerResult<String> {
+            self.calls.push("skill");
+            let calc = instr.as_calc().ok_or(HandlerError::Unhandled)?;
+            let value = calc
+                .evaluate()
+                .map_err(|e| HandlerError::Failed(e.to_string()))?;
+            Ok(Some(value.to_string()))
+        }
+
+        fn emotion(&mut self, _: &ExtendedInstruction) -> HandlerResult<String> {
+            self.calls.push("emotion");
+            Ok(None)
+        }
+
+        fn chain(&mut self, _: &ExtendedInstruction) -> HandlerResult<String> {
+            self.calls.push("chain");
+            Ok(None)
+        }
+
+        fn load_chain(
+            &mut self,
+            model_id: u8,
+            _: &ExtendedInstruction,
+        ) -> Result<Option<Vec<ExtendedInstruction>>, HandlerError> {
+            self.loads += 1;
+            Ok(self.models.get(&model_id).cloned())
+        }
+    }
+
+    fn simple(action: Action) -> ExtendedInstruction {
+        ExtendedInstruction::new(Instruction::simple(action, Subject::USER))
+    }
+
+    fn to_model(action: Action, model_id: u8) -> ExtendedInstruction {
+        ExtendedInstruction::new(Instruction::simple(action, Subject::trm_ref(model_id)))
+    }
+
+    fn limit_error<O>(result: Result<Execution<O>, VmError>) -> Option<(usize, LimitExceeded)> {
+        match result {
+            Err(VmError::LimitExceeded { index, limit, .. }) => Some((index, limit)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_dispatch_and_outputs() {
+        let calc = ExtendedInstruction::with_calc(
+            Instruction::simple(Action::CALCULATE, Subject::NUMBER),
+            CalcPayload::new(Op::Mul, 6.0, 7.0),
+        );
+        let program = [
+            simple(Action::GREET),
+            simple(Action::NOP),
+            calc,
+            simple(Action::EMPATHY),
+            simple(Action::STATUS),
+        ];
+        let mut vm = Vm::new(Recorder::default());
+        let run = vm.run(&program).unwrap();
+        assert_eq!(run.outputs, vec!["GREET", "42", "status USER"]);
+        assert_eq!(run.status, ExitStatus::Completed);
+        assert_eq!(run.steps, 5);
+        assert!(run.is_success());
+        assert_eq!(
+            vm.handler().calls,
+            vec!["response", "skill", "emotion", "system"]
+        );
+    }
+
+    #[test]
+    fn test_halt_and_error() {
+        let mut vm = Vm::new(Recorder::default());
+        let run = vm
+            .run(&[
+                simple(Action::GREET),
+                simple(Action::HALT),
+                simple(Action::THANK),
+            ])
+            .unwrap();
+        assert_eq!(run.outputs, vec!["GREET"]);
+        assert_eq!(run.status, ExitStatus::Halted { index: 1, depth: 0 });
+        assert_eq!(run.steps, 2);
+        assert!(run.is_success());
+
+        let error =
+            ExtendedInstruction::new(Instruction::simple(Action::ERROR, Subject::rag_ref(7)));
+        let run = vm
+            .run(&[simple(Action::GREET), error, simple(Action::THANK)])
+            .unwrap();
+        assert_eq!(
+            run.status,
+            ExitStatus::Error {
+                index: 1,
+                depth: 0,
+                code: Subject::rag_ref(7)
+            }
+        );
+        assert_eq!(run.outputs, vec!["GREET"]);
+        assert!(!run.is_success());
+
+        // Empty programs complete immediately
+        let run = vm.run(&[]).unwrap();
+        assert_eq!((run.status, run.steps), (ExitStatus::Completed, 0));
+    }
+
+    #[test]
+    fn test_vm_errors() {
+        let mut vm = Vm::new(Recorder::default());
+        assert_eq!(
+            vm.run(&[simple(Action::GREET), simple(Action::ASK)]),
+            Err(VmError::Unhandled {
+                index: 1,
+                depth: 0,
+                action: Action::ASK
+            })
+        );
+        assert_eq!(
+            vm.run(&[simple(Action::DENY)]),
+            Err(VmError::Handler {
+                index: 0,
+                depth: 0,
+                action: Action::DENY,
+                message: "refused".to_string()
+            })
+        );
+        assert_eq!(
+            vm.run(&[simple(Action::from_u16(0x0900))]),
+            Err(VmError::UnknownCategory {
+                index: 0,
+                depth: 0,
+                action: Action::from_u16(0x0900)
+            })
+        );
+
+        // Payloads are validated before dispatch
+        let timer_on_greet = ExtendedInstruction::with_timer(
+            Instruction::simple(Action::GREET, Subject::USER),
+            TimerPayload::new(5, TimeUnit::Minute),
+        );
+        assert!(matches!(
+            vm.run(&[timer_on_greet]),
+            Err(VmError::Invalid { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_borrowed_handler() {
+        let mut recorder = Recorder::default();
+        let run = Vm::new(&mut recorder)
+            .run_instructions(&[Instruction::simple(Action::CONFIRM, Subject::USER)])
+            .unwrap();
+        assert_eq!(run.outputs, vec!["CONFIRM"]);
+        assert_eq!(recorder.calls, vec!["response"]);
+    }
+
+    #[test]
+    fn test_chained_programs() {
+        let mut recorder = Recorder::default();
+        recorder
+            .models
+            .insert(1, vec![simple(Action::THANK), simple(Action::HALT)]);
+        recorder.models.insert(
+            2,
+            vec![simple(Action::APOLOGIZE), to_model(Action::CHAIN, 1)],
+        );
+        let mut vm = Vm::new(recorder);
+
+        // A HALT in a chained program returns to the caller
+        let run = vm
+            .run(&[
+                simple(Action::GREET),
+                to_model(Action::CHAIN, 2),
+                simple(Action::CONFIRM),
+            ])
+            .unwrap();
+        assert_eq!(run.outputs, vec!["GREET", "APOLOGIZE", "THANK", "CONFIRM"]);
+        assert_eq!(run.status, ExitStatus::Completed);
+        assert_eq!(run.steps, 7);
+
+        // Models without a program go to the chain handler
+        vm.handler_mut().calls.clear();
+        let run = vm.run(&[to_model(Action::CHAIN, 9)]).unwrap();
+        assert_eq!(run.steps, 1);
+        assert_eq!(vm.handler().calls, vec!["chain"]);
+
+        // Errors and ERROR exits are located by chain depth
+        let error = ExtendedInstruction::new(Instruction::simple(Action::ERROR, Subject::USER));
+        vm.handler_mut()
+            .models
+            .insert(3, vec![simple(Action::THANK), error]);
+        vm.handler_mut()
+            .models
+            .insert(4, vec![simple(Action::NOP), simple(Action::ASK)]);
+        let run = vm
+            .run(&[to_model(Action::CHAIN, 3), simple(Action::CONFIRM)])
+            .unwrap();
+        assert_eq!(
+            run.status,
+            ExitStatus::Error {
+                index: 1,
+                depth: 1,
+                code: Subject::USER
+            }
+        );
+        assert_eq!(
+            vm.run(&[simple(Action::GREET), to_model(Action::CHAIN, 4)]),
+            Err(VmError::Unhandled {
+                index: 1,
+                depth: 1,
+                action: Action::ASK
+            })
+        );
+    }
+
+    #[test]
+    fn test_chain_cycle() {
+        let mut recorder = Recorder::default();
+        recorder.models.insert(1, vec![to_model(Action::CHAIN, 2)]);
+        recorder.models.insert(2, vec![to_model(Action::CHAIN, 1)]);
+
+        let mut vm = Vm::new(recorder).with_limits(Limits::default().with_max_chain_depth(4));
+        assert_eq!(
+            vm.run(&[to_model(Action::CHAIN, 1)]),
+            Err(VmError::LimitExceeded {
+                index: 0,
+                depth: 4,
+                limit: LimitExceeded::ChainDepth { max: 4 }
+            })
+        );
+        // The chain over the limit is refused before its program is loaded
+        assert_eq!(vm.handler().loads, 4);
+
+        // Without a depth limit the instruction budget still stops the cycle
+        let limits = Limits::unlimited().with_max_instructions(50);
+        let mut vm = Vm::new(vm.into_handler()).with_limits(limits);
+        assert_eq!(
+            vm.run(&[to_model(Action::CHAIN, 1)]),
+            Err(VmError::LimitExceeded {
+                index: 0,
+                depth: 50,
+                limit: LimitExceeded::Instructions { max: 50 }
+            })
+        );
+    }
+
+    #[test]
+    fn test_instruction_and_payload_limits() {
+        let limits = Limits::default().with_max_instructions(2);
+        let mut vm = Vm::new(Recorder::default()).with_limits(limits);
+        assert!(vm
+            .run(&[simple(Action::GREET), simple(Action::THANK)])
+            .is_ok());
+        assert_eq!(
+            limit_error(vm.run(&[
+                simple(Action::GREET),
+                simple(Action::NOP),
+                simple(Action::THANK)
+            ])),
+            Some((2, LimitExceeded::Instructions { max: 2 }))
+        );
+
+        let calc = ExtendedInstruction::with_calc(
+            Instruction::simple(Action::CALCULATE, Subject::NUMBER),
+            CalcPayload::new(Op::Add, 1.0, 2.0),
+        );
+        let actual = calc.payload.byte_size();
+        let mut vm = Vm::new(Recorder::default())
+            .with_limits(Limits::default().with_max_payload_bytes(actual - 1));
+        assert_eq!(
+            limit_error(vm.run(&[simple(Action::GREET), calc])),
+            Some((
+                1,
+                LimitExceeded::PayloadSize {
+                    max: actual - 1,
+                    actual
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_fork_fan_out() {
+        let limits = Limits::default().with_max_fork_fan_out(2);
+        let mut vm = Vm::new(Recorder::default()).with_limits(limits);
+        let run = vm
+            .run(&[
+                to_model(Action::FORK, 1),
+                to_model(Action::FORK, 2),
+                simple(Action::MERGE),
+                to_model(Action::FORK, 3),
+                to_model(Action::FORK, 4),
+                simple(Action::MERGE),
+            ])
+            .unwrap();
+        assert_eq!(run.steps, 6);
+
+        let forks = [1, 2, 3].map(|model_id| to_model(Action::FORK, model_id));
+        assert_eq!(
+            limit_error(vm.run(&forks)),
+            Some((2, LimitExceeded::ForkFanOut { max: 2 }))
+        );
+
+        // Forks left open stay open inside a chained program
+        vm.handler_mut()
+            .models
+            .insert(5, vec![to_model(Action::FORK, 2)]);
+        assert_eq!(
+            limit_error(vm.run(&[
+                to_model(Action::FORK, 1),
+                to_model(Action::CHAIN, 5),
+                to_model(Action::FORK, 3),
+            ])),
+            Some((2, LimitExceeded::ForkFanOut { max: 2 }))
+        );
+    }
+
+    #[test]
+    fn test_deadline() {
+        let limits = Limits::default().with_deadline(Duration::ZERO);
+        let mut vm = Vm::new(Recorder::default()).with_limits(limits);
+        // The clock has moved on by the second instruction at the latest
+        let result = vm.run(&[simple(Action::GREET), simple(Action::THANK)]);
+        assert!(matches!(
+            limit_error(result),
+            Some((_, LimitExceeded::Deadline { .. }))
+        ));
+    }
+}